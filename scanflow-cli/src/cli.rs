@@ -1,27 +1,80 @@
 use memflow::prelude::v1::*;
 
+use std::borrow::Cow;
+use std::collections::{BTreeMap, VecDeque};
 use std::convert::TryInto;
-use std::io::Write;
-use std::sync::mpsc::{channel, Receiver};
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::sync::{Mutex, MutexGuard, OnceLock};
 use std::thread;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use scanflow::{
-    disasm::Disasm, pointer_map::PointerMap, sigmaker::Sigmaker, value_scanner::ValueScanner,
+    disasm::{code_and_data_sections, code_and_rodata_sections, Disasm, RefKind, SectionFilter},
+    error::{Error, Result},
+    export::{address_label, chains_to_cheat_engine, chains_to_dot, offset_label},
+    pointer_map::{deref_targets, rescan_chains, PointerMap, PointerPath},
+    regions::{classify_regions, regions_of_class, RegionClass},
+    sigmaker::{SigFormat, SigScope, Sigmaker, DEFAULT_MAX_SIG_LENGTH},
+    task::CancelToken,
+    value_scanner::{
+        default_scan_range, process_maps, CaseFolding, DeltaKind, FloatMatch, MapsFn, MergeOp,
+        ScanEstimate, ScanPredicate, SeqPred, ValueScanner,
+    },
+    value_type::{int_range, int_width, is_integer, parse_value, print_value, ValueType, TYPES},
+    writer::{write_pattern_with, write_typed_batch, WritePattern},
 };
+use serde::{Deserialize, Serialize};
+
+use crate::interrupt;
+use crate::session_log::SessionLog;
 
 pub const MAX_PRINT: usize = 16;
 
+/// Number of results kept in the CLI history.
+pub const HISTORY_LEN: usize = 64;
+
+/// Command result kept in the CLI history.
+#[derive(Clone)]
+pub enum HistoryItem {
+    /// Pointer chain from a static address to a match.
+    Path(PointerPath),
+    /// Code signature within a module.
+    Signature(String, String),
+}
+
+impl fmt::Display for HistoryItem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HistoryItem::Path(path) => match &path.root_kind {
+                Some(kind) => write!(f, "{} [{}]", path, kind),
+                None => write!(f, "{}", path),
+            },
+            HistoryItem::Signature(module, sig) => write!(f, "{}: {}", module, sig),
+        }
+    }
+}
+
 pub struct Funcs<T> {
-    maps: fn(&mut T, imem, Address, Address) -> Vec<MemoryRange>,
+    maps: MapsFn<T>,
     info: fn(&T) -> &str,
+    modules: fn(&mut T) -> Vec<ModuleInfo>,
+    /// Lists the regions of the module with the given name, even if modules are skipped.
+    module_regions: fn(&mut T, &str) -> Result<Vec<MemoryRange>>,
+    /// Picks `maps` according to the scanner settings, if modules can be skipped.
+    maps_for: Option<fn(&ValueScanner) -> MapsFn<T>>,
 }
 
 impl<T: Process + MemoryView> Funcs<T> {
     fn process() -> Self {
         Self {
-            maps: |proc, gap_size, from, to| proc.mapped_mem_range_vec(gap_size, from, to),
+            maps: process_maps,
             info: |proc| &proc.info().name,
+            modules: |proc| proc.module_list().unwrap_or_default(),
+            module_regions: ValueScanner::module_regions,
+            maps_for: Some(|scanner| scanner.process_maps_fn()),
         }
     }
 }
@@ -43,6 +96,9 @@ impl<T: MemoryView> Funcs<T> {
                 }
             },
             info: |_| "view",
+            modules: |_| vec![],
+            module_regions: |_, name| Err(Error::ModuleNotFound(name.into())),
+            maps_for: None,
         }
     }
 }
@@ -54,14 +110,98 @@ impl<T> Clone for Funcs<T> {
 }
 impl<T> Copy for Funcs<T> {}
 
+/// Last scan or filter performed, so that it can be repeated.
+#[derive(Clone)]
+enum LastScan {
+    /// Scan for a value of the given type.
+    Value(Box<[u8]>, String),
+    /// Filter by how the value changed.
+    Delta(DeltaKind),
+    /// Filter by whether the value is zero.
+    Zero(bool),
+    /// Scan for an integer of the given type within a range.
+    Range(i128, i128, String),
+    /// Scan for a number of the given type passing a comparison.
+    Cmp(ScanPredicate, String),
+    /// Scan for a byte pattern with wildcards.
+    Aob(Vec<Option<u8>>),
+    /// Scan for a float of the given type within a tolerance of a value.
+    FloatNear(f64, f64, String),
+    /// Filter by a sequence of samples, taken the given number of times, and interval apart.
+    Sequence(SeqPred, usize, Duration),
+}
+
+/// Version of the session file format, bumped on incompatible changes.
+const SESSION_VERSION: u32 = 1;
+
+/// State saved by `session save`.
+#[derive(Serialize)]
+struct SessionRef<'a> {
+    version: u32,
+    value_scanner: &'a ValueScanner,
+    typename: &'a Option<String>,
+    buf_len: usize,
+    pointer_map: &'a PointerMap,
+    disasm: &'a Disasm,
+    labels: &'a BTreeMap<Address, String>,
+    saved_sets: &'a BTreeMap<String, ValueScanner>,
+}
+
+/// State restored by `session load`, the owned counterpart of `SessionRef`.
+#[derive(Deserialize)]
+struct Session {
+    version: u32,
+    value_scanner: ValueScanner,
+    typename: Option<String>,
+    buf_len: usize,
+    pointer_map: PointerMap,
+    disasm: Disasm,
+    labels: BTreeMap<Address, String>,
+    saved_sets: BTreeMap<String, ValueScanner>,
+}
+
+/// Result set that can be paged through with `more`.
+#[derive(Clone, Copy)]
+enum Paged {
+    /// Value scanner matches.
+    Matches,
+    /// Pointer chains of the last offset scan.
+    Chains,
+}
+
 /// Scanflow CLI context.
 pub struct CliCtx<T> {
     memory: T,
+    /// Earlier snapshot of the memory, to `diff` against.
+    baseline: Option<T>,
     value_scanner: ValueScanner,
     typename: Option<String>,
     buf_len: usize,
+    case_insensitive: bool,
+    /// Text format signatures are printed in.
+    sig_format: SigFormat,
+    /// Code signatures are checked to be unique within.
+    sig_scope: SigScope,
+    float_match: FloatMatch,
+    scan_threshold: Option<umem>,
+    regions: Option<Vec<MemoryRange>>,
     disasm: Disasm,
+    sigmaker: Sigmaker,
     pointer_map: PointerMap,
+    history: VecDeque<(usize, HistoryItem)>,
+    history_next: usize,
+    chains: Vec<PointerPath>,
+    labels: BTreeMap<Address, String>,
+    /// Result sets saved with `merge save`.
+    saved_sets: BTreeMap<String, ValueScanner>,
+    last_scan: Option<LastScan>,
+    write_interval: Duration,
+    compact_prompt: bool,
+    phases: Vec<(&'static str, Duration)>,
+    /// Last printed result set, and the index of its next unprinted item.
+    paging: Option<(Paged, usize)>,
+    /// Set while `auto` repeats scans, which then only print the number of matches.
+    auto_scan: bool,
     funcs: Funcs<T>,
 }
 
@@ -69,14 +209,697 @@ impl<T> CliCtx<T> {
     fn new(memory: T, funcs: Funcs<T>) -> Self {
         Self {
             memory,
+            baseline: None,
             value_scanner: Default::default(),
             typename: None,
             buf_len: 0,
+            case_insensitive: false,
+            sig_format: SigFormat::Ida,
+            sig_scope: SigScope::Module,
+            float_match: FloatMatch::Exact,
+            scan_threshold: None,
+            regions: None,
             disasm: Default::default(),
+            sigmaker: Default::default(),
             pointer_map: Default::default(),
+            history: VecDeque::new(),
+            history_next: 0,
+            chains: vec![],
+            labels: BTreeMap::new(),
+            saved_sets: BTreeMap::new(),
+            last_scan: None,
+            write_interval: Duration::from_millis(0),
+            compact_prompt: false,
+            phases: vec![],
+            paging: None,
+            auto_scan: false,
             funcs,
         }
     }
+
+    /// Save the scan state to a file.
+    ///
+    /// This covers the matches, the pointer map, the collected globals, the current type, labels,
+    /// and saved result sets. Settings are not restored by `load_session`.
+    fn save_session(&self, path: &str) -> Result<()> {
+        let session = SessionRef {
+            version: SESSION_VERSION,
+            value_scanner: &self.value_scanner,
+            typename: &self.typename,
+            buf_len: self.buf_len,
+            pointer_map: &self.pointer_map,
+            disasm: &self.disasm,
+            labels: &self.labels,
+            saved_sets: &self.saved_sets,
+        };
+
+        let file = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(file, &session).map_err(|e| Error::Io(e.to_string()))
+    }
+
+    /// Restore the scan state saved with `save_session`, replacing the current one.
+    ///
+    /// The current settings are kept, thus the memory regions are listed as before.
+    fn load_session(&mut self, path: &str) -> Result<()> {
+        let file = BufReader::new(File::open(path)?);
+        let session: Session =
+            serde_json::from_reader(file).map_err(|e| Error::InvalidFormat(e.to_string()))?;
+
+        if session.version != SESSION_VERSION {
+            return Err(Error::InvalidFormat(format!(
+                "unsupported version {} (expected {})",
+                session.version, SESSION_VERSION
+            )));
+        }
+
+        // Tokens are not saved, and Ctrl-C has to keep cancelling the restored state.
+        let mut value_scanner = session.value_scanner;
+        value_scanner.set_config(&self.value_scanner.config());
+        value_scanner.set_cancel_token(self.value_scanner.cancel_token().cloned());
+        let mut pointer_map = session.pointer_map;
+        pointer_map.set_read_retries(self.pointer_map.read_retries());
+        pointer_map.set_timeout(self.pointer_map.timeout());
+        pointer_map.set_chunk_size(self.pointer_map.chunk_size());
+        pointer_map.set_min_region_size(self.pointer_map.min_region_size());
+        pointer_map.set_max_region_size(self.pointer_map.max_region_size());
+        pointer_map.set_pointer_size(self.pointer_map.pointer_size_override());
+        pointer_map.set_cancel_token(self.pointer_map.cancel_token().cloned());
+        let mut disasm = session.disasm;
+        disasm.set_cancel_token(self.disasm.cancel_token().cloned());
+
+        self.value_scanner = value_scanner;
+        self.typename = session.typename;
+        self.buf_len = session.buf_len;
+        self.pointer_map = pointer_map;
+        self.disasm = disasm;
+        self.labels = session.labels;
+        self.saved_sets = session.saved_sets;
+        self.chains.clear();
+        self.last_scan = None;
+        self.paging = None;
+
+        Ok(())
+    }
+
+    /// Record the time a phase of the current command took, since `start`.
+    fn phase(&mut self, name: &'static str, start: Instant) {
+        self.phases.push((name, start.elapsed()));
+    }
+
+    /// Log the time every phase of the last command took, and start over.
+    fn log_phases(&mut self) {
+        if self.phases.is_empty() {
+            return;
+        }
+
+        let breakdown = self
+            .phases
+            .drain(..)
+            .map(|(name, time)| format!("{} {:.2}ms", name, time.as_secs_f64() * 1000.0))
+            .collect::<Vec<_>>();
+
+        log::info!("Timing: {}", breakdown.join(", "));
+    }
+
+    /// Build the prompt, showing the scan state unless the prompt is compact.
+    fn prompt(&self) -> String {
+        let mut prompt = format!("scanflow@{}", (self.funcs.info)(&self.memory));
+
+        if !self.compact_prompt {
+            if let Some(tn) = &self.typename {
+                prompt += &format!(" [{}]", tn);
+            }
+
+            if let Some(filter) = self.value_scanner.page_filter() {
+                prompt += &format!(" (pf {})", page_filter_name(filter));
+            }
+
+            if self.value_scanner.scanned() {
+                prompt += &format!(" ({} matches)", self.value_scanner.matches().len());
+            }
+        }
+
+        prompt + " >> "
+    }
+
+    /// Store a result in the history, and print it alongside its history index.
+    fn remember(&mut self, item: HistoryItem) {
+        println!("[{}] {}", self.history_next, item);
+
+        if self.history.len() >= HISTORY_LEN {
+            self.history.pop_front();
+        }
+
+        self.history.push_back((self.history_next, item));
+        self.history_next += 1;
+    }
+
+    /// Get the results added to the history since the given index, formatted for display.
+    fn history_since(&self, start: usize) -> Vec<String> {
+        self.history
+            .iter()
+            .filter(|(i, _)| *i >= start)
+            .map(|(_, item)| item.to_string())
+            .collect()
+    }
+
+    /// Find a result in the history by its index.
+    fn recall(&self, arg: &str) -> Result<&HistoryItem> {
+        let idx = arg
+            .trim()
+            .parse::<usize>()
+            .map_err(|_| Error::InvalidArgument(format!("`{}` is not a valid index", arg)))?;
+
+        self.history
+            .iter()
+            .find(|(i, _)| *i == idx)
+            .map(|(_, item)| item)
+            .ok_or(Error::InvalidIndex(idx))
+    }
+}
+
+impl<T: MemoryView + Clone> CliCtx<T> {
+    /// Get the regions of the initial scan, taking the region selection into account.
+    ///
+    /// Once scanned, no regions are needed, since scans only filter the matches.
+    fn scan_regions(&mut self) -> Vec<MemoryRange> {
+        match &self.regions {
+            Some(regions) if !self.value_scanner.scanned() => regions.clone(),
+            _ => self
+                .value_scanner
+                .initial_regions(&mut self.memory, self.funcs.maps),
+        }
+    }
+
+    /// Estimate the initial value scan, taking the region selection into account.
+    fn estimate(&mut self) -> ScanEstimate {
+        let regions = self.scan_regions();
+        self.value_scanner.estimate_in_regions(regions)
+    }
+
+    /// Pick how values of the given type are compared.
+    fn set_case_folding(&mut self, typename: &str) {
+        self.value_scanner.set_case_folding(match typename {
+            "str" if self.case_insensitive => CaseFolding::Ascii,
+            "str_utf16" if self.case_insensitive => CaseFolding::Utf16,
+            _ => CaseFolding::Exact,
+        });
+    }
+
+    /// Run a scan aligned as set, or to the natural alignment of the type.
+    ///
+    /// An alignment of 0 in the scanner settings stands for `auto`, which is applied for the
+    /// duration of the scan only, thus the setting is left as is.
+    fn with_alignment<R>(
+        &mut self,
+        typename: &str,
+        size: usize,
+        scan: impl FnOnce(&mut Self) -> R,
+    ) -> R {
+        let auto = self.value_scanner.config().alignment == 0;
+
+        if auto {
+            self.value_scanner
+                .set_alignment(natural_alignment(typename, size));
+        }
+
+        let ret = scan(self);
+
+        if auto {
+            self.value_scanner.set_alignment(0);
+        }
+
+        ret
+    }
+
+    /// Scan for data, taking the region selection, case sensitivity, alignment, and float matching
+    /// into account.
+    fn scan_for(&mut self, data: &[u8], typename: &str) -> Result<()> {
+        self.set_case_folding(typename);
+
+        // Values are in the byte order of the target.
+        let mut native = data.to_vec();
+        ValueType::find(typename)?
+            .convert_byte_order(&mut native, self.memory.metadata().little_endian);
+
+        let float = match typename {
+            "f32" => native
+                .try_into()
+                .ok()
+                .map(f32::from_ne_bytes)
+                .map(f64::from),
+            "f64" => native.try_into().ok().map(f64::from_ne_bytes),
+            _ => None,
+        };
+
+        if let Some(value) = float.filter(|_| self.float_match != FloatMatch::Exact) {
+            let (width, mode) = (data.len(), self.float_match);
+
+            let regions = self.scan_regions();
+            return self.value_scanner.scan_for_float_in_regions(
+                &mut self.memory,
+                regions,
+                value,
+                width,
+                mode,
+            );
+        }
+
+        let regions = self.scan_regions();
+        self.with_alignment(typename, data.len(), |ctx| {
+            ctx.value_scanner
+                .scan_for_in_regions(&mut ctx.memory, regions, data)
+        })
+    }
+
+    /// Select the regions of a module for the initial scan of `--module {name} {input}`.
+    ///
+    /// Returns the input, and the previous region selection, to restore after the scan.
+    fn select_module<'a>(&mut self, args: &'a str) -> Result<(&'a str, Option<Vec<MemoryRange>>)> {
+        let (name, input) = args
+            .trim()
+            .split_once(char::is_whitespace)
+            .ok_or_else(|| Error::InvalidArgument("expected a module name, and a value".into()))?;
+
+        let regions = if self.value_scanner.scanned() {
+            self.regions.clone()
+        } else {
+            Some((self.funcs.module_regions)(&mut self.memory, name)?)
+        };
+
+        Ok((input.trim(), std::mem::replace(&mut self.regions, regions)))
+    }
+
+    /// Change the width of the values, re-reading the matches if it differs.
+    fn set_width(&mut self, buf_len: usize) {
+        if buf_len != self.buf_len {
+            let invalid = self.value_scanner.reinterpret(&mut self.memory, buf_len);
+
+            if invalid > 0 {
+                println!(
+                    "Warning: {} of {} matches can not hold a {} byte value, their values are likely garbage",
+                    invalid,
+                    self.value_scanner.matches().len(),
+                    buf_len
+                );
+            }
+        }
+
+        self.buf_len = buf_len;
+    }
+
+    /// Print the number of matches, and the first page of them.
+    fn print_matches(&mut self, typename: &str) -> Result<()> {
+        println!("Matches found: {}", self.value_scanner.matches().len());
+
+        if self.value_scanner.timed_out() {
+            println!("Warning: the scan timed out, or was interrupted, matches are incomplete");
+        }
+
+        if self.auto_scan {
+            return Ok(());
+        }
+
+        self.print_page(Paged::Matches, typename, 0, MAX_PRINT)
+    }
+
+    /// Repeat the last scan or filter on the current matches.
+    fn repeat_scan(&mut self) -> Result<()> {
+        match self.last_scan.clone() {
+            Some(LastScan::Value(buf, t)) => self.scan_value(buf, t),
+            Some(LastScan::Delta(delta)) => self.filter_delta(delta),
+            Some(LastScan::Zero(zero)) => self.filter_zero(zero),
+            Some(LastScan::Range(lo, hi, t)) => self.scan_range(lo, hi, t),
+            Some(LastScan::Cmp(pred, t)) => self.scan_cmp(pred, t),
+            Some(LastScan::Aob(pattern)) => self.scan_aob(pattern),
+            Some(LastScan::FloatNear(value, eps, t)) => self.scan_float_near(value, eps, t),
+            Some(LastScan::Sequence(pred, samples, interval)) => {
+                self.filter_sequence(pred, samples, interval)
+            }
+            None => Err(Error::NotScanned),
+        }
+    }
+
+    /// Print up to `count` items of a result set, starting at `start`.
+    ///
+    /// Matches are printed with their values, while pointer chains are put in the history.
+    fn print_page(
+        &mut self,
+        paged: Paged,
+        typename: &str,
+        start: usize,
+        count: usize,
+    ) -> Result<()> {
+        let total = match paged {
+            Paged::Matches => self.value_scanner.matches().len(),
+            Paged::Chains => self.chains.len(),
+        };
+
+        let end = std::cmp::min(start.saturating_add(count), total);
+
+        match paged {
+            Paged::Matches if start < end => {
+                let matches = self
+                    .value_scanner
+                    .matches_with_values()
+                    .skip(start)
+                    .take(end - start)
+                    .collect::<Vec<_>>();
+
+                print_matches(
+                    &matches,
+                    &mut self.memory,
+                    self.buf_len,
+                    typename,
+                    &self.labels,
+                )?
+            }
+            Paged::Chains if start < end => {
+                let chains = self.chains[start..end].to_vec();
+
+                for chain in chains {
+                    self.remember(HistoryItem::Path(chain));
+                }
+            }
+            _ => {}
+        }
+
+        let end = std::cmp::max(start, end);
+        self.paging = Some((paged, end));
+
+        if end < total {
+            println!("{} more, use `more` to print them", total - end);
+        }
+
+        Ok(())
+    }
+
+    /// Scan for, or filter by a value, and print the matches.
+    fn scan_value(&mut self, buf: Box<[u8]>, typename: String) -> Result<()> {
+        if !self.value_scanner.scanned() && !confirm_scan(self.scan_threshold, || self.estimate()) {
+            return Ok(());
+        }
+
+        self.buf_len = buf.len();
+        let start = Instant::now();
+        self.scan_for(&buf, &typename)?;
+        self.phase("scan", start);
+        self.print_matches(&typename)?;
+        self.typename = Some(typename.clone());
+        self.last_scan = Some(LastScan::Value(buf, typename));
+
+        Ok(())
+    }
+
+    /// Scan for, or filter by a range of integers, and print the matches.
+    fn scan_range(&mut self, lo: i128, hi: i128, typename: String) -> Result<()> {
+        let width = int_width(&typename).ok_or_else(|| {
+            Error::InvalidArgument(format!(
+                "range scans require an integer type, not `{}`",
+                typename
+            ))
+        })?;
+
+        if !self.value_scanner.scanned() && !confirm_scan(self.scan_threshold, || self.estimate()) {
+            return Ok(());
+        }
+
+        // Integers of either sign are signed, unless the range is out of reach for signed values.
+        let signed = match typename.as_bytes()[0] {
+            b'i' => true,
+            b'u' => false,
+            _ => hi < 1 << (width * 8 - 1),
+        };
+
+        let start = Instant::now();
+
+        let regions = self.scan_regions();
+        self.value_scanner.scan_for_range_in_regions(
+            &mut self.memory,
+            regions,
+            lo,
+            hi,
+            width,
+            signed,
+        )?;
+
+        self.phase("scan", start);
+        self.buf_len = width;
+        self.print_matches(&typename)?;
+        self.typename = Some(typename.clone());
+        self.last_scan = Some(LastScan::Range(lo, hi, typename));
+
+        Ok(())
+    }
+
+    /// Scan for, or filter by numbers passing a comparison, and print the matches.
+    fn scan_cmp(&mut self, pred: ScanPredicate, typename: String) -> Result<()> {
+        if !self.value_scanner.scanned() && !confirm_scan(self.scan_threshold, || self.estimate()) {
+            return Ok(());
+        }
+
+        let start = Instant::now();
+
+        let regions = self.scan_regions();
+        self.value_scanner
+            .scan_for_cmp_in_regions(&mut self.memory, regions, &typename, &pred)?;
+
+        self.phase("scan", start);
+        self.buf_len = ValueType::find(&typename)?.1.unwrap_or(0);
+        self.print_matches(&typename)?;
+        self.typename = Some(typename.clone());
+        self.last_scan = Some(LastScan::Cmp(pred, typename));
+
+        Ok(())
+    }
+
+    /// Scan for, or filter by a float within a tolerance of a value, and print the matches.
+    ///
+    /// The tolerance applies to this scan only, unlike the one set with `float`.
+    fn scan_float_near(&mut self, value: f64, eps: f64, typename: String) -> Result<()> {
+        let width = match typename.as_str() {
+            "f32" => 4,
+            "f64" => 8,
+            _ => {
+                return Err(Error::InvalidArgument(format!(
+                    "tolerance scans require a float type, not `{}`",
+                    typename
+                )))
+            }
+        };
+
+        if !self.value_scanner.scanned() && !confirm_scan(self.scan_threshold, || self.estimate()) {
+            return Ok(());
+        }
+
+        let mode = FloatMatch::Epsilon(eps);
+        let start = Instant::now();
+
+        let regions = self.scan_regions();
+        self.value_scanner.scan_for_float_in_regions(
+            &mut self.memory,
+            regions,
+            value,
+            width,
+            mode,
+        )?;
+
+        self.phase("scan", start);
+        self.buf_len = width;
+        self.print_matches(&typename)?;
+        self.typename = Some(typename.clone());
+        self.last_scan = Some(LastScan::FloatNear(value, eps, typename));
+
+        Ok(())
+    }
+
+    /// Scan for, or filter by a byte pattern with wildcards, and print the matches.
+    fn scan_aob(&mut self, pattern: Vec<Option<u8>>) -> Result<()> {
+        if !self.value_scanner.scanned() && !confirm_scan(self.scan_threshold, || self.estimate()) {
+            return Ok(());
+        }
+
+        let start = Instant::now();
+
+        let regions = self.scan_regions();
+        self.value_scanner
+            .scan_aob_in_regions(&mut self.memory, regions, &pattern)?;
+
+        self.phase("scan", start);
+        self.buf_len = pattern.len();
+        self.print_matches("bytes")?;
+        self.typename = Some("bytes".into());
+        self.last_scan = Some(LastScan::Aob(pattern));
+
+        Ok(())
+    }
+
+    /// Take a snapshot of all values of an integer type, and print the number of matches.
+    fn scan_unknown(&mut self, typename: String) -> Result<()> {
+        let width = int_width(&typename).filter(|&w| w <= 8).ok_or_else(|| {
+            Error::InvalidArgument(format!(
+                "unknown value scans require an integer type of up to 8 bytes, not `{}`",
+                typename
+            ))
+        })?;
+
+        if !confirm_scan(self.scan_threshold, || self.estimate()) {
+            return Ok(());
+        }
+
+        let start = Instant::now();
+
+        let regions = match &self.regions {
+            Some(regions) => regions.clone(),
+            None => ValueScanner::memory_regions(&mut self.memory, self.funcs.maps),
+        };
+        self.value_scanner
+            .scan_unknown_in_regions(&mut self.memory, regions, width)?;
+
+        self.phase("scan", start);
+        self.buf_len = width;
+        self.print_matches(&typename)?;
+        self.typename = Some(typename);
+        self.last_scan = None;
+
+        Ok(())
+    }
+
+    /// Find the values of a type that differ from the baseline snapshot, and print the matches.
+    fn diff_baseline(&mut self, typename: String) -> Result<()> {
+        let width = ValueType::find(&typename)?.1.ok_or_else(|| {
+            Error::InvalidArgument(format!(
+                "diffs require a type of fixed size, not `{}`",
+                typename
+            ))
+        })?;
+
+        let baseline = self.baseline.as_mut().ok_or_else(|| {
+            Error::InvalidArgument("no baseline snapshot, pass one with `--baseline`".into())
+        })?;
+
+        let start = Instant::now();
+
+        let regions = match &self.regions {
+            Some(regions) => regions.clone(),
+            None => ValueScanner::common_regions(
+                baseline,
+                self.funcs.maps,
+                &mut self.memory,
+                self.funcs.maps,
+            ),
+        };
+        self.value_scanner
+            .diff_in_regions(baseline, &mut self.memory, regions, width)?;
+
+        self.phase("scan", start);
+        self.buf_len = width;
+        self.print_matches(&typename)?;
+        self.typename = Some(typename);
+        self.last_scan = None;
+
+        Ok(())
+    }
+
+    /// Filter by how the value changed, and print the matches.
+    fn filter_delta(&mut self, delta: DeltaKind) -> Result<()> {
+        let t = match &self.typename {
+            Some(t) if is_integer(t) => t.clone(),
+            Some(t) => {
+                return Err(Error::InvalidArgument(format!(
+                    "delta scans require an integer type, not `{}`",
+                    t
+                )))
+            }
+            None => return Err(Error::NotScanned),
+        };
+
+        let start = Instant::now();
+        self.value_scanner
+            .filter_delta(&mut self.memory, delta, self.buf_len)?;
+        self.phase("filter", start);
+        self.last_scan = Some(LastScan::Delta(delta));
+
+        self.print_matches(&t)
+    }
+
+    /// Filter by how the value changes over several samples, and print the matches.
+    fn filter_sequence(&mut self, pred: SeqPred, samples: usize, interval: Duration) -> Result<()> {
+        let t = match &self.typename {
+            Some(t) if is_integer(t) => t.clone(),
+            Some(t) => {
+                return Err(Error::InvalidArgument(format!(
+                    "sequence scans require an integer type, not `{}`",
+                    t
+                )))
+            }
+            None => return Err(Error::NotScanned),
+        };
+
+        let start = Instant::now();
+        self.value_scanner.scan_for_sequence(
+            &mut self.memory,
+            self.buf_len,
+            samples,
+            interval,
+            pred,
+        )?;
+        self.phase("filter", start);
+        self.last_scan = Some(LastScan::Sequence(pred, samples, interval));
+
+        self.print_matches(&t)
+    }
+
+    /// Filter by whether the value is zero at the current width, and print the matches.
+    fn filter_zero(&mut self, zero: bool) -> Result<()> {
+        let t = self.typename.clone().ok_or(Error::NotScanned)?;
+        let start = Instant::now();
+
+        if zero {
+            self.value_scanner
+                .filter_zero(&mut self.memory, self.buf_len)?;
+        } else {
+            self.value_scanner
+                .filter_nonzero(&mut self.memory, self.buf_len)?;
+        }
+
+        self.phase("filter", start);
+
+        self.last_scan = Some(LastScan::Zero(zero));
+
+        self.print_matches(&t)
+    }
+
+    /// Count matches of data, taking the region selection and case sensitivity into account.
+    fn count_matches(&mut self, data: &[u8], typename: &str) -> Result<usize> {
+        self.set_case_folding(typename);
+
+        let regions = self.scan_regions();
+        self.with_alignment(typename, data.len(), |ctx| {
+            ctx.value_scanner
+                .count_matches_in_regions(&mut ctx.memory, regions, data)
+        })
+    }
+}
+
+impl<T: Process + MemoryView + Clone> CliCtx<T> {
+    /// Collect the globals of all modules, unless a complete collection is present.
+    ///
+    /// Returns whether the globals are complete, which they are not if collecting them was
+    /// interrupted, in which case a warning is printed.
+    fn ensure_globals(&mut self) -> Result<bool> {
+        if !self.disasm.complete() {
+            let start = Instant::now();
+            self.disasm.collect_globals(&mut self.memory, None)?;
+            self.phase("collect_globals", start);
+        }
+
+        if !self.disasm.complete() {
+            println!("Warning: collecting globals was interrupted, globals are incomplete");
+        }
+
+        Ok(self.disasm.complete())
+    }
 }
 
 /// Scanflow command.
@@ -100,31 +923,35 @@ pub struct CmdDef<'a, T> {
     short: &'a str,
     invoke: CmdHandler<T>,
     help: &'a str,
-    long_help: Option<&'a str>,
+    long_help: Option<Cow<'a, str>>,
 }
 
 impl<'a, T> Clone for CmdDef<'a, T> {
     fn clone(&self) -> Self {
-        *self
+        Self {
+            long: self.long,
+            short: self.short,
+            invoke: self.invoke,
+            help: self.help,
+            long_help: self.long_help.clone(),
+        }
     }
 }
 
-impl<'a, T> Copy for CmdDef<'a, T> {}
-
 impl<'a, T> CmdDef<'a, T> {
     fn new(
         long: &'a str,
         short: &'a str,
         handle: CmdHandler<T>,
         help: &'a str,
-        long_help: Option<&'a str>,
+        long_help: Option<impl Into<Cow<'a, str>>>,
     ) -> Self {
         Self {
             long,
             short,
             invoke: handle,
             help,
-            long_help,
+            long_help: long_help.map(Into::into),
         }
     }
 }
@@ -149,10 +976,12 @@ fn view_cmds<'a, T: MemoryView + Clone>() -> impl IntoIterator<Item = CmdDef<'a,
                 ctx.disasm.reset();
                 ctx.pointer_map.reset();
                 ctx.typename = None;
+                ctx.regions = None;
+                ctx.last_scan = None;
                 Ok(())
             },
             "reset all context state",
-            None,
+            None::<&str>,
         ),
         CmdDef::<T>::new(
             "reinterpret",
@@ -161,215 +990,2185 @@ fn view_cmds<'a, T: MemoryView + Clone>() -> impl IntoIterator<Item = CmdDef<'a,
                 let mut split = arg.split_whitespace();
 
                 let (arg, len) = (
-                    split.next().ok_or(ErrorKind::InvalidArgument)?.to_string(),
+                    split
+                        .next()
+                        .ok_or_else(|| Error::InvalidArgument("missing type".into()))?
+                        .to_string(),
                     split.next(),
                 );
 
-                if let Some(Type(_, size, _, _)) = TYPES
-                    .iter()
-                    .filter(|Type(name, _, _, _)| name == &arg)
-                    .next()
+                if let Some(ValueType(_, size, _, _)) =
+                    TYPES.iter().find(|ValueType(name, _, _, _)| name == &arg)
                 {
-                    ctx.typename = Some(arg);
-
-                    if let Some(size) = size {
-                        ctx.buf_len = *size;
+                    let buf_len = if let Some(size) = size {
+                        *size
                     } else {
-                        ctx.buf_len = len
-                            .and_then(|len| len.parse().ok())
-                            .ok_or(ErrorKind::InvalidArgument)?;
-                    }
+                        len.and_then(|len| len.parse().ok()).ok_or_else(|| {
+                            Error::InvalidArgument(format!("`{}` requires a length", arg))
+                        })?
+                    };
+
+                    ctx.set_width(buf_len);
+                    ctx.typename = Some(arg);
 
                     Ok(())
                 } else {
-                    Err(ErrorKind::InvalidArgument.into())
+                    Err(Error::UnknownType(arg))
                 }
             },
             "reinterpret matches as another type. Usage: {type} ({unsized len})",
             Some(
                 r#"- {type}
-    - Target recast type: `str, str_utf16, i8, u8, i16, u16, i32, u32, i64, u64, i128, u128, f32, f64`
+    - Target recast type: `str, str_utf16, bytes, v128, v256, i8, u8, i16, u16, i32, u32, i64, u64, i128, u128, u256, x8, x16, x32, x64, f32, f64`
 - ({unsized len})
-    - Optional: Size of the type, Applicable to `str` and `str_utf16`"#,
+    - Optional: Size of the type, Applicable to `str`, `str_utf16` and `bytes`"#,
             ),
         ),
         CmdDef::<T>::new(
-            "add",
-            "a",
+            "width",
+            "len",
             |arg, ctx| {
-                let addr = u64::from_str_radix(arg, 16).map_err(|_| ErrorKind::InvalidArgument)?;
-                ctx.value_scanner.matches_mut().push(addr.into());
+                let arg = arg.trim();
+
+                if !arg.is_empty() {
+                    let width = arg.parse::<usize>().ok().filter(|&w| w > 0).ok_or_else(|| {
+                        Error::InvalidArgument(format!("`{}` is not a valid width", arg))
+                    })?;
+
+                    match ctx
+                        .typename
+                        .as_deref()
+                        .and_then(|t| TYPES.iter().find(|ValueType(name, _, _, _)| name == &t))
+                    {
+                        Some(ValueType(name, Some(size), _, _)) if *size != width => {
+                            return Err(Error::InvalidArgument(format!(
+                                "`{}` is always {} bytes wide",
+                                name, size
+                            )))
+                        }
+                        Some(ValueType("str_utf16", _, _, _)) if width % 2 != 0 => {
+                            return Err(Error::InvalidArgument(
+                                "`str_utf16` width must be a multiple of 2".into(),
+                            ))
+                        }
+                        _ => {}
+                    }
+
+                    ctx.set_width(width);
+                }
+
+                match &ctx.typename {
+                    Some(t) => println!("Width: {} bytes ({})", ctx.buf_len, t),
+                    None => println!("Width: {} bytes (no type)", ctx.buf_len),
+                }
+
                 Ok(())
             },
-            "manually add an address to matches",
-            None,
+            "print or set the width of scanned values. Usage: ({bytes})",
+            Some(
+                r#"- ({bytes})
+    - Optional: New width of the values, in bytes. Without arguments, the current width, and type are printed.
+
+The width is normally set by scans, and `reinterpret`. Sized types, such as `i32`, only allow their own size. Overriding the width of `str`, `str_utf16`, or `bytes` re-reads the matches, so that `print`, and subsequent filters use the new width."#,
+            ),
         ),
         CmdDef::<T>::new(
-            "remove",
-            "rm",
+            "estimate",
+            "est",
+            |arg, ctx| {
+                if !arg.is_empty() {
+                    let mb = arg.parse::<umem>().map_err(|_| {
+                        Error::InvalidArgument(format!("`{}` is not a valid threshold", arg))
+                    })?;
+                    ctx.scan_threshold = if mb == 0 { None } else { Some(mb << 20) };
+                }
+
+                print_estimate(&ctx.estimate());
+
+                if let Some(threshold) = ctx.scan_threshold {
+                    println!("Confirmation required above: {} MiB", threshold >> 20);
+                }
+
+                Ok(())
+            },
+            "estimate the amount of memory a full scan goes through. Usage: ({threshold MiB})",
+            Some(
+                r#"- ({threshold MiB})
+    - Optional: Ask for confirmation before scans and pointer map builds going through more memory than this. `0` disables the confirmation."#,
+            ),
+        ),
+        CmdDef::<T>::new(
+            "retries",
+            "rt",
+            |arg, ctx| {
+                if !arg.is_empty() {
+                    let retries = arg.parse::<u32>().map_err(|_| {
+                        Error::InvalidArgument(format!("`{}` is not a valid retry count", arg))
+                    })?;
+                    ctx.value_scanner.set_read_retries(retries);
+                    ctx.pointer_map.set_read_retries(retries);
+                }
+
+                println!("Read retries: {}", ctx.value_scanner.read_retries());
+
+                Ok(())
+            },
+            "set how many times failed reads are retried during scans. Usage: ({count})",
+            Some(
+                r#"- ({count})
+    - Optional: Number of retries for value scans and pointer map builds. `0` disables retries. Without arguments, the current count is printed.
+
+Useful with connectors that fail reads intermittently. Failures that remain after all retries are logged."#,
+            ),
+        ),
+        CmdDef::<T>::new(
+            "store_values",
+            "sv",
+            |arg, ctx| {
+                if !arg.trim().is_empty() {
+                    ctx.value_scanner.set_store_values(parse_switch(arg)?);
+                }
+
+                println!(
+                    "Store values: {}",
+                    switch_name(ctx.value_scanner.store_values())
+                );
+
+                Ok(())
+            },
+            "set whether values are stored alongside matches. Usage: ({on/off})",
+            Some(
+                r#"- ({on/off})
+    - Optional: `on` stores the value of every match seen by the last scan or filter, which is the default. `off` saves memory on scans with many matches. Without arguments, the current setting is printed.
+
+Stored values are printed without reading memory again, and are what `delta` compares against."#,
+            ),
+        ),
+        CmdDef::<T>::new(
+            "prompt",
+            "pr",
             |arg, ctx| {
-                let idx = arg
-                    .parse::<usize>()
-                    .map_err(|_| ErrorKind::InvalidArgument)?;
-                ctx.value_scanner.matches_mut().remove(idx);
+                ctx.compact_prompt = match arg.trim() {
+                    "full" => false,
+                    "compact" => true,
+                    "" => !ctx.compact_prompt,
+                    arg => {
+                        return Err(Error::InvalidArgument(format!(
+                            "`{}` is not a prompt style",
+                            arg
+                        )))
+                    }
+                };
+
+                Ok(())
+            },
+            "switch between a full, and a compact prompt. Usage: (full|compact)",
+            Some(
+                r#"- (full|compact)
+    - Optional: Prompt style. Without arguments, the style is toggled.
+
+The full prompt, which is the default, shows the active type, page filter, and the number of matches, for instance `scanflow@game.exe [i32] (57 matches) >>`. The compact prompt only shows the target."#,
+            ),
+        ),
+        CmdDef::<T>::new(
+            "timeout",
+            "to",
+            |arg, ctx| {
+                if !arg.trim().is_empty() {
+                    let timeout = parse_timeout(arg)?;
+                    ctx.value_scanner.set_timeout(timeout);
+                    ctx.pointer_map.set_timeout(timeout);
+                }
+
+                println!("Timeout: {}", timeout_name(ctx.value_scanner.timeout()));
+
+                Ok(())
+            },
+            "limit how long scans may take. Usage: ({seconds}|off)",
+            Some(
+                r#"- ({seconds}|off)
+    - Optional: Wall-clock limit for value scans, filters, and pointer map builds. `off` or `0` removes the limit. Without arguments, the current limit is printed.
+
+When the limit is reached, the operation stops and keeps what it found so far. Such results are incomplete: an initial scan misses matches in memory it did not reach, and a filter keeps the matches it did not check. A warning is printed whenever this happens."#,
+            ),
+        ),
+        CmdDef::<T>::new(
+            "pagefilter",
+            "pf",
+            |arg, ctx| {
+                if !arg.trim().is_empty() {
+                    ctx.value_scanner.set_page_filter(parse_page_filter(arg)?);
+                }
+
+                match ctx.value_scanner.page_filter() {
+                    Some(filter) => println!("Page filter: {}", page_filter_name(filter)),
+                    None => println!("Page filter: all"),
+                }
+
+                Ok(())
+            },
+            "limit initial scans to pages of specific type. Usage: ({flags}|all)",
+            Some(
+                r#"- ({flags})
+    - Optional: Page flags regions must have to be scanned, separated with `-`, or `,`. Without arguments, the current filter is printed.
+    - `writable` (`rw`): writeable pages.
+    - `readonly` (`ro`): read-only pages.
+    - `noexec` (`nx`): non-executable pages.
+    - For instance, `rw-noexec` only scans writeable data, which is where most values live.
+- all
+    - Scan regions of all types (default).
+
+Regions of unknown type are always scanned."#,
+            ),
+        ),
+        CmdDef::<T>::new(
+            "region_size",
+            "rsz",
+            |arg, ctx| {
+                let (min, max) = match arg.trim() {
+                    "" => (
+                        ctx.value_scanner.min_region_size(),
+                        ctx.value_scanner.max_region_size(),
+                    ),
+                    arg => parse_size_range(arg)?,
+                };
+
+                ctx.value_scanner.set_min_region_size(min);
+                ctx.value_scanner.set_max_region_size(max);
+                ctx.pointer_map.set_min_region_size(min);
+                ctx.pointer_map.set_max_region_size(max);
+
+                println!("Region sizes: {}", size_range_name(min, max));
+
+                Ok(())
+            },
+            "limit scans to regions of a certain size. Usage: ({min}..{max}|off)",
+            Some(
+                r#"- ({min}..{max})
+    - Optional: Smallest, and largest size of the regions to scan, either of which can be left out. Sizes are decimal bytes, with an optional `K`, `M`, or `G` suffix, for instance, `..1G` skips regions larger than 1 GiB, which often excludes asset caches. Without arguments, the current limits are printed.
+- off
+    - Scan regions of any size (default).
+
+Applies to initial value scans, and pointer map builds."#,
+            ),
+        ),
+        CmdDef::<T>::new(
+            "config",
+            "set",
+            |arg, ctx| {
+                let (name, value) = arg.trim().split_once(' ').unwrap_or((arg.trim(), ""));
+                let value = value.trim();
+
+                if !name.is_empty() && value.is_empty() {
+                    return Err(Error::InvalidArgument(format!(
+                        "expected a value for `{}`",
+                        name
+                    )));
+                }
+
+                let mut config = ctx.value_scanner.config();
+
+                match name {
+                    "" => {}
+                    "retries" => {
+                        config.read_retries = value.parse::<u32>().map_err(|_| {
+                            Error::InvalidArgument(format!("`{}` is not a valid retry count", value))
+                        })?;
+                        ctx.pointer_map.set_read_retries(config.read_retries);
+                    }
+                    "timeout" => {
+                        config.timeout = parse_timeout(value)?;
+                        ctx.pointer_map.set_timeout(config.timeout);
+                    }
+                    "pagefilter" => config.page_filter = parse_page_filter(value)?,
+                    "region_size" => {
+                        let (min, max) = parse_size_range(value)?;
+                        config.min_region_size = min;
+                        config.max_region_size = max;
+                        ctx.pointer_map.set_min_region_size(min);
+                        ctx.pointer_map.set_max_region_size(max);
+                    }
+                    "store_values" => config.store_values = parse_switch(value)?,
+                    "insertion_order" => config.insertion_order = parse_switch(value)?,
+                    "alignment" => {
+                        config.alignment = match value {
+                            "auto" => 0,
+                            _ => value
+                                    .parse::<usize>()
+                                    .ok()
+                                    .filter(|&a| a > 0)
+                                    .ok_or_else(|| {
+                                        Error::InvalidArgument(format!(
+                                            "`{}` is not a valid alignment",
+                                            value
+                                        ))
+                                    })?,
+                        }
+                    }
+                    "sig_format" => {
+                        ctx.sig_format = SigFormat::parse(value).ok_or_else(|| {
+                            Error::InvalidArgument(format!(
+                                "`{}` is not a signature format",
+                                value
+                            ))
+                        })?
+                    }
+                    "sig_scope" => {
+                        ctx.sig_scope = match value {
+                            "module" => SigScope::Module,
+                            "process" => SigScope::Process,
+                            _ => {
+                                return Err(Error::InvalidArgument(format!(
+                                    "`{}` is not a signature scope",
+                                    value
+                                )))
+                            }
+                        }
+                    }
+                    "skip_modules" => {
+                        if ctx.funcs.maps_for.is_none() {
+                            return Err(Error::InvalidArgument(
+                                "modules can not be skipped without a process".into(),
+                            ));
+                        }
+                        config.skip_modules = parse_switch(value)?;
+                    }
+                    name => {
+                        return Err(Error::InvalidArgument(format!(
+                            "`{}` is not a setting",
+                            name
+                        )))
+                    }
+                }
+
+                ctx.value_scanner.set_config(&config);
+
+                if let Some(maps_for) = ctx.funcs.maps_for {
+                    ctx.funcs.maps = maps_for(&ctx.value_scanner);
+                }
+
+                let page_filter = config
+                    .page_filter
+                    .map(page_filter_name)
+                    .unwrap_or_else(|| "all".into());
+
+                println!("retries: {}", config.read_retries);
+                println!("timeout: {}", timeout_name(config.timeout));
+                println!("pagefilter: {}", page_filter);
+                println!(
+                    "region_size: {}",
+                    size_range_name(config.min_region_size, config.max_region_size)
+                );
+                println!("store_values: {}", switch_name(config.store_values));
+                println!("insertion_order: {}", switch_name(config.insertion_order));
+                println!("skip_modules: {}", switch_name(config.skip_modules));
+                match config.alignment {
+                    0 => println!("alignment: auto"),
+                    alignment => println!("alignment: {}", alignment),
+                }
+                println!("sig_format: {}", ctx.sig_format);
+                println!("sig_scope: {}", ctx.sig_scope);
+
+                Ok(())
+            },
+            "list, or change scan settings. Usage: ({setting} {value})",
+            Some(
+                r#"- ({setting} {value})
+    - Optional: Setting to change, and its new value. Without arguments, all settings are printed.
+
+Settings:
+- retries {count}: Read retries, see `retries`.
+- timeout ({seconds}|off): Scan time limit, see `timeout`.
+- pagefilter ({flags}|all): Page types to scan, see `pagefilter`.
+- region_size ({min}..{max}|off): Sizes of the regions to scan, see `region_size`.
+- store_values {on/off}: Whether values are stored alongside matches, see `store_values`.
+- insertion_order {on/off}: Keep matches in the order they were found in, rather than sorting them by address. Off by default.
+- skip_modules {on/off}: Whether initial scans skip loaded modules, see `skip_modules`. Requires a process.
+- alignment ({bytes}|auto): Only match values at multiples of this many bytes. `auto`, the default, aligns numbers to their size, up to 16 bytes, and does not align strings, and byte patterns.
+- sig_format (ida|x64dbg|c|code): Format `sigmaker` prints signatures in. `ida`, the default, writes `48 8B ? ?`, `x64dbg` writes `48 8B ?? ??`, `c` writes a byte array, and mask, as in `{ 0x48, 0x8B, 0x00, 0x00 }, "xx??"`, and `code` writes an escaped string, and mask, as in `"\x48\x8B\x00\x00", "xx??"`.
+- sig_scope (module|process): Code `sigmaker` checks signatures to be unique within. `module`, the default, only checks the text sections of the module containing the signed code, while `process` checks those of all modules, for signatures that are safe to scan the whole process with.
+
+For instance, `set pagefilter rw` only scans writeable pages."#,
+            ),
+        ),
+        CmdDef::<T>::new(
+            "regions",
+            "rg",
+            |arg, ctx| {
+                let regions = ValueScanner::memory_regions(&mut ctx.memory, ctx.funcs.maps);
+
+                if arg.is_empty() {
+                    for (i, &CTup3(base, size, page_type)) in regions.iter().enumerate() {
+                        let selected = ctx
+                            .regions
+                            .as_ref()
+                            .map(|r| r.iter().any(|&CTup3(b, _, _)| b == base))
+                            .unwrap_or(false);
+                        println!(
+                            "{}{}: {:x}-{:x} ({:x}) {:?}",
+                            if selected { "*" } else { " " },
+                            i,
+                            base,
+                            base + size,
+                            size,
+                            page_type
+                        );
+                    }
+                    return Ok(());
+                }
+
+                if arg == "all" {
+                    ctx.regions = None;
+                    return Ok(());
+                }
+
+                let mut selected = vec![];
+
+                for tok in arg.split_whitespace() {
+                    let invalid =
+                        || Error::InvalidArgument(format!("`{}` is not a valid index", tok));
+                    let (start, end) = match tok.split_once('-') {
+                        Some((s, e)) => (
+                            s.parse::<usize>().map_err(|_| invalid())?,
+                            e.parse::<usize>().map_err(|_| invalid())?,
+                        ),
+                        None => {
+                            let i = tok.parse::<usize>().map_err(|_| invalid())?;
+                            (i, i)
+                        }
+                    };
+
+                    if end >= regions.len() {
+                        return Err(Error::InvalidIndex(end));
+                    }
+
+                    selected.extend(regions.iter().take(end + 1).skip(start).copied());
+                }
+
+                selected.sort_by_key(|&CTup3(base, _, _)| base);
+                selected.dedup_by_key(|&mut CTup3(base, _, _)| base);
+
+                ctx.regions = Some(selected);
+
+                Ok(())
+            },
+            "list memory regions, or limit the initial scan to some of them. Usage: ({idx}...|all)",
+            Some(
+                r#"- no arguments
+    - List all memory regions with their indices. Selected regions are marked with `*`.
+- {idx}...
+    - Limit the next initial scan to the given region indices. Ranges, like `3-7`, are accepted.
+- all
+    - Clear the selection and scan all regions."#,
+            ),
+        ),
+        CmdDef::<T>::new(
+            "next",
+            "n",
+            |_, ctx| ctx.repeat_scan(),
+            "repeat the last scan or filter on the current matches",
+            Some(
+                r#"Filters the matches with the same value as the last scan, or with the same delta as the last `delta` filter, or again by `zero`, or `nonzero`."#,
+            ),
+        ),
+        CmdDef::<T>::new(
+            "auto",
+            "au",
+            |arg, ctx| {
+                let arg = arg.trim();
+                let secs = arg.parse::<f64>().ok().filter(|&s| s > 0.0).ok_or_else(|| {
+                    Error::InvalidArgument(format!("`{}` is not a valid interval", arg))
+                })?;
+
+                if !ctx.value_scanner.scanned() || ctx.last_scan.is_none() {
+                    return Err(Error::NotScanned);
+                }
+
+                let interval = Duration::from_secs_f64(secs);
+
+                println!("Repeating the last scan every {}s, press enter to stop", secs);
+
+                ctx.auto_scan = true;
+
+                let ret = loop {
+                    if wait_for_stop(interval, ctx.value_scanner.cancel_token()) {
+                        break Ok(());
+                    }
+
+                    if let Err(e) = ctx.repeat_scan() {
+                        break Err(e);
+                    }
+
+                    if ctx
+                        .value_scanner
+                        .cancel_token()
+                        .is_some_and(CancelToken::is_cancelled)
+                    {
+                        break Ok(());
+                    }
+                };
+
+                ctx.auto_scan = false;
+
+                ret
+            },
+            "repeat the last scan or filter periodically. Usage: {seconds}",
+            Some(
+                r#"- {seconds}
+    - Time between scans, may be fractional, such as `0.5`.
+
+Repeats the last scan or filter, like `next` does, until enter, or Ctrl-C is pressed, printing the number of matches after every scan. Useful for values that change on their own, such as timers, or regenerating resources. Use `print` afterwards to see the remaining matches."#,
+            ),
+        ),
+        CmdDef::<T>::new(
+            "near",
+            "nr",
+            |arg, ctx| {
+                let usage = || Error::InvalidArgument("expected {idx} {radius} {value}".into());
+                let mut words = arg.splitn(3, ' ');
+                let (idx, radius, value) = (
+                    words.next().ok_or_else(usage)?,
+                    words.next().ok_or_else(usage)?,
+                    words.next().ok_or_else(usage)?,
+                );
+
+                let idx = idx.parse::<usize>().map_err(|_| {
+                    Error::InvalidArgument(format!("`{}` is not a valid index", idx))
+                })?;
+                let center = *ctx
+                    .value_scanner
+                    .matches()
+                    .get(idx)
+                    .ok_or(Error::InvalidIndex(idx))?;
+                let radius = radius.parse::<usize>().map_err(|_| {
+                    Error::InvalidArgument(format!("`{}` is not a valid radius", radius))
+                })?;
+
+                // Allow to look for a different type than the one being scanned for.
+                let explicit_type = value
+                    .split_whitespace()
+                    .next()
+                    .map(|t| TYPES.iter().any(|ValueType(name, _, _, _)| *name == t))
+                    .unwrap_or(false);
+                let typename = if explicit_type {
+                    None
+                } else {
+                    ctx.typename.clone()
+                };
+
+                let (buf, t) =
+                    parse_input(value, &typename, ctx.memory.metadata().little_endian)?;
+                ctx.set_case_folding(&t);
+
+                let offsets = ctx
+                    .value_scanner
+                    .scan_near(&mut ctx.memory, center, radius, &buf)?;
+
+                println!("Found {} around {:x}", offsets.len(), center);
+
+                for off in offsets.into_iter().take(MAX_PRINT) {
+                    if off < 0 {
+                        println!("-{:x}: {:x}", off.unsigned_abs(), center - off.unsigned_abs());
+                    } else {
+                        println!("+{:x}: {:x}", off, center + off as usize);
+                    }
+                }
+
+                Ok(())
+            },
+            "scan for a value near a match. Usage: {idx} {radius} ({type}) {value}",
+            Some(
+                r#"- {idx}
+    - Index of the match to scan around.
+- {radius}
+    - Maximum distance from the match, in bytes.
+- ({type}) {value}
+    - Value to look for. The type defaults to the one being scanned for.
+
+Prints hex offsets of the found values relative to the match. Useful for confirming structure layouts. Matches are left untouched."#,
+            ),
+        ),
+        CmdDef::<T>::new(
+            "count",
+            "c",
+            |arg, ctx| {
+                let (buf, t) =
+                    parse_input(arg, &ctx.typename, ctx.memory.metadata().little_endian)?;
+
+                if !ctx.value_scanner.scanned()
+                    && !confirm_scan(ctx.scan_threshold, || ctx.estimate())
+                {
+                    return Ok(());
+                }
+
+                let count = ctx.count_matches(&buf, &t)?;
+
+                if ctx.value_scanner.scanned() {
+                    println!(
+                        "Matches that would be kept: {} of {}",
+                        count,
+                        ctx.value_scanner.matches().len()
+                    );
+                } else {
+                    println!("Matches found: {}", count);
+                }
+
+                if ctx.value_scanner.timed_out() {
+                    println!("Warning: the scan timed out, or was interrupted, the count is incomplete");
+                }
+
+                Ok(())
+            },
+            "count matches of a value without storing them. Usage: ({type}) {value}",
+            Some(
+                r#"- ({type}) {value}
+    - Same input as a regular scan. The type can be omitted after the initial scan.
+
+Before the initial scan, counts all locations holding the value. Afterwards, counts how many of the current matches would be kept by filtering with the value. Matches are left untouched either way."#,
+            ),
+        ),
+        CmdDef::<T>::new(
+            "case",
+            "ci",
+            |arg, ctx| {
+                match arg.trim() {
+                    "" => {}
+                    "on" => ctx.case_insensitive = true,
+                    "off" => ctx.case_insensitive = false,
+                    arg => {
+                        return Err(Error::InvalidArgument(format!(
+                            "expected `on` or `off`, not `{}`",
+                            arg
+                        )))
+                    }
+                }
+
+                println!(
+                    "Case insensitive string scans: {}",
+                    if ctx.case_insensitive { "on" } else { "off" }
+                );
+
+                Ok(())
+            },
+            "toggle case insensitive string scans. Usage: (on|off)",
+            Some(
+                r#"- (on|off)
+    - Optional: Enable or disable case insensitive scans. Without arguments, the current state is printed.
+
+Applies to `str` (ASCII letters only) and `str_utf16` scans. Exact matching is the default."#,
+            ),
+        ),
+        CmdDef::<T>::new(
+            "float",
+            "fm",
+            |arg, ctx| {
+                let mut words = arg.split_whitespace();
+
+                match (words.next(), words.next()) {
+                    (None, _) => {}
+                    (Some("exact"), None) => ctx.float_match = FloatMatch::Exact,
+                    (Some("trunc"), None) => ctx.float_match = FloatMatch::Truncated,
+                    (Some("round"), None) => ctx.float_match = FloatMatch::Rounded,
+                    (Some("eps"), Some(eps)) => {
+                        let eps = eps.parse::<f64>().ok().filter(|e| *e >= 0.0).ok_or_else(|| {
+                            Error::InvalidArgument(format!("`{}` is not a valid epsilon", eps))
+                        })?;
+                        ctx.float_match = FloatMatch::Epsilon(eps);
+                    }
+                    _ => {
+                        return Err(Error::InvalidArgument(format!(
+                            "expected `exact`, `eps {{epsilon}}`, `trunc`, or `round`, not `{}`",
+                            arg.trim()
+                        )))
+                    }
+                }
+
+                match ctx.float_match {
+                    FloatMatch::Exact => println!("Float scans: exact"),
+                    FloatMatch::Epsilon(eps) => println!("Float scans: within {}", eps),
+                    FloatMatch::Truncated => println!("Float scans: truncated to an integer"),
+                    FloatMatch::Rounded => println!("Float scans: rounded to an integer"),
+                }
+
+                Ok(())
+            },
+            "set how float values are matched. Usage: (exact|eps {epsilon}|trunc|round)",
+            Some(
+                r#"- (exact|eps {epsilon}|trunc|round)
+    - Optional: Matching mode. Without arguments, the current mode is printed.
+        - `exact` - match the value exactly, which is the default.
+        - `eps {epsilon}` - match values at most `epsilon` away from the value.
+        - `trunc` - match values whose integer part equals the value, for instance, `100` matches anything in `[100.0, 101.0)`.
+        - `round` - match values that round to the value, for instance, `100` matches anything in `[99.5, 100.5)`.
+
+Applies to `f32` and `f64` scans, and filters. Games often display floats as truncated, or rounded integers, which the integer modes find without guessing an epsilon. In the loose modes, only addresses aligned to the float width are scanned."#,
+            ),
+        ),
+        CmdDef::<T>::new(
+            "add",
+            "a",
+            |arg, ctx| {
+                let addr = u64::from_str_radix(arg, 16).map_err(|_| {
+                    Error::InvalidArgument(format!("`{}` is not a hex address", arg))
+                })?;
+                let idx = ctx.value_scanner.add_match(&mut ctx.memory, addr.into());
+                println!("Match {:x} is at index {}", addr, idx);
+                Ok(())
+            },
+            "manually add an address to matches",
+            None::<&str>,
+        ),
+        CmdDef::<T>::new(
+            "import",
+            "imp",
+            |arg, ctx| {
+                if arg.is_empty() {
+                    return Err(Error::InvalidArgument("expected a file path".into()));
+                }
+                let modules = (ctx.funcs.modules)(&mut ctx.memory);
+                let cnt = ctx
+                    .value_scanner
+                    .import_matches_with_modules(&mut ctx.memory, &modules, arg)?;
+                println!("Imported {} matches", cnt);
+                Ok(())
+            },
+            "import addresses from a file as matches",
+            Some(
+                r#"Usage: import {file}
+
+Reads one address per line, in hex, with or without the 0x prefix, or relative to a module, such as
+game.exe+1f00. CSV files are accepted, the address is taken from the first field. Empty lines and
+lines starting with # are skipped.
+
+The addresses are added to the matches, and can be filtered by further scans."#,
+            ),
+        ),
+        CmdDef::<T>::new(
+            "save",
+            "sm",
+            |arg, ctx| {
+                if arg.is_empty() {
+                    return Err(Error::InvalidArgument("expected a file path".into()));
+                }
+                ctx.value_scanner.save_matches(arg)?;
+                println!("Saved {} matches", ctx.value_scanner.matches().len());
+                Ok(())
+            },
+            "save the matches to a file. Usage: save {file}",
+            Some(
+                r#"Usage: save {file}
+
+Writes the matches, their stored values, and the memory regions of the initial scan, so that a long
+scan can be continued later with `load`. Settings, such as the current type, are not saved."#,
+            ),
+        ),
+        CmdDef::<T>::new(
+            "load",
+            "lm",
+            |arg, ctx| {
+                if arg.is_empty() {
+                    return Err(Error::InvalidArgument("expected a file path".into()));
+                }
+                let cnt = ctx.value_scanner.load_matches(arg)?;
+                if let Some(value) = ctx.value_scanner.previous_value(0) {
+                    ctx.buf_len = value.len();
+                }
+                ctx.last_scan = None;
+                println!("Loaded {} matches", cnt);
+                Ok(())
+            },
+            "load matches saved with `save`, replacing the current ones. Usage: load {file}",
+            Some(
+                r#"Usage: load {file}
+
+The loaded matches are filtered by further scans, just like the ones of a fresh scan. The type is
+not stored in the file, the current one is kept."#,
+            ),
+        ),
+        CmdDef::<T>::new(
+            "remove",
+            "rm",
+            |arg, ctx| {
+                let idx = arg.parse::<usize>().map_err(|_| {
+                    Error::InvalidArgument(format!("`{}` is not a valid index", arg))
+                })?;
+                ctx.value_scanner.remove_match(idx)?;
+                Ok(())
+            },
+            "remove match by index",
+            None::<&str>,
+        ),
+        CmdDef::<T>::new(
+            "label",
+            "lbl",
+            |arg, ctx| {
+                let mut words = arg.trim().splitn(2, ' ');
+
+                let idx = match words.next() {
+                    Some("") | None => {
+                        for (addr, label) in &ctx.labels {
+                            match ctx.value_scanner.position(*addr) {
+                                Some(idx) => println!("{}: {:x} [{}]", idx, addr, label),
+                                None => println!("-: {:x} [{}]", addr, label),
+                            }
+                        }
+                        return Ok(());
+                    }
+                    Some(idx) => idx.parse::<usize>().map_err(|_| {
+                        Error::InvalidArgument(format!("`{}` is not a valid index", idx))
+                    })?,
+                };
+
+                let addr = *ctx
+                    .value_scanner
+                    .matches()
+                    .get(idx)
+                    .ok_or(Error::InvalidIndex(idx))?;
+
+                match words.next().map(str::trim).filter(|l| !l.is_empty()) {
+                    Some(label) => {
+                        ctx.labels.insert(addr, label.into());
+                    }
+                    None => {
+                        ctx.labels.remove(&addr);
+                    }
+                }
+
+                Ok(())
+            },
+            "name a match. Usage: ({idx} ({name}))",
+            Some(
+                r#"- {idx}
+    - Optional: Index of the match to label. Without arguments, all labels are listed, alongside the indices of their matches.
+- {name}
+    - Optional: Label of the match, which may contain spaces. Without a name, the label of the match is removed.
+
+Labels are attached to the address, thus they outlive the match itself, and are shown again, should the address match a later scan. `print` shows the labels next to the values."#,
+            ),
+        ),
+        CmdDef::<T>::new(
+            "session",
+            "ss",
+            |arg, ctx| {
+                let (op, path) = arg.trim().split_once(' ').ok_or_else(|| {
+                    Error::InvalidArgument("expected save {file}, or load {file}".into())
+                })?;
+
+                match op {
+                    "save" => {
+                        ctx.save_session(path.trim())?;
+                        println!("Session saved");
+                    }
+                    "load" => {
+                        ctx.load_session(path.trim())?;
+                        println!(
+                            "Session loaded: {} matches, {} pointers, {} globals",
+                            ctx.value_scanner.matches().len(),
+                            ctx.pointer_map.map().len(),
+                            ctx.disasm.globals().len()
+                        );
+                    }
+                    _ => {
+                        return Err(Error::InvalidArgument(format!(
+                            "`{}` is not a session operation",
+                            op
+                        )))
+                    }
+                }
+
+                Ok(())
+            },
+            "save, or restore the scan state. Usage: (save|load) {file}",
+            Some(
+                r#"- (save|load)
+    - `save`: Write the scan state to a file.
+    - `load`: Replace the scan state with one saved before.
+- {file}
+    - Path of the session file.
+
+The scan state consists of the matches with their values, the current type, the pointer map, the collected globals, labels, and result sets saved with `merge`. The history, and the pointer chains of the last offset scan are not saved. Loading keeps the current settings, thus a session can be restored into a CLI set up differently, for instance, with modules skipped. Building a pointer map of a large process takes a while, thus saving it allows to pick up where a previous run left off, as long as the target keeps running."#,
+            ),
+        ),
+        CmdDef::<T>::new(
+            "merge",
+            "mg",
+            |arg, ctx| {
+                let mut words = arg.split_whitespace();
+
+                let (op, name) = match (words.next(), words.next()) {
+                    (None, _) => {
+                        for (name, set) in &ctx.saved_sets {
+                            println!("{}: {} matches", name, set.matches().len());
+                        }
+                        return Ok(());
+                    }
+                    (Some(op), Some(name)) => (op, name),
+                    _ => return Err(Error::InvalidArgument("expected {op} {name}".into())),
+                };
+
+                let op = match op {
+                    "save" => {
+                        ctx.saved_sets
+                            .insert(name.into(), ctx.value_scanner.clone());
+                        println!(
+                            "Saved {} matches as {}",
+                            ctx.value_scanner.matches().len(),
+                            name
+                        );
+                        return Ok(());
+                    }
+                    "drop" => {
+                        return ctx
+                            .saved_sets
+                            .remove(name)
+                            .map(|_| ())
+                            .ok_or_else(|| {
+                                Error::InvalidArgument(format!("no result set named `{}`", name))
+                            });
+                    }
+                    "union" => MergeOp::Union,
+                    "intersection" => MergeOp::Intersection,
+                    "difference" => MergeOp::Difference,
+                    op => {
+                        return Err(Error::InvalidArgument(format!(
+                            "`{}` is not a valid merge operation",
+                            op
+                        )))
+                    }
+                };
+
+                let set = ctx.saved_sets.get(name).ok_or_else(|| {
+                    Error::InvalidArgument(format!("no result set named `{}`", name))
+                })?;
+
+                ctx.value_scanner.merge(set, op);
+
+                match ctx.typename.clone() {
+                    Some(t) => ctx.print_matches(&t),
+                    None => {
+                        println!("Matches found: {}", ctx.value_scanner.matches().len());
+                        Ok(())
+                    }
+                }
+            },
+            "save result sets, and combine them with the current one. Usage: ({op} {name})",
+            Some(
+                r#"- {op}
+    - `save`: Save the current matches under a name, replacing any set saved under it before.
+    - `drop`: Forget a saved set.
+    - `union`: Add the matches of a saved set to the current ones.
+    - `intersection`: Keep the current matches that are also in a saved set.
+    - `difference`: Keep the current matches that are not in a saved set.
+- {name}
+    - Name of the saved set.
+
+Without arguments, saved sets are listed. For instance, to find addresses that held 100 earlier, and hold 90 now, scan for 100, `merge save a`, `reset`, scan for 90, and `merge intersection a`."#,
+            ),
+        ),
+        CmdDef::new(
+            "aob",
+            "ab",
+            |arg, ctx| ctx.scan_aob(parse_aob(arg)?),
+            "scan for a byte pattern with wildcards. Usage: {pattern}",
+            Some(
+                r#"- {pattern}
+    - Bytes in hex, separated by spaces, where `??`, or `?` matches any byte, for instance `48 8B ?? ?? 89`.
+
+Matches are not aligned, and the matched bytes are printed as their values. Like other scans, the first scan searches memory, and further ones filter the matches."#,
+            ),
+        ),
+        CmdDef::new(
+            "unknown",
+            "uk",
+            |arg, ctx| {
+                let typename = match arg.trim() {
+                    "" => ctx.typename.clone().unwrap_or_else(|| "i32".into()),
+                    t => t.to_string(),
+                };
+
+                ValueType::find(&typename)?;
+                ctx.scan_unknown(typename)
+            },
+            "snapshot all values of a type, to filter them with `delta`. Usage: ({type})",
+            Some(
+                r#"- {type}
+    - Integer type of up to 8 bytes. Defaults to the current type, or `i32`.
+
+Every aligned address becomes a match, with its current value stored. This replaces any existing matches. Follow up with `delta changed`, `delta inc`, and so on, to narrow down values that are not known, only how they change. Every aligned unit of the readable pages keeps its address, and value, thus a snapshot of 4 byte values takes three times the readable memory, while unreadable pages take nothing. Restrict the regions with `regions` where possible."#,
+            ),
+        ),
+        CmdDef::new(
+            "diff",
+            "df",
+            |arg, ctx| {
+                let typename = match arg.trim() {
+                    "" => ctx.typename.clone().unwrap_or_else(|| "i32".into()),
+                    t => t.to_string(),
+                };
+
+                ctx.diff_baseline(typename)
+            },
+            "find the values that differ from the baseline snapshot. Usage: ({type})",
+            Some(
+                r#"- {type}
+    - Type of fixed size to compare values of. Defaults to the current type, or `i32`.
+
+Compares the memory against the earlier snapshot passed with `--baseline`, such as a core dump captured before the current one, in units of the type size, aligned to the start of the regions mapped in both. Units that differ become the matches, with their current values stored, and can be filtered further as usual. This replaces any existing matches, and only the regions selected with `regions` are compared, if any."#,
+            ),
+        ),
+        CmdDef::new(
+            "delta",
+            "dt",
+            |arg, ctx| {
+                let delta = match arg.trim() {
+                    "changed" | "ch" => DeltaKind::Changed,
+                    "unchanged" | "un" => DeltaKind::Unchanged,
+                    "inc" | "+" => DeltaKind::Increased,
+                    "dec" | "-" => DeltaKind::Decreased,
+                    n => {
+                        let n = n.parse::<i64>().map_err(|_| {
+                            Error::InvalidArgument(format!("`{}` is not a valid delta", arg))
+                        })?;
+
+                        if n < 0 {
+                            DeltaKind::DecreasedBy(n.wrapping_neg())
+                        } else {
+                            DeltaKind::IncreasedBy(n)
+                        }
+                    }
+                };
+
+                ctx.filter_delta(delta)
+            },
+            "keep matches whose value changed in a given way since the last scan. Usage: {delta}",
+            Some(
+                r#"- {delta}
+    - `changed` (`ch`): The value is different.
+    - `unchanged` (`un`): The value is the same.
+    - `inc` (`+`): The value is larger.
+    - `dec` (`-`): The value is smaller.
+    - Signed difference between the current and the previous value, for instance `500`, or `-1`.
+
+Only integer types of up to 8 bytes are supported. Differences wrap around, thus a counter overflowing counts as increased. Use `unknown` to start from a value that is not known at all."#,
+            ),
+        ),
+        CmdDef::new(
+            "sequence",
+            "sq",
+            |arg, ctx| {
+                let mut words = arg.split_whitespace();
+
+                let pred = match words.next() {
+                    Some("inc") => SeqPred::MonotonicIncreasing,
+                    Some("dec") => SeqPred::MonotonicDecreasing,
+                    Some("step") => {
+                        let step = words.next().unwrap_or("");
+                        SeqPred::StepBy(step.parse::<i64>().map_err(|_| {
+                            Error::InvalidArgument(format!("`{}` is not a valid step", step))
+                        })?)
+                    }
+                    _ => {
+                        return Err(Error::InvalidArgument(
+                            "expected inc, dec, or step {step}".into(),
+                        ))
+                    }
+                };
+
+                let samples = match words.next() {
+                    Some(w) => w.parse::<usize>().map_err(|_| {
+                        Error::InvalidArgument(format!("`{}` is not a valid sample count", w))
+                    })?,
+                    None => 5,
+                };
+
+                let interval = match words.next() {
+                    Some(w) => Duration::from_millis(w.parse::<u64>().map_err(|_| {
+                        Error::InvalidArgument(format!("`{}` is not a valid interval", w))
+                    })?),
+                    None => Duration::from_millis(100),
+                };
+
+                ctx.filter_sequence(pred, samples, interval)
+            },
+            "keep matches whose value changes in a pattern over time. Usage: {inc/dec/step {step}} ({samples}) ({interval ms})",
+            Some(
+                r#"- {inc/dec/step {step}}
+    - inc: Every sample is larger than the previous one.
+    - dec: Every sample is smaller than the previous one.
+    - step {step}: Every sample differs from the previous one by exactly `step`. `step 0` keeps values that stay constant.
+- ({samples})
+    - Optional: Number of samples to take. Default = 5
+- ({interval ms})
+    - Optional: Time between samples in milliseconds. Default = 100
+
+Finds counters, and timers without knowing their value. Start from a broad scan, for instance a range such as `i32 0..100000`, then narrow the matches down with `sequence inc`. Only integer types of up to 8 bytes are supported."#,
+            ),
+        ),
+        CmdDef::new(
+            "zero",
+            "z",
+            |_, ctx| ctx.filter_zero(true),
+            "keep matches whose value is currently zero",
+            Some(
+                r#"Keeps the matches whose value is zero at the current width (see `width`), so a 4 byte zero is not confused with a single zero byte. Useful for finding flags, and toggles, together with `nonzero`."#,
+            ),
+        ),
+        CmdDef::new(
+            "nonzero",
+            "nz",
+            |_, ctx| ctx.filter_zero(false),
+            "keep matches whose value is currently nonzero",
+            Some(
+                r#"Keeps the matches with any nonzero byte at the current width (see `width`)."#,
+            ),
+        ),
+        CmdDef::new(
+            "history",
+            "hi",
+            |arg, ctx| {
+                let count = if arg.trim().is_empty() {
+                    ctx.history.len()
+                } else {
+                    arg.trim().parse::<usize>().map_err(|_| {
+                        Error::InvalidArgument(format!("`{}` is not a valid count", arg))
+                    })?
+                };
+
+                let skip = ctx.history.len().saturating_sub(count);
+
+                for (i, item) in ctx.history.iter().skip(skip) {
+                    println!("[{}] {}", i, item);
+                }
+
+                Ok(())
+            },
+            "reprint results of previous offset scans and sigmaker runs. Usage: ({count})",
+            Some(
+                r#"- ({count})
+    - Optional: Only print this many of the latest results.
+
+Pointer chains and signatures are numbered as they are printed. The numbers can be passed to commands like `resolve`."#,
+            ),
+        ),
+        CmdDef::new(
+            "print",
+            "p",
+            |args, ctx| {
+                let t = ctx.typename.clone().ok_or(Error::NotScanned)?;
+                let mut words = args.split_whitespace();
+
+                let start = match words.next() {
+                    Some(start) => start.parse::<usize>().map_err(|_| {
+                        Error::InvalidArgument(format!("`{}` is not a valid index", start))
+                    })?,
+                    None => return ctx.print_matches(&t),
+                };
+
+                let count = match words.next() {
+                    Some(count) => count.parse::<usize>().map_err(|_| {
+                        Error::InvalidArgument(format!("`{}` is not a valid count", count))
+                    })?,
+                    None => MAX_PRINT,
+                };
+
+                ctx.print_page(Paged::Matches, &t, start, count)
+            },
+            "print found matches after initial scan. Usage: ({start} ({count}))",
+            Some(
+                r#"- {start}
+    - Optional: Index of the first match to print. Without arguments, the number of matches, and the first page of them is printed.
+- {count}
+    - Optional: Number of matches to print, 16 by default.
+
+Use `more` to continue printing from where the last page ended. Values are read from memory when printed. Values stored by the last scan or filter that differ from the current ones are printed after them, as in `64 (stored 42)`, and in place of them if the memory can no longer be read."#,
+            ),
+        ),
+        CmdDef::<T>::new(
+            "more",
+            "mo",
+            |_, ctx| {
+                let (paged, next) = ctx.paging.ok_or_else(|| {
+                    Error::InvalidArgument("no results to page through".into())
+                })?;
+
+                let t = match paged {
+                    Paged::Matches => ctx.typename.clone().ok_or(Error::NotScanned)?,
+                    Paged::Chains => String::new(),
+                };
+
+                ctx.print_page(paged, &t, next, MAX_PRINT)
+            },
+            "print the next page of the last results",
+            Some(
+                r#"Continues where the last printed matches, or pointer chains of `offset_scan`, and `rescan` ended. Chains are put in the history as they are printed, thus they can be passed to commands like `resolve`."#,
+            ),
+        ),
+        CmdDef::new(
+            "write",
+            "wr",
+            |args, ctx| {
+                write_value(
+                    args,
+                    &ctx.typename,
+                    ctx.value_scanner.matches(),
+                    &mut ctx.memory,
+                    ctx.write_interval,
+                    ctx.value_scanner.cancel_token(),
+                )
+            },
+            "write values to select matches. Arguments: {idx/*} {o/c} {value}",
+            Some(
+                r#"Arguments:
+- {idx/*}
+    - `idx`: Write to the search match idx.
+    - `*`: Write to the all search matches. (I'd prefer `all` as oppose to `*`)
+- {o/c}
+    - `o`: Write once.
+    - `c`: Continuously write, until enter, or Ctrl-C is pressed.
+- value: Self explanatory
+
+In `c` mode, the value can also change over time, once every `write_interval`:
+- ramp {start} {step}
+    - Start at a number, and add a step to it every time. Integers wrap around.
+- cycle {value} {value}...
+    - Go through a list of values, starting over after the last one.
+"#,
+            ),
+        ),
+        CmdDef::new(
+            "write_interval",
+            "wi",
+            |arg, ctx| {
+                if !arg.is_empty() {
+                    let ms = arg.parse::<u64>().map_err(|_| {
+                        Error::InvalidArgument(format!("`{}` is not a valid interval", arg))
+                    })?;
+                    ctx.write_interval = Duration::from_millis(ms);
+                }
+
+                println!(
+                    "Continuous write interval: {}ms",
+                    ctx.write_interval.as_millis()
+                );
+
+                Ok(())
+            },
+            "set how often continuous writes happen. Usage: ({ms})",
+            Some(
+                r#"- ({ms})
+    - Optional: Milliseconds between continuous writes. `0` writes as often as possible. Without arguments, the current interval is printed.
+
+Ramps and cycles advance once per interval."#,
+            ),
+        ),
+    ]
+}
+
+/// Commands that need a process, which report as much, instead of being unknown.
+fn view_only_cmds<'a, T: MemoryView + Clone>() -> impl IntoIterator<Item = CmdDef<'a, T>> {
+    [
+        CmdDef::new(
+            "sections",
+            "sec",
+            |_, _| {
+                Err(Error::InvalidArgument(
+                    "module sections are not available without a process".into(),
+                ))
+            },
+            "list the sections of a module (requires a process)",
+            None::<&str>,
+        ),
+        CmdDef::new(
+            "skip_modules",
+            "skm",
+            |_, _| {
+                Err(Error::InvalidArgument(
+                    "modules can not be skipped without a process".into(),
+                ))
+            },
+            "set whether initial scans skip loaded modules (requires a process)",
+            None::<&str>,
+        ),
+        CmdDef::new(
+            "region_class",
+            "rcl",
+            |_, _| {
+                Err(Error::InvalidArgument(
+                    "region classes are not available without a process".into(),
+                ))
+            },
+            "limit the initial scan to regions of a class (requires a process)",
+            None::<&str>,
+        ),
+    ]
+}
+
+fn proc_cmds<'a, T: Process + MemoryView + Clone>() -> impl IntoIterator<Item = CmdDef<'a, T>> {
+    [
+        CmdDef::new(
+            "pointer_map",
+            "pm",
+            |args, ctx: &mut CliCtx<T>| {
+                match args.trim().split_once(' ') {
+                    Some(("save", path)) => {
+                        ctx.pointer_map.save(path.trim())?;
+                        println!("Saved {} pointers", ctx.pointer_map.map().len());
+                        return Ok(());
+                    }
+                    Some(("load", path)) => {
+                        let cnt = ctx.pointer_map.load(path.trim())?;
+                        println!("Loaded {} pointers", cnt);
+                        return Ok(());
+                    }
+                    _ if ["save", "load"].contains(&args.trim()) => {
+                        return Err(Error::InvalidArgument("expected a file path".into()));
+                    }
+                    _ => {}
+                }
+
+                if !confirm_scan(ctx.scan_threshold, || {
+                    ctx.pointer_map.estimate(&mut ctx.memory)
+                }) {
+                    return Ok(());
+                }
+
+                let mut relative = false;
+                let mut page_types = false;
+                let mut words = args.split_whitespace();
+
+                while let Some(w) = words.next() {
+                    match w {
+                        "-r" => relative = true,
+                        "-t" => page_types = true,
+                        "-c" => {
+                            let kib = words
+                                .next()
+                                .and_then(|w| w.parse::<usize>().ok())
+                                .filter(|&kib| kib > 0)
+                                .ok_or_else(|| {
+                                    Error::InvalidArgument("expected -c {chunk KiB}".into())
+                                })?;
+                            ctx.pointer_map.set_chunk_size(kib << 10);
+                        }
+                        w => {
+                            return Err(Error::InvalidArgument(format!(
+                                "unknown flag `{}`",
+                                w
+                            )))
+                        }
+                    }
+                }
+
+                ctx.pointer_map.set_relative(relative);
+                ctx.pointer_map.set_record_page_types(page_types);
+                ctx.pointer_map.reset();
+                let start = Instant::now();
+                ctx.pointer_map.create_map_auto(&mut ctx.memory)?;
+                ctx.phase("create_map", start);
+
+                if ctx.pointer_map.timed_out() {
+                    println!("Warning: the pointer map build timed out, or was interrupted, the map is incomplete");
+                }
+
+                Ok(())
+            },
+            "build a pointer map. args: (-r) (-t) (-c {chunk KiB}), or (save/load) {file}",
+            Some(
+                r#"- Re-builds pointer map, (used in `offset_scan`)
+- Done automatically in `offset_scan`.
+- Allows to manually trigger rebuild, if process memory has changed significantly.
+
+Arguments:
+- (-r)
+    - Optional: Also treat 32-bit values as pointers relative to their own address. Finds relative/compressed pointers, but produces more false positives.
+- (-t)
+    - Optional: Record whether pointers are in writable memory. `offset_scan` then lists chains rooted in writable memory first, since those are variables, rather than constant tables.
+- (-c {chunk KiB})
+    - Optional: Read memory in chunks of the given size, rounded up to whole pages. Larger chunks, such as `2048`, need fewer reads, which is much faster on high latency connectors. Default is 4 KiB. The size is kept for later builds.
+
+Saving, and loading:
+- save {file}
+    - Write the current pointer map to a file, instead of building one.
+- load {file}
+    - Replace the pointer map with one saved earlier, so that `offset_scan` can be re-run without rebuilding it. Files of another format version are rejected. The map is not checked against the process, thus only load maps of the same process run."#,
+            ),
+        ),
+        CmdDef::new(
+            "sections",
+            "sec",
+            |args, ctx| {
+                let module = args.trim();
+
+                if module.is_empty() {
+                    return Err(Error::InvalidArgument("expected a module name".into()));
+                }
+
+                let module = ctx
+                    .memory
+                    .module_by_name(module)
+                    .map_err(|_| Error::ModuleNotFound(module.into()))?;
+
+                let mut sections = vec![];
+                ctx.memory.module_section_list_callback(
+                    &module,
+                    (&mut |s: SectionInfo| {
+                        sections.push(s);
+                        true
+                    })
+                        .into(),
+                )?;
+
+                println!("Sections of {}: {}", module.name, sections.len());
+
+                for s in sections {
+                    let kind = if s.is_text() {
+                        "code"
+                    } else if Disasm::is_writable_section(&s.name) {
+                        "data"
+                    } else {
+                        ""
+                    };
+
+                    println!("{:x}: {} ({:x} bytes) {}", s.base, s.name, s.size, kind);
+                }
+
+                Ok(())
+            },
+            "list the sections of a module. Usage: {module}",
+            Some(
+                r#"- {module}
+    - Name of the module.
+
+Prints the base address, name, and size of every section. Code sections are marked with `code`, and sections holding writable globals, judged by their name, with `data`."#,
+            ),
+        ),
+        CmdDef::new(
+            "skip_modules",
+            "skm",
+            |arg, ctx| {
+                if !arg.trim().is_empty() {
+                    ctx.value_scanner.set_skip_modules(parse_switch(arg)?);
+                }
+
+                ctx.funcs.maps = ctx.value_scanner.process_maps_fn();
+
+                println!(
+                    "Skip modules: {}",
+                    switch_name(ctx.value_scanner.skip_modules())
+                );
+
+                Ok(())
+            },
+            "set whether initial scans skip loaded modules. Usage: ({on/off})",
+            Some(
+                r#"- ({on/off})
+    - Optional: `on` leaves the images of loaded modules out of initial scans, which avoids matches in code, and constant data. `off` scans them, which is the default. Without arguments, the current setting is printed.
+
+Regions selected with `regions`, or `region_class` are scanned as they are."#,
+            ),
+        ),
+        CmdDef::new(
+            "region_class",
+            "rcl",
+            |arg, ctx| {
+                let arg = arg.trim();
+
+                if arg.is_empty() {
+                    let range = default_scan_range(&ctx.memory);
+                    let regions = ctx.memory.mapped_mem_range_vec(0, range.start, range.end);
+                    let classes = classify_regions(&mut ctx.memory, &regions)?;
+
+                    for class in [
+                        RegionClass::Stack,
+                        RegionClass::Heap,
+                        RegionClass::Module,
+                        RegionClass::Other,
+                    ] {
+                        let (count, size) = regions
+                            .iter()
+                            .zip(&classes)
+                            .filter(|&(_, &c)| c == class)
+                            .fold((0, 0), |(count, size), (&CTup3(_, s, _), _)| {
+                                (count + 1, size + s)
+                            });
+                        println!("{}: {} regions ({:x} bytes)", class, count, size);
+                    }
+
+                    return Ok(());
+                }
+
+                let class = RegionClass::parse(arg).ok_or_else(|| {
+                    Error::InvalidArgument(format!("`{}` is not a valid region class", arg))
+                })?;
+
+                let regions = regions_of_class(&mut ctx.memory, class)?;
+                println!("Selected {} {} regions", regions.len(), class);
+                ctx.regions = Some(regions);
+
+                Ok(())
+            },
+            "limit the initial scan to regions of a class. Usage: ({stack/heap/module/other})",
+            Some(
+                r#"- ({stack/heap/module/other})
+    - Optional: Class of the regions to scan. Without arguments, the number, and size of regions of every class is printed.
+
+Classes are guessed from page types, module ranges, and region placement:
+- `module`: Regions overlapping a loaded module.
+- `stack`: Small writable regions above all modules. This matches Linux, while Windows stacks end up as `heap`.
+- `heap`: Other writable regions.
+- `other`: Everything else, such as read-only mappings.
+
+The selection replaces the one made with `regions`, use `regions all` to scan everything again."#,
+            ),
+        ),
+        CmdDef::new(
+            "globals",
+            "g",
+            |args, ctx| {
+                if let Some(path) = args.strip_prefix("save ") {
+                    return ctx.disasm.save(path.trim());
+                }
+
+                if let Some(path) = args.strip_prefix("load ") {
+                    ctx.disasm.load(&mut ctx.memory, path.trim())?;
+                    println!(
+                        "Global variable references loaded: {:x}",
+                        ctx.disasm.map().len()
+                    );
+                    return Ok(());
+                }
+
+                let (flag, rest) = args.split_once(' ').unwrap_or((args, ""));
+                let (filter, module): (Option<SectionFilter>, _) = match flag {
+                    "-d" => (Some(code_and_data_sections), rest.trim()),
+                    "-r" => (Some(code_and_rodata_sections), rest.trim()),
+                    _ => (None, args),
+                };
+                let module = if module.is_empty() {
+                    None
+                } else {
+                    Some(module)
+                };
+
+                ctx.disasm.reset();
+                let start = Instant::now();
+                match filter {
+                    Some(filter) => {
+                        ctx.disasm
+                            .collect_globals_with(&mut ctx.memory, module, filter)?
+                    }
+                    None => ctx.disasm.collect_globals(&mut ctx.memory, module)?,
+                }
+
+                ctx.phase("collect_globals", start);
+
+                if !ctx.disasm.complete() {
+                    println!("Warning: collecting globals was interrupted, globals are incomplete");
+                }
+
+                println!(
+                    "Global variable references found: {:x}",
+                    ctx.disasm.map().len()
+                );
+                println!(
+                    "Globals in writable sections: {:x} of {:x}",
+                    ctx.disasm.writable_globals().len(),
+                    ctx.disasm.globals().len()
+                );
+                println!(
+                    "Globals referenced by lea: {:x}",
+                    ctx.disasm.globals_by_kind(RefKind::Lea).len()
+                );
+                Ok(())
+            },
+            "find all global variables referenced by code. args: (-d/-r) ({module}) | save {file} | load {file}",
+            Some(
+                r#"Finds globals in target process' binary.
+
+It is automatically invoked by `sigmaker` and `offset_scan`, unless the globals were completely collected before, however, executing it manually allows the user to limit global variable search to a single module. Interrupting it with Ctrl-C keeps the globals found so far, but marks them as incomplete, thus `sigmaker` and `offset_scan` collect them again.
+
+Arguments:
+- (-d)
+    - Optional: Also scan data sections for pointers to globals (vtables, pointer tables).
+- (-r)
+    - Optional: Same as `-d`, but only scan read-only data sections, such as `.rdata`, where vtables, and jump tables usually are. Writable sections are judged by their name.
+- ({module})
+    - Optional: Limit the search to a single module.
+
+Subcommands:
+- save {file}
+    - Save found globals to a file.
+- load {file}
+    - Load globals from a file. Globals are moved to where their modules are loaded now, thus files stay valid after restarts of the target. Loading fails if a module is missing, or its size changed, as with a different build."#,
+            ),
+        ),
+CmdDef::new("sigmaker", "s", |args: &str, ctx| {
+            let (max_sig_length, args) = parse_sig_length(args)?;
+
+            if !args.trim().is_empty() && !ctx.ensure_globals()? {
+                return Ok(());
+            }
+
+            if args.split_whitespace().nth(1).is_some() {
+                let targets = args
+                    .split_whitespace()
+                    .map(|a| {
+                        u64::from_str_radix(a.trim_start_matches("0x"), 16)
+                            .map(Address::from)
+                            .map_err(|_| {
+                                Error::InvalidArgument(format!("`{}` is not a valid address", a))
+                            })
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+
+                let start = Instant::now();
+                let sig = ctx.sigmaker.find_best_sig(
+                    &mut ctx.memory,
+                    &ctx.disasm,
+                    &targets,
+                    max_sig_length,
+                    ctx.sig_scope,
+                )?;
+                ctx.phase("find_sigs", start);
+
+                println!("Best signature:");
+                ctx.remember(HistoryItem::Signature(sig.module.clone(), sig.format(ctx.sig_format)));
+
+                println!(
+                    "    {} bytes, {} instructions",
+                    sig.pattern.len(),
+                    sig.instr_count
+                );
+
+                if let Some(off) = sig.operand_offset {
+                    println!("    reference at +{:x}", off);
+                }
+
+                Ok(())
+            } else if let Some(addr) = scan_fmt_some!(args, "{x}", [hex u64]) {
+                let start = Instant::now();
+                let sigs = ctx.sigmaker.find_sigs(
+                    &mut ctx.memory,
+                    &ctx.disasm,
+                    addr.into(),
+                    max_sig_length,
+                    ctx.sig_scope,
+                );
+                ctx.phase("find_sigs", start);
+
+                match sigs {
+                    Ok(sigs) => {
+                        println!("Found signatures:");
+                        for sig in sigs {
+                            ctx.remember(HistoryItem::Signature(sig.module.clone(), sig.format(ctx.sig_format)));
+
+                            println!(
+                                "    {} bytes, {} instructions",
+                                sig.pattern.len(),
+                                sig.instr_count
+                            );
+
+                            if let Some(off) = sig.operand_offset {
+                                println!("    reference at +{:x}", off);
+                            }
+                        }
+                        Ok(())
+                    }
+                    Err(e) => Err(e),
+                }
+            } else {
+                Err(Error::InvalidArgument("expected a hex address".into()))
+            }
+        }, "finds code signatures referring to given address. args: (-l {length}) {addr}...", Some(format!(r#"Usage: After using offset scan, take the first hex value of the result you want, and sigmaker will produce a signature which you can scan for.
+
+Given multiple addresses, signatures are searched for all of them, and only the best one is kept, which is the shortest, with the fewest wildcards.
+
+Signatures are at most {} bytes long by default. Pass `-l {{length}}` first to allow longer ones, for code that takes more context to tell apart, or to limit them to shorter ones.
+
+If `globals` was not previously run, then this command will generate a list of globals on all executable regions. If you wish to look for signatures within a single module, first run `globals {{module}}`.
+
+Signatures are printed in IDA style by default. Use `set sig_format` to print them for x64dbg, or as C byte arrays, and masks.
+
+Signatures are unique within the module containing the code. Use `set sig_scope process` to make them unique within all modules."#, DEFAULT_MAX_SIG_LENGTH))),
+        CmdDef::new(
+            "sigmaker_at",
+            "sa",
+            |args: &str, ctx| {
+                let (max_sig_length, args) = parse_sig_length(args)?;
+                let addr = scan_fmt_some!(args, "{x}", [hex u64])
+                    .ok_or_else(|| Error::InvalidArgument("expected a hex address".into()))?;
+                let start = Instant::now();
+                let sig = ctx.sigmaker.find_sig_at(
+                    &mut ctx.memory,
+                    addr.into(),
+                    max_sig_length,
+                    ctx.sig_scope,
+                )?;
+                ctx.phase("find_sigs", start);
+
+                match sig {
+                    Some(sig) => {
+                        ctx.remember(HistoryItem::Signature(sig.module.clone(), sig.format(ctx.sig_format)))
+                    }
+                    None => println!("No unique signature found"),
+                }
+                Ok(())
+            },
+            "finds code signature for instructions at given address. args: (-l {length}) {addr}",
+            Some(format!(
+                r#"Usage: Pass the address of code (for instance, a function start), and sigmaker will produce a signature starting at that instruction.
+
+Unlike `sigmaker`, this does not require the address to be a global variable. As with `sigmaker`, `-l {{length}}` changes the maximum signature length from the default of {} bytes."#,
+                DEFAULT_MAX_SIG_LENGTH
+            )),
+        ),
+        CmdDef::new(
+            "resolve",
+            "rs",
+            |args: &str, ctx| {
+                let path = match ctx.recall(args)? {
+                    HistoryItem::Path(path) => path.clone(),
+                    _ => {
+                        return Err(Error::InvalidArgument(format!(
+                            "`{}` is not a pointer chain",
+                            args.trim()
+                        )))
+                    }
+                };
+
+                let size_addr = ctx.pointer_map.pointer_size(&ctx.memory);
+
+                let steps = path.resolve(&mut ctx.memory, size_addr)?;
+
+                print!("{:x}", steps[0]);
+
+                for (&addr, off) in steps.iter().zip(path.offsets()).skip(1) {
+                    print!(" -> [{:x}] + ({}) = {:x}", addr - off, off, addr);
+                }
+
+                println!();
+
+                let addr = *steps.last().unwrap();
+
+                if addr != path.target {
+                    println!("Chain now leads to {:x} instead of {:x}", addr, path.target);
+                }
+
+                Ok(())
+            },
+            "follow a pointer chain from the history in current memory. args: {idx}",
+            Some(
+                r#"- {idx}
+    - Index of a pointer chain, as printed by `offset_scan` or `history`.
+
+Reads every pointer in the chain, and prints the address it leads to now."#,
+            ),
+        ),
+        CmdDef::new(
+            "info",
+            "inf",
+            |_args: &str, ctx| {
+                println!(
+                    "Value scanner: {} matches{}",
+                    ctx.value_scanner.matches().len(),
+                    if ctx.value_scanner.scanned() {
+                        ""
+                    } else {
+                        " (not scanned)"
+                    }
+                );
+                println!("{:#?}", ctx.pointer_map);
+                println!("{:#?}", ctx.disasm);
+                println!("Pointer chains: {}", ctx.chains.len());
+
+                Ok(())
+            },
+            "print a summary of the scanner, pointer map and disassembler state",
+            Some(
+                r#"Prints counts, and address ranges of the pointer map, and of the collected globals. Useful for finding out why `offset_scan` finds nothing, for instance, an empty pointer map, or globals in the wrong module."#,
+            ),
+        ),
+        #[cfg(feature = "clipboard")]
+        CmdDef::new(
+            "copy",
+            "cp",
+            |args: &str, ctx| {
+                let mut words = args.split_whitespace();
+
+                let text = match (words.next(), words.next()) {
+                    (Some("sig"), None) => ctx
+                        .history
+                        .iter()
+                        .rev()
+                        .find_map(|(_, item)| match item {
+                            HistoryItem::Signature(_, sig) => Some(sig.clone()),
+                            _ => None,
+                        })
+                        .ok_or_else(|| {
+                            Error::InvalidArgument("no signature in the history".into())
+                        })?,
+                    (Some("h"), Some(idx)) => ctx.recall(idx)?.to_string(),
+                    (Some(idx), None) => {
+                        let idx = idx.parse::<usize>().map_err(|_| {
+                            Error::InvalidArgument(format!("`{}` is not a valid index", idx))
+                        })?;
+                        let addr = *ctx
+                            .value_scanner
+                            .matches()
+                            .get(idx)
+                            .ok_or(Error::InvalidIndex(idx))?;
+                        address_label(addr, &ctx.memory.module_list()?)
+                    }
+                    _ => {
+                        return Err(Error::InvalidArgument(
+                            "expected {idx}, sig, or h {idx}".into(),
+                        ))
+                    }
+                };
+
+                crate::clipboard::copy(&text)?;
+                println!("Copied: {}", text);
+
+                Ok(())
+            },
+            "copy a result to the clipboard. args: {idx} | sig | h {idx}",
+            Some(
+                r#"- {idx}
+    - Copy the address of a match, relative to the module containing it, like `game.exe+1a2b0`.
+- sig
+    - Copy the most recent signature found by `sigmaker`, or `sigmaker_at`.
+- h {idx}
+    - Copy a result from the history, as printed by `history`.
+
+Uses the clipboard utility of the system: `wl-copy`, `xclip`, or `xsel` on Linux, `pbcopy` on macOS, and `clip` on Windows."#,
+            ),
+        ),
+        CmdDef::new(
+            "rescan",
+            "rsc",
+            |_args: &str, ctx| {
+                if ctx.chains.is_empty() {
+                    return Err(Error::InvalidArgument(
+                        "no pointer chains, run `offset_scan` first".into(),
+                    ));
+                }
+
+                let size_addr = ctx.pointer_map.pointer_size(&ctx.memory);
+                let modules = ctx.memory.module_list()?;
+                let before = ctx.chains.len();
+
+                ctx.chains = rescan_chains(
+                    &mut ctx.memory,
+                    &modules,
+                    &ctx.chains,
+                    ctx.value_scanner.matches(),
+                    size_addr,
+                );
+
+                println!("Chains kept: {} of {}", ctx.chains.len(), before);
+
+                ctx.print_page(Paged::Chains, "", 0, MAX_PRINT)
+            },
+            "keep the pointer chains of the last offset scan that still lead to a match",
+            Some(
+                r#"Re-resolves every chain found by the last `offset_scan` in current memory, and keeps the ones that land on one of the current matches. Repeat to narrow down the chains:
+
+1. Run `offset_scan`.
+2. Change the state of the target, for instance, reload a level, so that the value moves.
+3. Scan for the value again, with `reset` and a new scan.
+4. Run `rescan`.
+
+Chains starting in a module are followed from the current base of the module, thus they survive restarts of the target. Other chains start from absolute addresses."#,
+            ),
+        ),
+        CmdDef::new(
+            "anchors",
+            "anc",
+            |_args: &str, ctx| {
+                if ctx.chains.is_empty() {
+                    return Err(Error::InvalidArgument(
+                        "no pointer chains, run `offset_scan` first".into(),
+                    ));
+                }
+
+                let modules = ctx.memory.module_list()?;
+                let size_addr = ctx.pointer_map.pointer_size(&ctx.memory);
+
+                // Show the shortest chain of every static base.
+                let mut anchors: BTreeMap<Address, (usize, &PointerPath)> = BTreeMap::new();
+
+                for chain in &ctx.chains {
+                    if let Some(base) = chain.root() {
+                        let (count, shortest) = anchors.entry(base).or_insert((0, chain));
+                        *count += 1;
+
+                        if chain.steps.len() < shortest.steps.len() {
+                            *shortest = chain;
+                        }
+                    }
+                }
+
+                println!("Static anchors: {}", anchors.len());
+
+                for (&base, &(count, chain)) in &anchors {
+                    let mut line = address_label(base, &modules);
+
+                    for off in chain.offsets() {
+                        line += &format!(" {}", offset_label(off));
+                    }
+
+                    match chain.resolve(&mut ctx.memory, size_addr) {
+                        Ok(steps) => {
+                            let target = *steps.last().unwrap();
+                            line += &format!(" => {:x}", target);
+
+                            if let Some(t) = &ctx.typename {
+                                let mut buf = vec![0; ctx.buf_len];
+
+                                if ctx.memory.read_raw_into(target, &mut buf).is_ok() {
+                                    if let Some(value) =
+                                        print_value(&buf, t, ctx.memory.metadata().little_endian)
+                                    {
+                                        line += &format!(" ({})", value);
+                                    }
+                                }
+                            }
+                        }
+                        Err(e) => line += &format!(" => unresolved ({})", e),
+                    }
+
+                    if count > 1 {
+                        line += &format!(" [{} chains]", count);
+                    }
+
+                    println!("{}", line);
+                }
+
+                Ok(())
+            },
+            "summarize the static bases of the last offset scan",
+            Some(
+                r#"For every static base of the chains found by the last `offset_scan`, prints the module-relative base, the offsets of its shortest chain, the address the chain leads to now, and the current value there, for instance `game.exe+1a2b0 +10 +8 => 7ff6a1c0 (100)`.
+
+Bases with more than one chain show the number of chains. Use `rescan` first to drop chains that no longer hold."#,
+            ),
+        ),
+        CmdDef::new(
+            "dot",
+            "dot",
+            |args: &str, ctx| {
+                let mut words = args.split_whitespace();
+
+                let path = words
+                    .next()
+                    .ok_or_else(|| Error::InvalidArgument("missing file".into()))?;
+
+                let mut chains = vec![];
+
+                for w in words {
+                    match ctx.recall(w)? {
+                        HistoryItem::Path(path) => chains.push(path.clone()),
+                        _ => {
+                            return Err(Error::InvalidArgument(format!(
+                                "`{}` is not a pointer chain",
+                                w
+                            )))
+                        }
+                    }
+                }
+
+                if chains.is_empty() {
+                    chains.extend(ctx.history.iter().filter_map(|(_, item)| match item {
+                        HistoryItem::Path(path) => Some(path.clone()),
+                        _ => None,
+                    }));
+                }
+
+                let modules = ctx.memory.module_list()?;
+
+                std::fs::write(path, chains_to_dot(&chains, &modules))?;
+
+                println!("Exported {} pointer chains", chains.len());
+
                 Ok(())
             },
-            "remove match by index",
-            None,
+            "export pointer chains from the history to a GraphViz graph. args: {file} ({idx}...)",
+            Some(
+                r#"- {file}
+    - Path of the DOT file to write.
+- ({idx}...)
+    - Optional: Indices of pointer chains to export, as printed by `offset_scan` or `history`. All chains in the history are exported by default.
+
+Render the graph with, for instance, `dot -Tsvg {file} -o graph.svg`."#,
+            ),
         ),
         CmdDef::new(
-            "print",
-            "p",
-            |_, ctx| {
-                if let Some(t) = &ctx.typename {
-                    print_matches(&ctx.value_scanner, &mut ctx.memory, ctx.buf_len, t)
-                } else {
-                    Err(ErrorKind::Uninitialized.into())
+            "export_chains",
+            "ec",
+            |args: &str, ctx| {
+                let path = args.trim();
+
+                if path.is_empty() {
+                    return Err(Error::InvalidArgument("missing file".into()));
                 }
+
+                let modules = ctx.memory.module_list()?;
+
+                std::fs::write(path, chains_to_cheat_engine(&ctx.chains, &modules))?;
+
+                println!("Exported {} pointer chains", ctx.chains.len());
+
+                Ok(())
             },
-            "print found matches after initial scan",
-            None,
-        ),
-        CmdDef::new(
-            "write",
-            "wr",
-            |args, ctx| {
-                write_value(
-                    args,
-                    &ctx.typename,
-                    ctx.value_scanner.matches(),
-                    &mut ctx.memory,
-                )
-            },
-            "write values to select matches. Arguments: {idx/*} {o/c} {value}",
+            "export the chains of the last offset scan for Cheat Engine. args: {file}",
             Some(
-                r#"Arguments:
-- {idx/*}
-    - `idx`: Write to the search match idx.
-    - `*`: Write to the all search matches. (I'd prefer `all` as oppose to `*`)
-- {o/c}
-    - `o`: Write once.
-    - `c`: Spawn thread and continuously write.
-- value: Self explanatory
-"#,
+                r#"- {file}
+    - Path of the text file to write.
+
+Writes every chain found by the last `offset_scan`, or `rescan` on its own line, in the pointer notation of Cheat Engine, such as `"client.dll"+0x1234 -> +0x10 -> +0x8`. The address holding the first pointer comes first, relative to its module if it lies within one, followed by the offsets added after every dereference."#,
             ),
         ),
-    ]
-}
-
-fn proc_cmds<'a, T: Process + MemoryView + Clone>() -> impl IntoIterator<Item = CmdDef<'a, T>> {
-    [
         CmdDef::new(
-            "pointer_map",
-            "pm",
-            |_, ctx: &mut CliCtx<T>| {
-                let size_addr = ArchitectureObj::from(ctx.memory.info().proc_arch).size_addr();
+            "pointer_size",
+            "ps",
+            |arg, ctx: &mut CliCtx<T>| {
+                match arg.trim() {
+                    "" => {}
+                    "auto" => ctx.pointer_map.set_pointer_size(None),
+                    arg => {
+                        let size = arg
+                            .parse::<usize>()
+                            .ok()
+                            .filter(|s| matches!(s, 4 | 8))
+                            .ok_or_else(|| {
+                                Error::InvalidArgument(format!(
+                                    "`{}` is not a valid pointer size",
+                                    arg
+                                ))
+                            })?;
+                        ctx.pointer_map.set_pointer_size(Some(size));
+                    }
+                }
 
-                ctx.pointer_map.reset();
-                ctx.pointer_map.create_map(&mut ctx.memory, size_addr)
+                let size = ctx.pointer_map.pointer_size(&ctx.memory);
+
+                match ctx.pointer_map.pointer_size_override() {
+                    Some(_) => println!("Pointer size: {} bytes (override)", size),
+                    None => println!("Pointer size: {} bytes (auto)", size),
+                }
+
+                Ok(())
             },
-            "build a pointer map",
+            "print or override the pointer size. Usage: ({bytes}|auto)",
             Some(
-                r#"- Re-builds pointer map, (used in `offset_scan`)
-- Done automatically in `offset_scan`.
-- Allows to manually trigger rebuild, if process memory has changed significantly.
-        CmdDef::new("globals", "g", |args, ctx| {
-            ctx.disasm.reset();
-            ctx.disasm.collect_globals(&mut ctx.process, if args.is_empty() { None } else { Some(args) })?;
-            println!("Global variable references found: {:x}", ctx.disasm.map().len());
-            Ok(())
-        }, "find all global variables referenced by code. args: ({module})", r#"Finds globals in target process' binary.
+                r#"- ({bytes}|auto)
+    - Optional: Pointer size in bytes used to build the pointer map, and to resolve chains. `auto` derives it from the process architecture, which is the default. Without arguments, the current size is printed.
 
-It is automatically invoked by `sigmaker` and `offset_scan`, however, executing it manually allows the user to limit global variable search to a single module."#,
+Overriding is needed when the architecture does not tell the real pointer size, such as with 32-bit processes running on 64-bit systems. Rebuild the pointer map with `pointer_map` after changing the size."#,
             ),
         ),
-CmdDef::new("sigmaker", "s", |args: &str, ctx| {
-            if let Some(addr) = scan_fmt_some!(args, "{x}", [hex u64]) {
-                match Sigmaker::find_sigs(&mut ctx.memory, &ctx.disasm, addr.into()) {
-                    Ok(sigs) => {
-                        println!("Found signatures:");
-                        for sig in sigs {
-                            println!("{}", sig);
-                        }
-                        Ok(())
-                    }
-                    Err(e) => Err(e),
-                }
-            } else {
-                Err(ErrorKind::ArgValidation.into())
-            }
-        }, "finds code signatures referring to given address. args: {addr}", Some(r#"Usage: After using offset scan, take the first hex value of the result you want, and sigmaker will produce a signature which you can scan for.
-
-If `globals` was not previously run, then this command will generate a list of globals on all executable regions. If you wish to look for signatures within a single module, first run `globals {module}`."#)),
         CmdDef::new("offset_scan", "os", |args, ctx| {
+            let (deref, args) = match args.trim_start().strip_prefix("-d ") {
+                Some(rest) => (true, rest),
+                None => (false, args),
+            };
+
             if let (Some(use_di), Some(lrange), Some(urange), Some(max_depth), filter_addr) =
                 scan_fmt_some!(args, "{} {} {} {} {x}", String, usize, usize, usize, [hex u64])
             {
                 if ctx.pointer_map.map().is_empty() {
-                    let size_addr = ArchitectureObj::from(ctx.memory.info().proc_arch).size_addr();
-                    ctx.pointer_map.create_map(
-                        &mut ctx.memory,
-                        size_addr
-                    )?;
+                    if !confirm_scan(ctx.scan_threshold, || ctx.pointer_map.estimate(&mut ctx.memory)) {
+                        return Ok(());
+                    }
+                    let start = Instant::now();
+                    ctx.pointer_map.create_map_auto(&mut ctx.memory)?;
+                    ctx.phase("create_map", start);
+
+                    if ctx.pointer_map.timed_out() {
+                        println!("Warning: the pointer map build timed out, or was interrupted, the map is incomplete");
+                    }
                 }
 
-                let start = Instant::now();
+                let targets = if deref {
+                    let size_addr = ctx.pointer_map.pointer_size(&ctx.memory);
+                    let targets = deref_targets(&mut ctx.memory, ctx.value_scanner.matches(), size_addr);
+                    println!("Pointed to addresses: {}", targets.len());
+                    targets
+                } else {
+                    ctx.value_scanner.matches().clone()
+                };
 
-                let matches = if use_di == "y" {
-                    if ctx.disasm.map().is_empty() {
-                        ctx.disasm.collect_globals(&mut ctx.memory, None)?;
+                let modules = ctx.memory.module_list().unwrap_or_default();
+                ctx.pointer_map
+                    .set_module_ranges(modules.iter().map(|m| (m.name.to_string(), m.base..m.base + m.size)).collect());
+
+                let mut start = Instant::now();
+
+                let matches = if matches!(use_di.as_str(), "y" | "w" | "l") {
+                    if !ctx.ensure_globals()? {
+                        return Ok(());
                     }
+                    start = Instant::now();
+                    let globals = if use_di == "w" {
+                        ctx.disasm.writable_globals()
+                    } else if use_di == "l" {
+                        ctx.disasm.globals_by_kind(RefKind::Lea)
+                    } else {
+                        ctx.disasm.globals().clone()
+                    };
                     ctx.pointer_map.find_matches_addrs(
                         (lrange, urange),
                         max_depth,
-                        ctx.value_scanner.matches(),
-                        ctx.disasm.globals(),
+                        &targets,
+                        &globals,
                     )
                 } else {
                     ctx.pointer_map.find_matches(
                         (lrange, urange),
                         max_depth,
-                        ctx.value_scanner.matches(),
+                        &targets,
                     )
                 };
 
+                ctx.phase("find_matches", start);
+
                 println!(
                     "Matches found: {} in {:.2}ms",
                     matches.len(),
                     start.elapsed().as_secs_f64() * 1000.0
                 );
 
-                if matches.len() > MAX_PRINT {
-                    println!("Printing first {} matches", MAX_PRINT);
-                }
-                for (m, offsets) in matches
+                let chains = matches
                     .into_iter()
-                        .filter(|(_, v)| {
+                        .filter(|chain| {
                             if let Some(a) = filter_addr {
-                                if let Some((s, _)) = v.first() {
-                                    s.to_umem() == a as umem
-                                } else {
-                                    false
-                                }
+                                chain.root().is_some_and(|s| s.to_umem() == a as umem)
                             } else {
                                 true
                             }
                         })
-                .take(MAX_PRINT)
-                {
-                    for (start, off) in offsets.into_iter() {
-                        print!("{:x} + ({}) => ", start, off);
-                    }
-                    println!("{:x}", m);
+                    .collect::<Vec<_>>();
+
+                let total = chains.len();
+                ctx.chains = PointerMap::rank_chains(chains);
+
+                if ctx.chains.len() < total {
+                    println!("Removed {} duplicate chains", total - ctx.chains.len());
                 }
 
-                Ok(())
+                ctx.pointer_map.sort_chains_by_root(&mut ctx.chains);
+
+                ctx.print_page(Paged::Chains, "", 0, MAX_PRINT)
             } else {
-                Err(ErrorKind::InvalidArgument.into())
+                Err(Error::InvalidArgument("expected (-d) {y/w/l/n} {lower range} {upper range} {max depth}".into()))
             }
-        }, "scan for offsets to matches. Arguments: {y/[n]} {lower range} {upper range} {max depth} ({filter})", Some(r#"Arguments:
-- {y/[n]}
+        }, "scan for offsets to matches. Arguments: (-d) {y/w/l/[n]} {lower range} {upper range} {max depth} ({filter})", Some(r#"Arguments:
+- (-d)
+    - Optional: Treat the matches as pointers, and find chains to the addresses they point to, instead of to the matches themselves. Useful when the match is a pointer field, and the referenced object is what needs a stable chain.
+- {y/w/l/[n]}
     - y: Use disassembler to find instructions in binary to refer to globals. If `globals` was not previously run, then this command will generate a list of globals on all executable regions. If you wish to look for pointers referred from a single module, first run `globals {module}`.
+    - w: Same as `y`, but only start from globals in writable data sections (`.data`, `.bss`), skipping constants in read-only ones.
+    - l: Same as `y`, but only start from globals whose address is taken by a `lea` instruction. These are usually the bases of structures, or arrays, rather than scalars loaded with `mov`.
     - n: use the whole memory range
     - Default = n
 - {lower range}
@@ -382,7 +3181,7 @@ If `globals` was not previously run, then this command will generate a list of g
 - ({filter})
     - Optional: Filter address (hex)
 
-Explanation: Finds a pointer chains from the binary to the scan results."#)),
+Explanation: Finds pointer chains from the binary to the scan results. Chains with the same root, offsets, and target are only listed once, and the rest are ranked, so that shorter chains, and chains with smaller offsets are printed first. Chains are marked `[static]` if their root lies within a module, thus survives restarts relative to the module base, and `[dynamic]` if it lies elsewhere, such as on the heap."#)),
         ]
 }
 
@@ -391,13 +3190,17 @@ Explanation: Finds a pointer chains from the binary to the scan results."#)),
 /// # Arguments
 ///
 /// * `process` - target process
-pub fn run<T: Process + MemoryView + Clone>(process: T) -> Result<()> {
+/// * `session_log` - optional log to record every command in
+pub fn run<T: Process + MemoryView + Clone>(
+    process: T,
+    session_log: Option<SessionLog>,
+) -> Result<()> {
     let mut cmds = view_cmds()
         .into_iter()
-        .chain(proc_cmds().into_iter())
+        .chain(proc_cmds())
         .collect::<Vec<_>>();
 
-    run_with_cmds(process, Funcs::process(), &mut cmds)
+    run_with_cmds(process, None, Funcs::process(), &mut cmds, session_log)
 }
 
 /// Run the CLI with a view
@@ -407,25 +3210,39 @@ pub fn run<T: Process + MemoryView + Clone>(process: T) -> Result<()> {
 /// # Arguments
 ///
 /// * `memory` - target memory object
-pub fn run_with_view<T: MemoryView + Clone>(process: T) -> Result<()> {
-    let mut cmds = view_cmds().into_iter().collect::<Vec<_>>();
+/// * `baseline` - optional earlier snapshot of the memory, to `diff` against
+/// * `session_log` - optional log to record every command in
+pub fn run_with_view<T: MemoryView + Clone>(
+    process: T,
+    baseline: Option<T>,
+    session_log: Option<SessionLog>,
+) -> Result<()> {
+    let mut cmds = view_cmds()
+        .into_iter()
+        .chain(view_only_cmds())
+        .collect::<Vec<_>>();
 
-    run_with_cmds(process, Funcs::view(), &mut cmds)
+    run_with_cmds(process, baseline, Funcs::view(), &mut cmds, session_log)
 }
 
 fn run_with_cmds<T: MemoryView + Clone>(
     state: T,
+    baseline: Option<T>,
     funcs: Funcs<T>,
     cmds: &mut [CmdDef<T>],
+    mut session_log: Option<SessionLog>,
 ) -> Result<()> {
     let mut ctx = CliCtx::new(state, funcs);
+    ctx.baseline = baseline;
 
-    loop {
-        if let Some(tn) = &ctx.typename {
-            print!("[{}] ", tn)
-        }
+    let cancel = interrupt::install();
+    ctx.value_scanner.set_cancel_token(Some(cancel.clone()));
+    ctx.pointer_map.set_cancel_token(Some(cancel.clone()));
+    ctx.disasm.set_cancel_token(Some(cancel.clone()));
+    ctx.sigmaker.set_cancel_token(Some(cancel));
 
-        print!("scanflow@{} >> ", (ctx.funcs.info)(&ctx.memory));
+    loop {
+        print!("{}", ctx.prompt());
 
         std::io::stdout().flush().ok();
 
@@ -456,7 +3273,13 @@ fn run_with_cmds<T: MemoryView + Clone>(
                     println!();
 
                     println!("To scan memory, enter wanted data type and its value. The type is omitted in consequtive function calls.");
-                    println!("Available types: str, str_utf16, i8, u8, i16, u16, i32, u32, i64, u64, i128, u128, f32, f64");
+                    println!("Available types: str, str_utf16, bytes, v128, v256, i8, u8, i16, u16, i32, u32, i64, u64, i128, u128, u256, x8, x16, x32, x64, f32, f64");
+                    println!("x8 to x64 are integers of either sign, for instance, x8 accepts both -1 and 255, which are the same byte.");
+                    println!("Integers can also be scanned for within an inclusive range, for instance, `i32 50..150`.");
+                    println!("Numbers can be compared as well, for instance, `i32 >100`, `f32 <0.5`, or `f32 0.25..0.75`.");
+                    println!("Floats can be scanned for within a tolerance, for instance, `f32 ~0.01 12.5` matches values at most 0.01 away from 12.5.");
+                    println!("Prefixing the input with `--module {{name}}` limits the initial scan to that module, for instance, `--module client.dll i32 100`.");
+                    println!("u256 is printed in hex, and parsed as decimal, or hex prefixed with 0x. v128 and v256 are 16, and 32 byte vectors, entered in hex like bytes.");
 
                     println!();
 
@@ -471,7 +3294,7 @@ fn run_with_cmds<T: MemoryView + Clone>(
                     {
                         println!("{}", cmd.help);
                         println!();
-                        if let Some(long) = cmd.long_help {
+                        if let Some(long) = &cmd.long_help {
                             println!("{}", long);
                         } else {
                             println!("(no further help available)");
@@ -486,22 +3309,113 @@ fn run_with_cmds<T: MemoryView + Clone>(
                 }
             }
             x => {
+                let _busy = interrupt::busy();
+                let history_start = ctx.history_next;
+
+                let (input, prev_regions) = match x {
+                    "--module" => match ctx.select_module(args) {
+                        Ok((input, prev)) => (input, Some(prev)),
+                        Err(e) => {
+                            println!("Invalid input: {}. Use `help` for command reference.", e);
+                            continue;
+                        }
+                    },
+                    _ => (line, None),
+                };
+
                 if let Some(cmd) = cmds.iter_mut().find(|cmd| cmd.short == x || cmd.long == x) {
-                    match cmd.invoke(args, &mut ctx) {
+                    let ret = cmd.invoke(args, &mut ctx);
+
+                    if let Some(log) = &mut session_log {
+                        log.record(
+                            cmd.long,
+                            args,
+                            ret.as_ref().err().map(|e| e.to_string()),
+                            ctx.value_scanner.matches().len(),
+                            ctx.history_since(history_start),
+                        );
+                    }
+
+                    match ret {
                         Ok(()) => {}
                         Err(e) => println!("{} error: {}\nHelp:\n{}", cmd.long, e, cmd.help()),
                     }
+                } else if let Some(range) = parse_range(input, &ctx.typename) {
+                    let ret = range.and_then(|(lo, hi, t)| ctx.scan_range(lo, hi, t));
+
+                    if let Some(log) = &mut session_log {
+                        log.record(
+                            "scan",
+                            line,
+                            ret.as_ref().err().map(|e| e.to_string()),
+                            ctx.value_scanner.matches().len(),
+                            vec![],
+                        );
+                    }
+
+                    if let Err(e) = ret {
+                        println!("Invalid input: {}. Use `help` for command reference.", e)
+                    }
+                } else if let Some(near) = parse_float_near(input, &ctx.typename) {
+                    let ret = near.and_then(|(value, eps, t)| ctx.scan_float_near(value, eps, t));
+
+                    if let Some(log) = &mut session_log {
+                        log.record(
+                            "scan",
+                            line,
+                            ret.as_ref().err().map(|e| e.to_string()),
+                            ctx.value_scanner.matches().len(),
+                            vec![],
+                        );
+                    }
+
+                    if let Err(e) = ret {
+                        println!("Invalid input: {}. Use `help` for command reference.", e)
+                    }
+                } else if let Some(cmp) = parse_cmp(input, &ctx.typename) {
+                    let ret = cmp.and_then(|(pred, t)| ctx.scan_cmp(pred, t));
+
+                    if let Some(log) = &mut session_log {
+                        log.record(
+                            "scan",
+                            line,
+                            ret.as_ref().err().map(|e| e.to_string()),
+                            ctx.value_scanner.matches().len(),
+                            vec![],
+                        );
+                    }
+
+                    if let Err(e) = ret {
+                        println!("Invalid input: {}. Use `help` for command reference.", e)
+                    }
                 } else {
-                    if let Some((buf, t)) = parse_input(line, &ctx.typename) {
-                        ctx.buf_len = buf.len();
-                        ctx.value_scanner
-                            .scan_for_2(&mut ctx.memory, ctx.funcs.maps, &buf)?;
-                        print_matches(&ctx.value_scanner, &mut ctx.memory, ctx.buf_len, &t)?;
-                        ctx.typename = Some(t);
-                    } else {
-                        println!("Invalid input! Use `help` for command reference.");
+                    match parse_input(input, &ctx.typename, ctx.memory.metadata().little_endian) {
+                        Ok((buf, t)) => {
+                            let ret = ctx.scan_value(buf, t);
+
+                            if let Some(log) = &mut session_log {
+                                log.record(
+                                    "scan",
+                                    line,
+                                    ret.as_ref().err().map(|e| e.to_string()),
+                                    ctx.value_scanner.matches().len(),
+                                    vec![],
+                                );
+                            }
+
+                            ret?
+                        }
+                        Err(e) => {
+                            println!("Invalid input: {}. Use `help` for command reference.", e)
+                        }
                     }
                 }
+
+                if let Some(prev) = prev_regions {
+                    ctx.regions = prev;
+                }
+
+                ctx.log_phases();
             }
         }
     }
@@ -509,36 +3423,256 @@ fn run_with_cmds<T: MemoryView + Clone>(
     Ok(())
 }
 
+/// Print matches alongside their current values, and labels.
+///
+/// Values are read from memory. Stored values that differ from the current ones are printed after
+/// them, and are printed instead, labelled as stored, if the memory can not be read.
 pub fn print_matches(
-    value_scanner: &ValueScanner,
+    matches: &[(Address, Option<&[u8]>)],
     mem: &mut impl MemoryView,
     buf_len: usize,
     typename: &str,
+    labels: &BTreeMap<Address, String>,
 ) -> Result<()> {
-    println!("Matches found: {}", value_scanner.matches().len());
+    let little_endian = mem.metadata().little_endian;
+    let print = |buf: &[u8]| {
+        print_value(buf, typename, little_endian).ok_or_else(|| Error::UnknownType(typename.into()))
+    };
 
-    for &m in value_scanner.matches().iter().take(MAX_PRINT) {
+    for &(m, stored) in matches {
+        let stored = stored.filter(|stored| stored.len() == buf_len);
         let mut buf = vec![0; buf_len];
-        mem.read_raw_into(m, &mut buf).data_part()?;
-        println!(
-            "{:x}: {}",
-            m,
-            print_value(&buf, typename).ok_or(ErrorKind::InvalidArgument)?
-        );
+
+        let value = match (mem.read_raw_into(m, &mut buf).data_part(), stored) {
+            (Ok(()), Some(stored)) if *stored != buf[..] => {
+                format!("{} (stored {})", print(&buf)?, print(stored)?)
+            }
+            (Ok(()), _) => print(&buf)?,
+            (Err(_), Some(stored)) => format!("{} (stored, unreadable now)", print(stored)?),
+            (Err(e), None) => return Err(e.into()),
+        };
+
+        match labels.get(&m) {
+            Some(label) => println!("{:x} [{}]: {}", m, label, value),
+            None => println!("{:x}: {}", m, value),
+        }
     }
 
     Ok(())
 }
 
+/// Get a short description of a page filter.
+pub fn page_filter_name(filter: PageType) -> String {
+    let mut flags = vec![];
+
+    if filter.contains(PageType::WRITEABLE) {
+        flags.push("rw");
+    }
+    if filter.contains(PageType::READ_ONLY) {
+        flags.push("ro");
+    }
+    if filter.contains(PageType::NOEXEC) {
+        flags.push("noexec");
+    }
+
+    if flags.is_empty() {
+        "none".into()
+    } else {
+        flags.join("-")
+    }
+}
+
+/// Parse an `on`, or `off` setting.
+fn parse_switch(arg: &str) -> Result<bool> {
+    match arg.trim() {
+        "on" => Ok(true),
+        "off" => Ok(false),
+        arg => Err(Error::InvalidArgument(format!(
+            "`{}` is not a valid setting, expected on or off",
+            arg
+        ))),
+    }
+}
+
+fn switch_name(on: bool) -> &'static str {
+    if on {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+/// Parse page flags separated with `-`, or `,`, or `all` for no filter.
+fn parse_page_filter(arg: &str) -> Result<Option<PageType>> {
+    let arg = arg.trim();
+
+    if arg == "all" {
+        return Ok(None);
+    }
+
+    let mut filter = PageType::NONE;
+
+    for flag in arg.split(&['-', ','][..]) {
+        filter |= match flag {
+            "writable" | "writeable" | "rw" | "w" => PageType::WRITEABLE,
+            "readonly" | "ro" => PageType::READ_ONLY,
+            "noexec" | "nx" => PageType::NOEXEC,
+            _ => {
+                return Err(Error::InvalidArgument(format!(
+                    "`{}` is not a valid page flag",
+                    flag
+                )))
+            }
+        };
+    }
+
+    Ok(Some(filter))
+}
+
+/// Parse a timeout in seconds, or `off`.
+fn parse_timeout(arg: &str) -> Result<Option<Duration>> {
+    match arg.trim() {
+        "off" | "0" => Ok(None),
+        arg => arg
+            .parse::<f64>()
+            .ok()
+            .filter(|s| *s > 0.0)
+            .map(|secs| Some(Duration::from_secs_f64(secs)))
+            .ok_or_else(|| Error::InvalidArgument(format!("`{}` is not a valid timeout", arg))),
+    }
+}
+
+fn timeout_name(timeout: Option<Duration>) -> String {
+    match timeout {
+        Some(timeout) => format!("{:.1}s", timeout.as_secs_f64()),
+        None => "off".into(),
+    }
+}
+
+/// Parse a `{min}..{max}` size range, either bound of which can be left out, or `off`.
+fn parse_size_range(arg: &str) -> Result<(Option<umem>, Option<umem>)> {
+    match arg.trim() {
+        "off" => Ok((None, None)),
+        arg => {
+            let (min, max) = arg
+                .split_once("..")
+                .ok_or_else(|| Error::InvalidArgument(format!("`{}` is not a size range", arg)))?;
+            Ok((parse_size(min)?, parse_size(max)?))
+        }
+    }
+}
+
+fn size_range_name(min: Option<umem>, max: Option<umem>) -> String {
+    let print = |size: Option<umem>| size.map(|s| s.to_string()).unwrap_or_default();
+
+    match (min, max) {
+        (None, None) => "any".into(),
+        _ => format!("{}..{}", print(min), print(max)),
+    }
+}
+
+pub fn print_estimate(estimate: &ScanEstimate) {
+    println!(
+        "Regions: {}, total: {:.2} MiB",
+        estimate.region_count,
+        estimate.total_bytes as f64 / mem::mb(1) as f64
+    );
+}
+
+/// Ask the user for confirmation if the estimated scan goes over the threshold.
+///
+/// The estimate is only computed when a threshold is set.
+pub fn confirm_scan(threshold: Option<umem>, estimate: impl FnOnce() -> ScanEstimate) -> bool {
+    let threshold = match threshold {
+        Some(threshold) => threshold,
+        None => return true,
+    };
+
+    let estimate = estimate();
+
+    if estimate.total_bytes <= threshold {
+        return true;
+    }
+
+    print_estimate(&estimate);
+    print!("Continue? [y/N] ");
+    std::io::stdout().flush().ok();
+
+    get_line()
+        .map(|l| l.trim().eq_ignore_ascii_case("y"))
+        .unwrap_or(false)
+}
+
+/// Lines of stdin, read by a single thread for the whole program.
+///
+/// Commands that wait for input only as long as they run, such as `auto`, take lines from here,
+/// rather than reading stdin on threads of their own, which would be left blocking on it, and take
+/// the next line entered at the prompt.
+static LINES: OnceLock<Mutex<Receiver<std::io::Result<String>>>> = OnceLock::new();
+
+fn lines() -> MutexGuard<'static, Receiver<std::io::Result<String>>> {
+    LINES
+        .get_or_init(|| {
+            let (tx, rx) = channel();
+
+            thread::spawn(move || loop {
+                let mut line = String::new();
+                let ret = std::io::stdin().read_line(&mut line).map(|_| line);
+                let done = !matches!(&ret, Ok(line) if !line.is_empty());
+
+                if tx.send(ret).is_err() || done {
+                    break;
+                }
+            });
+
+            Mutex::new(rx)
+        })
+        .lock()
+        .unwrap()
+}
+
+/// Read a line of input, blocking until one is entered.
+///
+/// At the end of input, an empty string is returned, as with `Stdin::read_line`.
 pub fn get_line() -> std::io::Result<String> {
-    let mut output = String::new();
-    std::io::stdin().read_line(&mut output).map(|_| output)
+    lines().recv().unwrap_or_else(|_| Ok(String::new()))
+}
+
+/// Read a line of input, if one is entered within `timeout`.
+pub fn get_line_within(timeout: Duration) -> Option<std::io::Result<String>> {
+    match lines().recv_timeout(timeout) {
+        Ok(line) => Some(line),
+        Err(RecvTimeoutError::Timeout) => None,
+        Err(RecvTimeoutError::Disconnected) => Some(Ok(String::new())),
+    }
 }
 
-pub fn async_get_line() -> Receiver<std::io::Result<String>> {
-    let (tx, rx) = channel();
-    thread::spawn(move || tx.send(get_line()).unwrap());
-    rx
+/// Wait up to `timeout` for a line of input, or for `cancel` to be cancelled.
+///
+/// Returns whether either happened. The token is checked at least every 100ms, so that Ctrl-C
+/// stops long waits promptly.
+pub fn wait_for_stop(timeout: Duration, cancel: Option<&CancelToken>) -> bool {
+    const POLL: Duration = Duration::from_millis(100);
+
+    let deadline = Instant::now() + timeout;
+
+    loop {
+        if cancel.is_some_and(CancelToken::is_cancelled) {
+            return true;
+        }
+
+        let left = deadline.saturating_duration_since(Instant::now());
+
+        match get_line_within(left.min(POLL)) {
+            Some(Err(e)) => {
+                println!("Error reading line: {}", e);
+                return true;
+            }
+            Some(Ok(_)) => return true,
+            None if left <= POLL => return false,
+            None => {}
+        }
+    }
 }
 
 pub fn write_value(
@@ -546,53 +3680,57 @@ pub fn write_value(
     typename: &Option<String>,
     matches: &[Address],
     mem: &mut impl MemoryView,
+    interval: Duration,
+    cancel: Option<&CancelToken>,
 ) -> Result<()> {
     if matches.is_empty() {
-        return Err(ErrorKind::Uninitialized.into());
+        return Err(Error::NoMatches);
     }
 
-    let usage: Error = ErrorKind::ArgValidation.into();
-    let mut words = args.splitn(3, " ");
+    let usage = || Error::InvalidArgument("expected {idx/*} {o/c} {value}".into());
+    let mut words = args.splitn(3, ' ');
     let (idx, mode, value) = (
-        words.next().ok_or(usage)?,
-        words.next().ok_or(usage)?,
-        words.next().ok_or(usage)?,
+        words.next().ok_or_else(usage)?,
+        words.next().ok_or_else(usage)?,
+        words.next().ok_or_else(usage)?,
     );
 
     let (skip, take) = if idx == "*" {
         (0, matches.len())
     } else {
-        (
-            idx.parse::<usize>()
-                .map_err(|_| ErrorKind::InvalidArgument)?,
-            1,
-        )
+        let idx = idx
+            .parse::<usize>()
+            .map_err(|_| Error::InvalidArgument(format!("`{}` is not a valid index", idx)))?;
+        if idx >= matches.len() {
+            return Err(Error::InvalidIndex(idx));
+        }
+        (idx, 1)
     };
 
-    let gl = match mode {
-        "o" => Ok(None),
-        "c" => Ok(Some(async_get_line())),
-        _ => Err(ErrorKind::InvalidArgument),
-    }?;
+    let addrs = &matches[skip..(skip + take)];
 
-    let (v, _) = parse_input(value, typename).ok_or(ErrorKind::InvalidArgument)?;
+    let (typename, value) = match typename {
+        Some(t) => (t.as_str(), value),
+        None => value
+            .split_once(' ')
+            .ok_or_else(|| Error::InvalidArgument("expected {type} {value}".into()))?,
+    };
 
     println!("Write to matches {}-{}", skip, skip + take - 1);
 
-    loop {
-        for &m in matches.iter().skip(skip).take(take) {
-            mem.write_raw(m, v.as_ref()).data_part()?;
-        }
+    match mode {
+        "o" => write_typed_batch(mem, addrs, typename, value)?,
+        "c" => {
+            let pattern = WritePattern::parse(value, typename, mem.metadata().little_endian)?;
 
-        if let Some(try_get_line) = &gl {
-            if let Ok(ret) = try_get_line.try_recv() {
-                if let Err(e) = ret {
-                    println!("Error reading line: {}", e.to_string());
-                }
-                break;
-            }
-        } else {
-            break;
+            // Keep writing until a line is entered, or the command is interrupted.
+            write_pattern_with(mem, addrs, &pattern, || wait_for_stop(interval, cancel))?;
+        }
+        _ => {
+            return Err(Error::InvalidArgument(format!(
+                "`{}` is not a valid write mode",
+                mode
+            )))
         }
     }
 
@@ -601,132 +3739,211 @@ pub fn write_value(
     Ok(())
 }
 
-type PrintFn = fn(&[u8]) -> Option<String>;
-type ParseFn = fn(&str) -> Option<Box<[u8]>>;
-
-pub struct Type(&'static str, Option<usize>, PrintFn, ParseFn);
-
-const TYPES: &[Type] = &[
-    Type(
-        "str",
-        None,
-        |buf| Some(String::from_utf8_lossy(buf).to_string()),
-        |value| Some(Box::from(value.as_bytes())),
-    ),
-    Type(
-        "str_utf16",
-        None,
-        |buf| {
-            let mut vec = vec![];
-            for w in buf.chunks_exact(2) {
-                let s = u16::from_ne_bytes(w.try_into().unwrap());
-                vec.push(s);
-            }
-            Some(format!("{}", String::from_utf16_lossy(&vec)))
-        },
-        |value| {
-            let mut out = vec![];
-            for v in value.encode_utf16() {
-                out.extend(v.to_ne_bytes().iter().copied());
-            }
-            Some(out.into_boxed_slice())
-        },
-    ),
-    Type(
-        "i128",
-        Some(16),
-        |buf| Some(format!("{}", i128::from_ne_bytes(buf.try_into().ok()?))),
-        |value| Some(Box::from(value.parse::<i128>().ok()?.to_ne_bytes())),
-    ),
-    Type(
-        "i64",
-        Some(8),
-        |buf| Some(format!("{}", i64::from_ne_bytes(buf.try_into().ok()?))),
-        |value| Some(Box::from(value.parse::<i64>().ok()?.to_ne_bytes())),
-    ),
-    Type(
-        "i32",
-        Some(4),
-        |buf| Some(format!("{}", i32::from_ne_bytes(buf.try_into().ok()?))),
-        |value| Some(Box::from(value.parse::<i32>().ok()?.to_ne_bytes())),
-    ),
-    Type(
-        "i16",
-        Some(2),
-        |buf| Some(format!("{}", i16::from_ne_bytes(buf.try_into().ok()?))),
-        |value| Some(Box::from(value.parse::<i16>().ok()?.to_ne_bytes())),
-    ),
-    Type(
-        "i8",
-        Some(1),
-        |buf| Some(format!("{}", i8::from_ne_bytes(buf.try_into().ok()?))),
-        |value| Some(Box::from(value.parse::<i8>().ok()?.to_ne_bytes())),
-    ),
-    Type(
-        "u128",
-        Some(16),
-        |buf| Some(format!("{}", u128::from_ne_bytes(buf.try_into().ok()?))),
-        |value| Some(Box::from(value.parse::<u128>().ok()?.to_ne_bytes())),
-    ),
-    Type(
-        "u64",
-        Some(8),
-        |buf| Some(format!("{}", u64::from_ne_bytes(buf.try_into().ok()?))),
-        |value| Some(Box::from(value.parse::<u64>().ok()?.to_ne_bytes())),
-    ),
-    Type(
-        "u32",
-        Some(4),
-        |buf| Some(format!("{}", u32::from_ne_bytes(buf.try_into().ok()?))),
-        |value| Some(Box::from(value.parse::<u32>().ok()?.to_ne_bytes())),
-    ),
-    Type(
-        "u16",
-        Some(2),
-        |buf| Some(format!("{}", u16::from_ne_bytes(buf.try_into().ok()?))),
-        |value| Some(Box::from(value.parse::<u16>().ok()?.to_ne_bytes())),
-    ),
-    Type(
-        "u8",
-        Some(1),
-        |buf| Some(format!("{}", u8::from_ne_bytes(buf.try_into().ok()?))),
-        |value| Some(Box::from(value.parse::<u8>().ok()?.to_ne_bytes())),
-    ),
-    Type(
-        "f64",
-        Some(4),
-        |buf| Some(format!("{}", f64::from_ne_bytes(buf.try_into().ok()?))),
-        |value| Some(Box::from(value.parse::<f64>().ok()?.to_ne_bytes())),
-    ),
-    Type(
-        "f32",
-        Some(4),
-        |buf| Some(format!("{}", f32::from_ne_bytes(buf.try_into().ok()?))),
-        |value| Some(Box::from(value.parse::<f32>().ok()?.to_ne_bytes())),
-    ),
-];
-
-pub fn print_value(buf: &[u8], typename: &str) -> Option<String> {
-    TYPES
-        .iter()
-        .filter(|Type(name, _, _, _)| name == &typename)
-        .next()
-        .and_then(|Type(_, _, pfn, _)| pfn(buf))
-}
-
-pub fn parse_input(input: &str, opt_typename: &Option<String>) -> Option<(Box<[u8]>, String)> {
+/// Parse a value, typed either by the selected type, or by the first word of the input.
+///
+/// # Arguments
+/// * `input` - value, prefixed with its type if none is selected
+/// * `opt_typename` - selected type
+/// * `little_endian` - byte order to parse the value in, usually that of the target
+pub fn parse_input(
+    input: &str,
+    opt_typename: &Option<String>,
+    little_endian: bool,
+) -> Result<(Box<[u8]>, String)> {
     let (typename, value) = if let Some(t) = opt_typename {
         (t.as_str(), input)
     } else {
-        let mut words = input.splitn(2, " ");
-        (words.next()?, words.next()?)
+        let mut words = input.splitn(2, ' ');
+        match (words.next(), words.next()) {
+            (Some(typename), Some(value)) => (typename, value),
+            _ => return Err(Error::InvalidArgument("expected {type} {value}".into())),
+        }
+    };
+
+    Ok((
+        parse_value(value, typename, little_endian)?,
+        typename.to_string(),
+    ))
+}
+
+/// Parse a byte size with an optional binary unit suffix, such as `64K`, or `1G`.
+///
+/// Returns `None` for an empty string.
+fn parse_size(size: &str) -> Result<Option<umem>> {
+    let size = size.trim();
+
+    if size.is_empty() {
+        return Ok(None);
+    }
+
+    let (digits, unit) = match size.char_indices().last() {
+        Some((i, 'K' | 'k')) => (&size[..i], mem::kb(1)),
+        Some((i, 'M' | 'm')) => (&size[..i], mem::mb(1)),
+        Some((i, 'G' | 'g')) => (&size[..i], mem::gb(1)),
+        _ => (size, 1),
+    };
+
+    digits
+        .parse::<umem>()
+        .ok()
+        .and_then(|n| n.checked_mul(unit as umem))
+        .map(Some)
+        .ok_or_else(|| Error::InvalidArgument(format!("`{}` is not a valid size", size)))
+}
+
+/// Split the `-l {length}` maximum signature length off sigmaker arguments.
+///
+/// Returns the default length if the flag is not given.
+fn parse_sig_length(args: &str) -> Result<(usize, &str)> {
+    match args.trim_start().strip_prefix("-l ") {
+        Some(rest) => {
+            let (len, rest) = rest
+                .trim_start()
+                .split_once(' ')
+                .unwrap_or((rest.trim(), ""));
+
+            let len = len
+                .parse::<usize>()
+                .ok()
+                .filter(|&len| len > 0)
+                .ok_or_else(|| {
+                    Error::InvalidArgument(format!("`{}` is not a valid signature length", len))
+                })?;
+
+            Ok((len, rest))
+        }
+        None => Ok((DEFAULT_MAX_SIG_LENGTH, args)),
+    }
+}
+
+/// Get the alignment values of a type are usually placed at.
+///
+/// Numbers are aligned to their size, up to 16 bytes, while strings, byte arrays, and vectors are
+/// not aligned.
+fn natural_alignment(typename: &str, size: usize) -> usize {
+    if (is_integer(typename) || typename.starts_with('f')) && size.is_power_of_two() {
+        size.min(16)
+    } else {
+        1
+    }
+}
+
+/// Parse a byte pattern with wildcards, such as `48 8B ?? ?? 89`.
+fn parse_aob(input: &str) -> Result<Vec<Option<u8>>> {
+    let pattern = input
+        .split_whitespace()
+        .map(|b| match b {
+            "??" | "?" => Ok(None),
+            _ => u8::from_str_radix(b, 16)
+                .ok()
+                .filter(|_| b.len() <= 2)
+                .map(Some)
+                .ok_or_else(|| Error::InvalidArgument(format!("`{}` is not a valid byte", b))),
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    if pattern.is_empty() {
+        return Err(Error::InvalidArgument("expected a pattern".into()));
+    }
+
+    Ok(pattern)
+}
+
+/// Parse a float scan input with a tolerance, such as `f32 ~0.01 12.5`, or `~0.01 12.5` if the type
+/// is already known.
+///
+/// Returns `None` if the input has no tolerance.
+fn parse_float_near(
+    input: &str,
+    opt_typename: &Option<String>,
+) -> Option<Result<(f64, f64, String)>> {
+    let (typename, rest) = match opt_typename {
+        Some(t) if input.starts_with('~') => (t.as_str(), input),
+        _ => {
+            let mut words = input.splitn(2, ' ');
+            (words.next()?, words.next()?.trim_start())
+        }
+    };
+
+    let (eps, value) = rest.strip_prefix('~')?.trim_start().split_once(' ')?;
+
+    let parse = |v: &str| {
+        v.trim()
+            .parse::<f64>()
+            .map_err(|_| Error::InvalidArgument(format!("`{}` is not a valid number", v.trim())))
+    };
+
+    Some(parse(eps).and_then(|eps| {
+        if eps >= 0.0 {
+            Ok((parse(value)?, eps, typename.to_string()))
+        } else {
+            Err(Error::InvalidArgument(format!(
+                "`{}` is not a valid tolerance",
+                eps
+            )))
+        }
+    }))
+}
+
+/// Parse a comparison scan input, such as `i32 >100`, `f32 0.25..0.75`, or `>100` if the type is
+/// already known.
+///
+/// Returns `None` if the input is not a comparison. Integer ranges are left to `parse_range`.
+fn parse_cmp(
+    input: &str,
+    opt_typename: &Option<String>,
+) -> Option<Result<(ScanPredicate, String)>> {
+    let (typename, value) = match opt_typename {
+        Some(t) if !input.contains(' ') => (t.as_str(), input),
+        _ => {
+            let mut words = input.splitn(2, ' ');
+            (words.next()?, words.next()?)
+        }
+    };
+
+    let value = value.trim();
+    let is_float = typename == "f32" || typename == "f64";
+
+    let is_cmp = value.starts_with(['>', '<']) || (is_float && value.contains(".."));
+
+    if !is_cmp {
+        return None;
+    }
+
+    Some(ScanPredicate::parse(value, typename).map(|pred| (pred, typename.to_string())))
+}
+
+/// Parse a range scan input, such as `i32 50..150`, or `50..150` if the type is already known.
+///
+/// Returns `None` if the input is not a range of an integer type.
+fn parse_range(input: &str, opt_typename: &Option<String>) -> Option<Result<(i128, i128, String)>> {
+    let (typename, value) = match opt_typename {
+        Some(t) if !input.contains(' ') => (t.as_str(), input),
+        _ => {
+            let mut words = input.splitn(2, ' ');
+            (words.next()?, words.next()?)
+        }
     };
 
-    let b = TYPES
-        .iter()
-        .filter(|Type(name, _, _, _)| name == &typename)
-        .next()?
-        .3(value)?;
+    let (lo, hi) = value.trim().split_once("..")?;
+    let (min, max) = int_range(typename)?;
+
+    let parse = |v: &str| {
+        let v = v.trim();
+        match v.parse::<i128>() {
+            Ok(n) if n < min || (n >= 0 && n as u128 > max) => Err(Error::ValueOutOfRange {
+                typename: typename.into(),
+                value: v.into(),
+                min,
+                max,
+            }),
+            Ok(n) => Ok(n),
+            Err(_) => Err(Error::InvalidValue {
+                typename: typename.into(),
+                value: v.into(),
+            }),
+        }
+    };
 
-    Some((b, typename.to_string()))
+    Some(parse(lo).and_then(|lo| Ok((lo, parse(hi)?, typename.to_string()))))
 }
@@ -0,0 +1,61 @@
+//! System clipboard access.
+//!
+//! The clipboard is accessed through the utility of the platform, rather than a library, so that
+//! no display server libraries need to be linked in.
+
+use scanflow::error::{Error, Result};
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+#[cfg(target_os = "windows")]
+const COMMANDS: &[&[&str]] = &[&["clip"]];
+
+#[cfg(target_os = "macos")]
+const COMMANDS: &[&[&str]] = &[&["pbcopy"]];
+
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+const COMMANDS: &[&[&str]] = &[
+    &["wl-copy"],
+    &["xclip", "-selection", "clipboard"],
+    &["xsel", "--clipboard", "--input"],
+];
+
+/// Copy text to the system clipboard.
+///
+/// The clipboard utilities are tried in order, until one of them succeeds.
+///
+/// # Arguments
+/// * `text` - text to copy
+pub fn copy(text: &str) -> Result<()> {
+    for cmd in COMMANDS {
+        let child = Command::new(cmd[0])
+            .args(&cmd[1..])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn();
+
+        let mut child = match child {
+            Ok(child) => child,
+            Err(_) => continue,
+        };
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+
+        if child.wait()?.success() {
+            return Ok(());
+        }
+    }
+
+    Err(Error::Io(format!(
+        "no clipboard utility found, tried: {}",
+        COMMANDS
+            .iter()
+            .map(|cmd| cmd[0])
+            .collect::<Vec<_>>()
+            .join(", ")
+    )))
+}
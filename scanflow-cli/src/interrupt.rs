@@ -0,0 +1,73 @@
+//! Ctrl-C handling.
+//!
+//! While a command is running, Ctrl-C cancels it, rather than the whole program, so that the state
+//! accumulated so far is not lost. Interrupted scans keep their partial results, like ones that
+//! timed out. Pressing Ctrl-C again before the command stops quits, in case it does not check for
+//! the cancellation. At an idle prompt, Ctrl-C quits as usual.
+//!
+//! This is only supported on unix. Elsewhere, no handler is installed, thus Ctrl-C always quits.
+
+use scanflow::task::CancelToken;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+
+static TOKEN: OnceLock<CancelToken> = OnceLock::new();
+static BUSY: AtomicBool = AtomicBool::new(false);
+
+/// Install the Ctrl-C handler, and get the token it cancels.
+///
+/// Only supported on unix, elsewhere Ctrl-C keeps quitting the program.
+pub fn install() -> CancelToken {
+    let token = TOKEN.get_or_init(CancelToken::default).clone();
+
+    #[cfg(unix)]
+    unsafe {
+        let handler: extern "C" fn(libc::c_int) = on_interrupt;
+        libc::signal(libc::SIGINT, handler as libc::sighandler_t);
+    }
+
+    #[cfg(not(unix))]
+    log::info!("cancelling commands with Ctrl-C is not supported, it quits the program instead");
+
+    token
+}
+
+/// Mark a command as running, until the returned guard is dropped.
+///
+/// The cancellation is cleared, so that an earlier Ctrl-C does not cancel the command.
+pub fn busy() -> Busy {
+    if let Some(token) = TOKEN.get() {
+        token.reset();
+    }
+
+    BUSY.store(true, Ordering::SeqCst);
+
+    Busy(())
+}
+
+/// Guard of a running command.
+pub struct Busy(());
+
+impl Drop for Busy {
+    fn drop(&mut self) {
+        BUSY.store(false, Ordering::SeqCst);
+
+        if TOKEN.get().is_some_and(CancelToken::is_cancelled) {
+            println!("Interrupted");
+        }
+    }
+}
+
+// Only async-signal-safe operations are allowed here, which atomics, and `write` are.
+#[cfg(unix)]
+extern "C" fn on_interrupt(_: libc::c_int) {
+    match TOKEN.get() {
+        Some(token) if BUSY.load(Ordering::SeqCst) && !token.is_cancelled() => {
+            const MSG: &[u8] = b"\nInterrupting, press Ctrl-C again to quit\n";
+            token.cancel();
+            unsafe { libc::write(libc::STDERR_FILENO, MSG.as_ptr().cast(), MSG.len()) };
+        }
+        _ => unsafe { libc::_exit(130) },
+    }
+}
@@ -10,10 +10,16 @@ use simplelog::{Config, TermLogger, TerminalMode};
 extern crate scan_fmt;
 
 mod cli;
+#[cfg(feature = "clipboard")]
+mod clipboard;
+mod interrupt;
+mod session_log;
 
-fn main() -> Result<()> {
+use session_log::SessionLog;
+
+fn main() -> scanflow::error::Result<()> {
     let matches = parse_args();
-    let (chain, target, elevate, level) = extract_args(&matches)?;
+    let (chain, baseline, target, elevate, level, log_file) = extract_args(&matches)?;
 
     if elevate {
         #[cfg(unix)]
@@ -29,18 +35,27 @@ fn main() -> Result<()> {
     )
     .unwrap();
 
+    let session_log = log_file.map(SessionLog::open).transpose()?;
+
     let inventory = Inventory::scan();
 
     match chain {
         Left(chain) => {
             let target = target.expect("In OS mode target program must be supplied");
             let os = inventory.builder().os_chain(chain).build()?;
-            let process = os.into_process_by_name(&target)?;
-            cli::run(process)
+            let process = os.into_process_by_name(target)?;
+            if baseline.is_some() {
+                log::warn!("baseline snapshots are only supported without an os");
+            }
+            cli::run(process, session_log)
         }
         Right(chain) => {
             let conn = inventory.builder().connector_chain(chain).build()?;
-            cli::run_with_view(conn.into_phys_view())
+            let baseline = baseline
+                .map(|chain| inventory.builder().connector_chain(chain).build())
+                .transpose()?
+                .map(|conn| conn.into_phys_view());
+            cli::run_with_view(conn.into_phys_view(), baseline, session_log)
         }
     }
 }
@@ -66,24 +81,42 @@ fn parse_args() -> ArgMatches {
                 .required(false)
                 .multiple_occurrences(true),
         )
+        .arg(
+            Arg::new("baseline")
+                .long("baseline")
+                .short('b')
+                .help("connector of an earlier snapshot of the memory, to diff against")
+                .takes_value(true)
+                .required(false)
+                .multiple_occurrences(true),
+        )
         .arg(
             Arg::new("elevate")
                 .long("elevate")
                 .short('e')
                 .required(false),
         )
+        .arg(
+            Arg::new("log-file")
+                .long("log-file")
+                .help("append a JSON record of every command to the given file")
+                .takes_value(true)
+                .required(false),
+        )
         .arg(Arg::new("program").takes_value(true).required(false))
         .get_matches()
 }
 
-fn extract_args(
-    matches: &ArgMatches,
-) -> Result<(
-    Either<OsChain, ConnectorChain>,
-    Option<&str>,
+type Args<'a> = (
+    Either<OsChain<'a>, ConnectorChain<'a>>,
+    Option<ConnectorChain<'a>>,
+    Option<&'a str>,
     bool,
     log::Level,
-)> {
+    Option<&'a str>,
+);
+
+fn extract_args(matches: &ArgMatches) -> Result<Args<'_>> {
     // set log level
     let level = match matches.occurrences_of("verbose") {
         0 => Level::Error,
@@ -110,6 +143,12 @@ fn extract_args(
         .flatten()
         .collect::<Vec<_>>();
 
+    let baseline = matches
+        .indices_of("baseline")
+        .zip(matches.values_of("baseline"))
+        .map(|(a, b)| ConnectorChain::new(a.zip(b), std::iter::empty()))
+        .transpose()?;
+
     Ok((
         if let Ok(chain) = OsChain::new(conn_iter.iter().copied(), os_iter.iter().copied()) {
             Left(chain)
@@ -119,8 +158,10 @@ fn extract_args(
                 os_iter.into_iter(),
             )?)
         },
+        baseline,
         matches.value_of("program"),
         matches.occurrences_of("elevate") > 0,
         level,
+        matches.value_of("log-file"),
     ))
 }
@@ -0,0 +1,62 @@
+//! Machine-readable session log.
+//!
+//! Every CLI command is appended to the log as a single line JSON record, alongside its outcome.
+//! Unlike the debug log, this is meant as an audit trail of how results were found.
+
+use scanflow::error::Result;
+use serde_json::{json, Value};
+
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+pub struct SessionLog {
+    file: File,
+}
+
+impl SessionLog {
+    /// Open a session log, appending to the file if it already exists.
+    ///
+    /// # Arguments
+    /// * `path` - path to the log file
+    pub fn open(path: &str) -> Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self { file })
+    }
+
+    /// Append a record of a command.
+    ///
+    /// # Arguments
+    /// * `command` - command that was run
+    /// * `args` - arguments of the command
+    /// * `error` - error the command failed with, if any
+    /// * `matches` - number of value scanner matches after the command
+    /// * `results` - results the command produced, such as pointer chains, or signatures
+    pub fn record(
+        &mut self,
+        command: &str,
+        args: &str,
+        error: Option<String>,
+        matches: usize,
+        results: Vec<String>,
+    ) {
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs_f64())
+            .unwrap_or_default();
+
+        let record: Value = json!({
+            "time": time,
+            "command": command,
+            "args": args,
+            "ok": error.is_none(),
+            "error": error,
+            "matches": matches,
+            "results": results,
+        });
+
+        if let Err(e) = writeln!(self.file, "{}", record) {
+            log::warn!("unable to write to the session log: {}", e);
+        }
+    }
+}
@@ -0,0 +1,265 @@
+//! Decoding of references to globals from machine code.
+//!
+//! `Disasm::collect_globals` goes through code sections with the `RefDecoder` of the process
+//! architecture, as picked by `decoder_for`.
+
+use crate::disasm::RefKind;
+use crate::error::{Error, Result};
+use iced_x86::{Decoder, DecoderOptions};
+use memflow::prelude::v1::*;
+use std::convert::TryInto;
+
+/// Number of instructions an `adrp` is paired with following instructions within.
+const ADRP_WINDOW: usize = 8;
+
+/// Instruction referencing a global, the global, and how it is referenced.
+pub type GlobalRef = (Address, Address, RefKind);
+
+/// Finds references to globals in the machine code of one architecture.
+pub trait RefDecoder: Send + Sync {
+    /// Decode instructions, collecting the globals they reference.
+    ///
+    /// Only instructions starting before `end` are collected. `code` may extend past `end`, so
+    /// that instructions crossing it can be decoded whole.
+    ///
+    /// Returns the references found, and the address following the last decoded instruction,
+    /// where decoding of the next chunk resumes.
+    ///
+    /// # Arguments
+    /// * `code` - machine code to decode
+    /// * `ip` - address of the first byte of `code`
+    /// * `end` - address to stop decoding at
+    fn decode_refs(&self, code: &[u8], ip: umem, end: umem) -> (Vec<GlobalRef>, umem);
+}
+
+/// Get the decoder for the code of an architecture.
+///
+/// Fails with `Error::InvalidArgument` if there is no decoder for the architecture.
+///
+/// # Arguments
+/// * `arch` - architecture of the code
+pub fn decoder_for(arch: ArchitectureIdent) -> Result<Box<dyn RefDecoder>> {
+    match arch {
+        ArchitectureIdent::X86(bits, _) => Ok(Box::new(X86Decoder::new(bits.into()))),
+        ArchitectureIdent::AArch64(_) => Ok(Box::new(Arm64Decoder)),
+        _ => Err(Error::InvalidArgument(format!(
+            "no disassembler for {} code",
+            arch
+        ))),
+    }
+}
+
+/// Decodes x86 code.
+///
+/// Any instruction with an instruction pointer relative memory operand references a global,
+/// except for branches.
+pub struct X86Decoder {
+    bitness: u32,
+}
+
+impl X86Decoder {
+    /// Create a decoder for x86 code.
+    ///
+    /// # Arguments
+    /// * `bitness` - 16, 32, or 64
+    pub fn new(bitness: u32) -> Self {
+        Self { bitness }
+    }
+}
+
+impl RefDecoder for X86Decoder {
+    fn decode_refs(&self, code: &[u8], ip: umem, end: umem) -> (Vec<GlobalRef>, umem) {
+        let mut decoder = Decoder::new(self.bitness, code, DecoderOptions::NONE);
+        decoder.set_ip(ip);
+
+        let mut next = end;
+
+        let refs = decoder
+            .into_iter()
+            .take_while(|i| (i.ip() as umem) < end) // we do not overflow the limit
+            .inspect(|i| next = (i.ip() as umem) + i.len() as umem) // sets next to next instruction addr
+            .filter(|i| i.is_ip_rel_memory_operand()) // uses IP relative memory
+            .filter(|i| i.near_branch_target() == 0) // is not a branch (call/jump)
+            .map(|i| {
+                (
+                    Address::from(i.ip()),
+                    Address::from(i.ip_rel_memory_address()),
+                    RefKind::from_mnemonic(i.mnemonic()),
+                )
+            })
+            .collect();
+
+        (refs, next)
+    }
+}
+
+/// Decodes AArch64 code.
+///
+/// AArch64 has no instruction pointer relative memory operands. Instead, `adrp` puts the 4 KiB page
+/// of a global into a register, and a following `add`, or load, or store adds the offset within
+/// the page. Such pairs are recognized, with the reference recorded at the second instruction.
+/// `adr`, and literal loads, which address within 1 MiB of themselves, are recognized as well.
+///
+/// `add`, and `adr` compute addresses, and are thus reported as `RefKind::Lea`, while loads, and
+/// stores are reported as `RefKind::Mov`.
+pub struct Arm64Decoder;
+
+impl RefDecoder for Arm64Decoder {
+    fn decode_refs(&self, code: &[u8], ip: umem, end: umem) -> (Vec<GlobalRef>, umem) {
+        // Page put into every register by `adrp`, and the index of the `adrp`.
+        let mut pages: [Option<(umem, usize)>; 32] = [None; 32];
+        let mut refs = vec![];
+        let mut next = ip;
+
+        for (idx, word) in code.chunks_exact(4).enumerate() {
+            let cur = ip + (idx * 4) as umem;
+
+            for page in pages.iter_mut() {
+                if page.is_some_and(|(_, at)| idx - at > ADRP_WINDOW) {
+                    *page = None;
+                }
+            }
+
+            // Instructions past the end are only decoded to complete pairs started before it.
+            if cur >= end {
+                if pages.iter().all(Option::is_none) {
+                    break;
+                }
+            } else {
+                next = cur + 4;
+            }
+
+            let w = u32::from_le_bytes(word.try_into().unwrap());
+            let rd = (w & 0x1f) as usize;
+            let rn = ((w >> 5) & 0x1f) as usize;
+
+            if w & 0x9f00_0000 == 0x9000_0000 {
+                // adrp
+                if cur < end && rd != 31 {
+                    let imm = sign_extend(adr_imm(w), 21) << 12;
+                    pages[rd] = Some(((cur & !0xfff).wrapping_add(imm as umem), idx));
+                }
+                continue;
+            }
+
+            if w & 0x7f80_0000 == 0x1100_0000 {
+                // add (immediate)
+                if let Some((page, _)) = pages[rn] {
+                    let shift = if w & (1 << 22) != 0 { 12 } else { 0 };
+                    let imm = (((w >> 10) & 0xfff) as umem) << shift;
+                    refs.push((cur.into(), page.wrapping_add(imm).into(), RefKind::Lea));
+                }
+                pages[rd] = None;
+                continue;
+            }
+
+            if w & 0x3b00_0000 == 0x3900_0000 {
+                // load, or store (unsigned immediate offset)
+                let simd = w & (1 << 26) != 0;
+                let opc = (w >> 22) & 3;
+                let scale = if simd && opc & 2 != 0 { 4 } else { w >> 30 };
+
+                if let Some((page, _)) = pages[rn] {
+                    let imm = (((w >> 10) & 0xfff) as umem) << scale;
+                    refs.push((cur.into(), page.wrapping_add(imm).into(), RefKind::Mov));
+                }
+
+                if !simd && opc != 0 {
+                    pages[rd] = None;
+                }
+                continue;
+            }
+
+            if cur >= end {
+                continue;
+            }
+
+            if w & 0x9f00_0000 == 0x1000_0000 {
+                // adr
+                let imm = sign_extend(adr_imm(w), 21);
+                refs.push((
+                    cur.into(),
+                    cur.wrapping_add(imm as umem).into(),
+                    RefKind::Lea,
+                ));
+            } else if w & 0x3b00_0000 == 0x1800_0000 && w >> 30 != 3 {
+                // ldr (literal), except for prfm
+                let imm = sign_extend((w >> 5) & 0x7ffff, 19) << 2;
+                refs.push((
+                    cur.into(),
+                    cur.wrapping_add(imm as umem).into(),
+                    RefKind::Mov,
+                ));
+            }
+        }
+
+        (refs, next.max(end))
+    }
+}
+
+/// Get the immediate of `adr`, and `adrp`, joining its high, and low parts.
+fn adr_imm(w: u32) -> u32 {
+    (((w >> 5) & 0x7ffff) << 2) | ((w >> 29) & 3)
+}
+
+/// Sign extend the lowest `bits` bits of a value.
+fn sign_extend(v: u32, bits: u32) -> i64 {
+    let shift = 64 - bits;
+    ((v as i64) << shift) >> shift
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code(words: &[u32]) -> Vec<u8> {
+        words.iter().flat_map(|w| w.to_le_bytes()).collect()
+    }
+
+    #[test]
+    fn arm64_adrp_with_scaled_loads() {
+        let code = code(&[
+            0xd0ff_ffe0, // adrp x0, #-0x2000
+            0xf940_0c01, // ldr x1, [x0, #0x18]
+            0xb940_2402, // ldr w2, [x0, #0x24]
+        ]);
+
+        let (refs, next) = Arm64Decoder.decode_refs(&code, 0x405100, 0x40510c);
+
+        assert_eq!(next, 0x40510c);
+        assert_eq!(
+            refs,
+            vec![
+                (
+                    Address::from(0x405104u64),
+                    Address::from(0x403018u64),
+                    RefKind::Mov
+                ),
+                (
+                    Address::from(0x405108u64),
+                    Address::from(0x403024u64),
+                    RefKind::Mov
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn arm64_adrp_with_add() {
+        let code = code(&[
+            0x9000_0010, // adrp x16, #0
+            0x9104_2210, // add x16, x16, #0x108
+        ]);
+
+        let (refs, _) = Arm64Decoder.decode_refs(&code, 0x1ffc, 0x2004);
+
+        assert_eq!(
+            refs,
+            vec![(
+                Address::from(0x2000u64),
+                Address::from(0x1108u64),
+                RefKind::Lea
+            )]
+        );
+    }
+}
@@ -1,19 +1,175 @@
 use memflow::prelude::v1::*;
 
+use crate::decoder::decoder_for;
+use crate::error::{Error, Result};
 use crate::pbar::PBar;
-use iced_x86::{Decoder, DecoderOptions};
+use crate::persist::{Reader, Writer};
+use crate::task::CancelToken;
+use crate::value_scanner::bytes_to_u64;
+use iced_x86::Mnemonic;
 
 use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use std::sync::Mutex;
 
 use rayon::prelude::*;
 use rayon_tlsctx::ThreadLocalCtx;
 
 /// Describes a disassembler state.
 #[derive(Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(remote = "Self")
+)]
 pub struct Disasm {
     map: BTreeMap<Address, Address>,
+    #[cfg_attr(feature = "serde", serde(skip))]
     inverse_map: BTreeMap<Address, Vec<Address>>,
+    /// Kind of every reference in `map`.
+    kinds: BTreeMap<Address, RefKind>,
+    #[cfg_attr(feature = "serde", serde(skip))]
     globals: Vec<Address>,
+    modules: Vec<(String, Address, umem)>,
+    /// Sections of the modules, sorted by their base address.
+    sections: Vec<(String, Address, umem)>,
+    /// Whether the last collection went through all of its modules.
+    #[cfg_attr(feature = "serde", serde(default))]
+    complete: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    cancel: Option<CancelToken>,
+}
+
+/// Only the references are stored, the inverse map, and the globals are rebuilt from them.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Disasm {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        Disasm::serialize(self, serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Disasm {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        let mut ret = Disasm::deserialize(deserializer)?;
+        ret.rebuild_globals();
+        Ok(ret)
+    }
+}
+
+const FILE_MAGIC: &[u8; 4] = b"SFDA";
+const FILE_VERSION: u32 = 5;
+
+/// Module index stored in place of a module, for addresses outside of all modules.
+const NO_MODULE: u64 = u64::MAX;
+
+/// How a global is referenced.
+///
+/// A `lea` computes the address of a global, which is typically the base of a structure, or an
+/// array, while a `mov` loads, or stores a scalar value. Globals referenced by `lea` thus tend to
+/// be better roots for offset scans. On AArch64, `add`, and `adr` count as `lea`, while loads,
+/// and stores count as `mov`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RefKind {
+    /// Address computed by a `lea` instruction, or its equivalent.
+    Lea,
+    /// Value loaded, or stored by a `mov` instruction, or its equivalent.
+    Mov,
+    /// Any other instruction using the global as an operand.
+    Other,
+    /// Pointer stored in a data section, found by `collect_data_globals`.
+    Data,
+}
+
+/// How `Disasm::collect_globals_with` treats a module section.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SectionUse {
+    /// Disassemble the section, collecting the globals its instructions reference.
+    Code,
+    /// Search the section for aligned pointers into its module, such as vtables, and jump tables.
+    Pointers,
+    /// Leave the section out.
+    Skip,
+}
+
+/// Decides how `Disasm::collect_globals_with` treats every module section.
+pub type SectionFilter = fn(&SectionInfo) -> SectionUse;
+
+/// `SectionFilter` disassembling code sections, and leaving out the rest.
+///
+/// This is what `Disasm::collect_globals` does.
+pub fn code_sections(section: &SectionInfo) -> SectionUse {
+    if section.is_text() {
+        SectionUse::Code
+    } else {
+        SectionUse::Skip
+    }
+}
+
+/// `SectionFilter` disassembling code sections, and searching all other sections for pointers.
+///
+/// This is what `Disasm::collect_globals`, followed by `Disasm::collect_data_globals` does.
+pub fn code_and_data_sections(section: &SectionInfo) -> SectionUse {
+    if section.is_text() {
+        SectionUse::Code
+    } else {
+        SectionUse::Pointers
+    }
+}
+
+/// `SectionFilter` disassembling code sections, and searching read-only data sections for
+/// pointers.
+///
+/// Vtables, and jump tables are usually read-only, thus this finds them without going through
+/// writable data, where stale, and heap pointers are more common. Sections are judged by their
+/// name, see `Disasm::is_writable_section`.
+pub fn code_and_rodata_sections(section: &SectionInfo) -> SectionUse {
+    if section.is_text() {
+        SectionUse::Code
+    } else if Disasm::is_writable_section(&section.name) {
+        SectionUse::Skip
+    } else {
+        SectionUse::Pointers
+    }
+}
+
+impl RefKind {
+    pub(crate) fn from_mnemonic(mnemonic: Mnemonic) -> Self {
+        match mnemonic {
+            Mnemonic::Lea => RefKind::Lea,
+            Mnemonic::Mov => RefKind::Mov,
+            _ => RefKind::Other,
+        }
+    }
+
+    fn to_u64(self) -> u64 {
+        match self {
+            RefKind::Lea => 0,
+            RefKind::Mov => 1,
+            RefKind::Other => 2,
+            RefKind::Data => 3,
+        }
+    }
+
+    fn from_u64(v: u64) -> Result<Self> {
+        match v {
+            0 => Ok(RefKind::Lea),
+            1 => Ok(RefKind::Mov),
+            2 => Ok(RefKind::Other),
+            3 => Ok(RefKind::Data),
+            _ => Err(Error::InvalidFormat(format!(
+                "unknown reference kind {}",
+                v
+            ))),
+        }
+    }
 }
 
 impl Disasm {
@@ -21,40 +177,253 @@ impl Disasm {
     pub fn reset(&mut self) {
         self.map.clear();
         self.inverse_map.clear();
+        self.kinds.clear();
         self.globals.clear();
+        self.modules.clear();
+        self.sections.clear();
+        self.complete = false;
+    }
+
+    /// Allow collecting globals to be cancelled with a `CancelToken`.
+    ///
+    /// A cancelled collection stops reading code, and keeps the globals found so far, which
+    /// `complete` reports as incomplete. The token is not reset. `None` disables cancellation,
+    /// which is the default.
+    pub fn set_cancel_token(&mut self, cancel: Option<CancelToken>) {
+        self.cancel = cancel;
+    }
+
+    /// Get the token collecting globals can be cancelled with.
+    pub fn cancel_token(&self) -> Option<&CancelToken> {
+        self.cancel.as_ref()
     }
 
     /// Collect global variables to the state.
     ///
-    /// Global variables can then be accessed through `map`, `inverse_map`, `globals` calls.
+    /// Global variables can then be accessed through `map`, `inverse_map`, `globals` calls. Code is
+    /// decoded according to the process architecture, see `decoder::decoder_for`, which supports
+    /// x86, and AArch64.
     ///
     /// # Arguments
     ///
     /// * `process` - target process to find the variables in
+    /// * `module` - optional name of the module to limit the search to
     pub fn collect_globals(
         &mut self,
         process: &mut (impl Process + MemoryView + Clone),
         module: Option<&str>,
     ) -> Result<()> {
+        self.collect_code_globals(process, module, code_sections)
+    }
+
+    /// Collect global variables to the state, choosing how every section is searched.
+    ///
+    /// Sections the filter marks as `SectionUse::Code` are disassembled as in `collect_globals`,
+    /// while the ones marked as `SectionUse::Pointers` are searched for pointers into their module
+    /// as in `collect_data_globals`. Both end up in the same maps.
+    ///
+    /// # Arguments
+    ///
+    /// * `process` - target process to find the variables in
+    /// * `module` - optional name of the module to limit the search to
+    /// * `filter` - function deciding how to treat every section, such as `code_and_data_sections`
+    pub fn collect_globals_with(
+        &mut self,
+        process: &mut (impl Process + MemoryView + Clone),
+        module: Option<&str>,
+        filter: SectionFilter,
+    ) -> Result<()> {
+        self.collect_code_globals(process, module, filter)?;
+        self.collect_pointer_globals(process, module, filter)
+    }
+
+    fn collect_code_globals(
+        &mut self,
+        process: &mut (impl Process + MemoryView + Clone),
+        module: Option<&str>,
+        filter: SectionFilter,
+    ) -> Result<()> {
+        let decoder = decoder_for(process.info().proc_arch)?;
+
         self.reset();
-        let modules = process.module_list()?;
+        let modules = Self::filter_modules(process.module_list()?, module);
+        self.add_modules(&modules);
 
         const CHUNK_SIZE: usize = size::mb(2);
+        const PAGE_SIZE: usize = size::kb(4);
 
         let ctx = ThreadLocalCtx::new_locked(move || process.clone());
         let ctx_bytes = ThreadLocalCtx::new(|| vec![0; CHUNK_SIZE + 32]);
-        let sections = ThreadLocalCtx::new(|| Vec::<SectionInfo>::new());
+        let sections = ThreadLocalCtx::new(Vec::<SectionInfo>::new);
+        let found_sections = Mutex::new(vec![]);
+
+        let pb = PBar::new(modules.iter().map(|m| m.size).sum::<u64>(), true);
+        let cancelled = || self.cancel.as_ref().is_some_and(CancelToken::is_cancelled);
+
+        let refs = modules
+            .into_par_iter()
+            .filter_map(|m| {
+                if cancelled() {
+                    return None;
+                }
+
+                let mut process = unsafe { ctx.get() };
+                let mut sections = unsafe { sections.get() };
+
+                sections.clear();
+
+                process
+                    .module_section_list_callback(&m, (&mut *sections).into())
+                    .ok()?;
+
+                std::mem::drop(process);
 
-        let pb = PBar::new(modules.iter().map(|m| m.size as u64).sum::<u64>(), true);
+                found_sections
+                    .lock()
+                    .unwrap()
+                    .extend(sections.iter().cloned());
+
+                let ret = sections
+                    .iter()
+                    .filter(|s| filter(s) == SectionUse::Code)
+                    .par_bridge()
+                    .flat_map(|section| {
+                        let mut process = unsafe { ctx.get() };
+                        let mut bytes = unsafe { ctx_bytes.get() };
+
+                        let start = section.base.to_umem();
+                        let end = start + section.size;
+
+                        let mut addr = start;
+                        let mut ret = vec![];
+
+                        while addr < end && !cancelled() {
+                            let chunk_end = std::cmp::min(end, addr + CHUNK_SIZE as umem);
+
+                            if process
+                                .read_raw_into(addr.into(), &mut bytes)
+                                .data_part()
+                                .is_ok()
+                            {
+                                let (refs, next) = decoder.decode_refs(&bytes, addr, chunk_end);
+                                ret.extend(refs);
+
+                                // continue at the instruction following the last decoded one
+                                addr = std::cmp::max(next, chunk_end);
+                                continue;
+                            }
+
+                            // Module images may contain guard, or unmapped pages, thus the chunk
+                            // is decoded page by page, skipping the pages that fail to read. An
+                            // instruction crossing into the next page is lost this way.
+                            while addr < chunk_end {
+                                let page_end =
+                                    std::cmp::min(chunk_end, align_up(addr + 1, PAGE_SIZE as umem));
+                                let bytes = &mut bytes[..(page_end - addr) as usize];
+
+                                addr = if process
+                                    .read_raw_into(addr.into(), bytes)
+                                    .data_part()
+                                    .is_ok()
+                                {
+                                    let (refs, next) = decoder.decode_refs(bytes, addr, page_end);
+                                    ret.extend(refs);
+                                    std::cmp::max(next, page_end)
+                                } else {
+                                    page_end
+                                };
+                            }
+                        }
+
+                        ret.into_par_iter()
+                    })
+                    .collect::<Vec<_>>()
+                    .into_par_iter();
+
+                pb.add(m.size);
+
+                Some(ret)
+            })
+            .flatten()
+            .collect::<Vec<_>>();
+
+        let complete = !cancelled();
+
+        for (k, v, kind) in refs {
+            self.map.insert(k, v);
+            self.kinds.insert(k, kind);
+        }
+
+        self.add_sections(found_sections.into_inner().unwrap());
+        self.rebuild_globals();
+        self.complete = complete;
+
+        pb.finish();
+
+        Ok(())
+    }
+
+    /// Collect global variables referenced from data sections to the state.
+    ///
+    /// Unlike `collect_globals`, this does not disassemble code, but instead goes through non-code
+    /// sections of each module and looks for aligned absolute pointers that land inside the same
+    /// module. This finds globals that are referenced by other globals (vtables, pointer tables),
+    /// rather than by code.
+    ///
+    /// The results are merged into the existing state, so this is usually called after
+    /// `collect_globals`.
+    ///
+    /// # Arguments
+    ///
+    /// * `process` - target process to find the variables in
+    /// * `module` - optional name of the module to limit the search to
+    pub fn collect_data_globals(
+        &mut self,
+        process: &mut (impl Process + MemoryView + Clone),
+        module: Option<&str>,
+    ) -> Result<()> {
+        self.collect_pointer_globals(process, module, |s| {
+            if s.is_text() {
+                SectionUse::Skip
+            } else {
+                SectionUse::Pointers
+            }
+        })
+    }
+
+    fn collect_pointer_globals(
+        &mut self,
+        process: &mut (impl Process + MemoryView + Clone),
+        module: Option<&str>,
+        filter: SectionFilter,
+    ) -> Result<()> {
+        let modules = Self::filter_modules(process.module_list()?, module);
+        self.add_modules(&modules);
+
+        // Pointer size, and byte order of the target.
+        let pointer = (
+            ArchitectureObj::from(process.info().proc_arch).size_addr(),
+            process.metadata().little_endian,
+        );
+
+        const CHUNK_SIZE: usize = size::mb(2);
+        const PAGE_SIZE: usize = size::kb(4);
+
+        let ctx = ThreadLocalCtx::new_locked(move || process.clone());
+        let ctx_bytes = ThreadLocalCtx::new(|| vec![0; CHUNK_SIZE]);
+        let sections = ThreadLocalCtx::new(Vec::<SectionInfo>::new);
+        let found_sections = Mutex::new(vec![]);
+
+        let pb = PBar::new(modules.iter().map(|m| m.size).sum::<u64>(), true);
+        let cancel = self.cancel.clone();
+        let cancelled = || cancel.as_ref().is_some_and(CancelToken::is_cancelled);
 
         self.map.par_extend(
             modules
                 .into_par_iter()
                 .filter_map(|m| {
-                    if let Some(module) = module {
-                        if m.name.as_ref() != module {
-                            return None;
-                        }
+                    if cancelled() {
+                        return None;
                     }
 
                     let mut process = unsafe { ctx.get() };
@@ -68,9 +437,16 @@ impl Disasm {
 
                     std::mem::drop(process);
 
+                    found_sections
+                        .lock()
+                        .unwrap()
+                        .extend(sections.iter().cloned());
+
+                    let (mod_start, mod_end) = (m.base, m.base + m.size);
+
                     let ret = sections
                         .iter()
-                        .filter(|s| s.is_text())
+                        .filter(|s| filter(s) == SectionUse::Pointers)
                         .par_bridge()
                         .flat_map(|section| {
                             let mut process = unsafe { ctx.get() };
@@ -79,71 +455,254 @@ impl Disasm {
                             let start = section.base.to_umem();
                             let end = start + section.size;
 
-                            let mut addr = start;
+                            // Chunks are aligned, so that a chunk failing to read is skipped
+                            // without affecting the following ones. Within a failed chunk, the
+                            // readable pages are still collected, since module images may contain
+                            // guard or unmapped pages.
+                            std::iter::once(start)
+                                .chain(
+                                    (align_up(start + 1, CHUNK_SIZE as umem)..end)
+                                        .step_by(CHUNK_SIZE),
+                                )
+                                .take_while(|_| !cancelled())
+                                .flat_map(|addr| {
+                                    let chunk_end =
+                                        std::cmp::min(end, align_up(addr + 1, CHUNK_SIZE as umem));
+                                    let bytes = &mut bytes[..(chunk_end - addr) as usize];
 
-                            (addr..end)
-                                .step_by(CHUNK_SIZE)
-                                .filter_map(|_| {
-                                    let end = std::cmp::min(end, addr + CHUNK_SIZE as umem);
-                                    process
-                                        .read_raw_into(addr.into(), &mut bytes)
+                                    if process
+                                        .read_raw_into(addr.into(), bytes)
                                         .data_part()
-                                        .ok()?;
-
-                                    let mut decoder = Decoder::new(
-                                        ArchitectureObj::from(process.info().proc_arch)
-                                            .bits()
-                                            .into(),
-                                        &bytes,
-                                        DecoderOptions::NONE,
-                                    );
-
-                                    decoder.set_ip(addr as u64);
-
-                                    addr += CHUNK_SIZE as umem;
-
-                                    Some(
-                                        decoder
-                                            .into_iter()
-                                            .filter(|i| (i.ip() as umem) < end) // we do not overflow the limit
-                                            .inspect(|i| addr = (i.ip() as umem) + i.len() as umem) // sets addr to next instruction addr
-                                            .filter(|i| i.is_ip_rel_memory_operand()) // uses IP relative memory
-                                            .filter(|i| i.near_branch_target() == 0) // is not a branch (call/jump)
-                                            .map(|i| {
-                                                (
-                                                    Address::from(i.ip()),
-                                                    Address::from(i.ip_rel_memory_address()),
-                                                )
-                                            })
-                                            .collect::<Vec<_>>()
-                                            .into_iter(),
-                                    )
+                                        .is_ok()
+                                    {
+                                        return pointers_in(
+                                            bytes, addr, pointer, mod_start, mod_end,
+                                        );
+                                    }
+
+                                    let mut ret = vec![];
+                                    let mut page = addr;
+
+                                    while page < chunk_end {
+                                        let page_end = std::cmp::min(
+                                            chunk_end,
+                                            align_up(page + 1, PAGE_SIZE as umem),
+                                        );
+                                        let bytes = &mut bytes
+                                            [(page - addr) as usize..(page_end - addr) as usize];
+
+                                        if process
+                                            .read_raw_into(page.into(), bytes)
+                                            .data_part()
+                                            .is_ok()
+                                        {
+                                            ret.extend(pointers_in(
+                                                bytes, page, pointer, mod_start, mod_end,
+                                            ));
+                                        }
+
+                                        page = page_end;
+                                    }
+
+                                    ret
                                 })
-                                .flatten()
                                 .collect::<Vec<_>>()
                                 .into_par_iter()
                         })
                         .collect::<Vec<_>>()
                         .into_par_iter();
 
-                    pb.add(m.size as u64);
+                    pb.add(m.size);
 
                     Some(ret)
                 })
                 .flatten(),
         );
 
-        for (&k, &v) in &self.map {
-            self.inverse_map.entry(v).or_default().push(k);
+        for &k in self.map.keys() {
+            self.kinds.entry(k).or_insert(RefKind::Data);
         }
 
-        self.globals = self.inverse_map.keys().copied().collect();
+        self.add_sections(found_sections.into_inner().unwrap());
+        self.rebuild_globals();
+        self.complete &= !cancelled();
 
         pb.finish();
 
         Ok(())
     }
 
+    /// Save the collected globals to a file.
+    ///
+    /// The file also stores the names, and sizes of the modules the globals were collected from,
+    /// so that `load` can reject globals of a different build of the program. Addresses within the
+    /// modules are stored relative to them, so that the globals can be loaded after the modules
+    /// moved.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - path of the file to write
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let mut w = Writer::create(path, FILE_MAGIC, FILE_VERSION)?;
+
+        w.u64(self.complete as u64)?;
+
+        w.u64(self.modules.len() as u64)?;
+        for (name, _, size) in &self.modules {
+            w.str(name)?;
+            w.u64(*size)?;
+        }
+
+        w.u64(self.sections.len() as u64)?;
+        for (name, base, size) in &self.sections {
+            w.str(name)?;
+            self.write_addr(&mut w, *base)?;
+            w.u64(*size)?;
+        }
+
+        w.u64(self.map.len() as u64)?;
+        for (&k, &v) in &self.map {
+            self.write_addr(&mut w, k)?;
+            self.write_addr(&mut w, v)?;
+            w.u64(self.ref_kind(k).unwrap_or(RefKind::Other).to_u64())?;
+        }
+
+        w.finish()
+    }
+
+    /// Load globals previously stored with `save`.
+    ///
+    /// The globals are rebased on the current base of their modules. Loading fails if any of the
+    /// stored modules is not loaded in the process, or has a different size.
+    ///
+    /// # Arguments
+    ///
+    /// * `process` - target process to validate the globals against
+    /// * `path` - path of the file to read
+    pub fn load(&mut self, process: &mut impl Process, path: impl AsRef<Path>) -> Result<()> {
+        let mut r = Reader::open(path, FILE_MAGIC, FILE_VERSION)?;
+
+        let current = process.module_list()?;
+
+        let complete = r.u64()? != 0;
+
+        let mut modules = vec![];
+
+        for _ in 0..r.u64()? {
+            let name = r.str()?;
+            let size = r.u64()?;
+
+            let module = current
+                .iter()
+                .find(|m| m.name.as_ref() == name && m.size == size)
+                .ok_or_else(|| {
+                    Error::StaleData(format!("module `{}` ({:x}) is not loaded", name, size))
+                })?;
+
+            modules.push((name, module.base, size));
+        }
+
+        let bases = modules.iter().map(|&(_, base, _)| base).collect::<Vec<_>>();
+
+        let mut sections = vec![];
+
+        for _ in 0..r.u64()? {
+            let name = r.str()?;
+            let base = Self::read_addr(&mut r, &bases)?;
+            let size = r.u64()?;
+            sections.push((name, base, size));
+        }
+
+        let mut map = BTreeMap::new();
+        let mut kinds = BTreeMap::new();
+
+        for _ in 0..r.u64()? {
+            let k = Self::read_addr(&mut r, &bases)?;
+            let v = Self::read_addr(&mut r, &bases)?;
+            map.insert(k, v);
+            kinds.insert(k, RefKind::from_u64(r.u64()?)?);
+        }
+
+        self.reset();
+        self.map = map;
+        self.kinds = kinds;
+        self.modules = modules;
+        self.sections = sections;
+        self.sections.sort_by_key(|&(_, base, _)| base);
+        self.rebuild_globals();
+        self.complete = complete;
+
+        Ok(())
+    }
+
+    /// Write an address as the index of the module containing it, and the offset into it.
+    fn write_addr(&self, w: &mut Writer, addr: Address) -> Result<()> {
+        match self
+            .modules
+            .iter()
+            .position(|&(_, base, size)| base <= addr && base + size > addr)
+        {
+            Some(idx) => {
+                w.u64(idx as u64)?;
+                w.u64(addr.to_umem() - self.modules[idx].1.to_umem())
+            }
+            None => {
+                w.u64(NO_MODULE)?;
+                w.u64(addr.to_umem())
+            }
+        }
+    }
+
+    /// Read an address written with `write_addr`, rebasing it on the current module bases.
+    fn read_addr(r: &mut Reader, bases: &[Address]) -> Result<Address> {
+        let idx = r.u64()?;
+        let off = r.u64()?;
+
+        if idx == NO_MODULE {
+            return Ok(Address::from(off));
+        }
+
+        bases
+            .get(idx as usize)
+            .map(|&base| base + off)
+            .ok_or_else(|| Error::InvalidFormat(format!("unknown module index {}", idx)))
+    }
+
+    fn filter_modules(modules: Vec<ModuleInfo>, module: Option<&str>) -> Vec<ModuleInfo> {
+        modules
+            .into_iter()
+            .filter(|m| module.map(|n| m.name.as_ref() == n).unwrap_or(true))
+            .collect()
+    }
+
+    fn add_modules(&mut self, modules: &[ModuleInfo]) {
+        for m in modules {
+            if !self.modules.iter().any(|(_, base, _)| *base == m.base) {
+                self.modules.push((m.name.to_string(), m.base, m.size));
+            }
+        }
+    }
+
+    fn add_sections(&mut self, sections: Vec<SectionInfo>) {
+        for s in sections {
+            if !self.sections.iter().any(|(_, base, _)| *base == s.base) {
+                self.sections.push((s.name.to_string(), s.base, s.size));
+            }
+        }
+
+        self.sections.sort_by_key(|&(_, base, _)| base);
+    }
+
+    fn rebuild_globals(&mut self) {
+        self.inverse_map.clear();
+
+        for (&k, &v) in &self.map {
+            self.inverse_map.entry(v).or_default().push(k);
+        }
+
+        self.globals = self.inverse_map.keys().copied().collect();
+    }
+
     pub fn map(&self) -> &BTreeMap<Address, Address> {
         &self.map
     }
@@ -155,4 +714,313 @@ impl Disasm {
     pub fn globals(&self) -> &Vec<Address> {
         &self.globals
     }
+
+    /// Check whether the globals were collected from all of their modules.
+    ///
+    /// This is false before any collection, and after one that was cancelled, in which case the
+    /// globals are only partial, and have to be collected again.
+    pub fn complete(&self) -> bool {
+        self.complete
+    }
+
+    /// Get the name of the module section containing an address.
+    ///
+    /// Sections are known for the modules globals were collected from.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - address to find the section of
+    pub fn section_of(&self, addr: Address) -> Option<&str> {
+        let idx = self.sections.partition_point(|&(_, base, _)| base <= addr);
+
+        idx.checked_sub(1)
+            .map(|i| &self.sections[i])
+            .filter(|&&(_, base, size)| addr < base + size)
+            .map(|(name, _, _)| name.as_str())
+    }
+
+    /// Check whether a section is writable at runtime, judging by its name.
+    ///
+    /// Memflow does not expose section flags, thus the usual names of writable data sections are
+    /// recognized instead. Read-only data, such as `.rdata`, or `.rodata`, is not writable.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - name of the section
+    pub fn is_writable_section(name: &str) -> bool {
+        let name = name.trim_start_matches('.').trim_start_matches("__");
+
+        matches!(
+            name,
+            "data"
+                | "bss"
+                | "tls"
+                | "got"
+                | "got.plt"
+                | "common"
+                | "la_symbol_ptr"
+                | "nl_symbol_ptr"
+        )
+    }
+
+    /// Get the globals located in a specific section.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - name of the section, such as `.data`
+    pub fn globals_in_section(&self, name: &str) -> Vec<Address> {
+        self.globals
+            .iter()
+            .copied()
+            .filter(|&g| self.section_of(g) == Some(name))
+            .collect()
+    }
+
+    /// Get the globals located in writable data sections.
+    ///
+    /// These are the most likely to hold variables, rather than constants, or code.
+    pub fn writable_globals(&self) -> Vec<Address> {
+        self.globals
+            .iter()
+            .copied()
+            .filter(|&g| self.section_of(g).is_some_and(Self::is_writable_section))
+            .collect()
+    }
+
+    /// Get how the global is referenced at an address.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - address of the referencing instruction, or data pointer, as in `map`
+    pub fn ref_kind(&self, addr: Address) -> Option<RefKind> {
+        self.kinds.get(&addr).copied()
+    }
+
+    /// Get the globals referenced at least once in a specific way.
+    ///
+    /// Globals referenced by `lea` are usually the bases of structures, or arrays, which makes them
+    /// good anchors for offset scans.
+    ///
+    /// # Arguments
+    ///
+    /// * `kind` - kind of reference to look for
+    pub fn globals_by_kind(&self, kind: RefKind) -> Vec<Address> {
+        self.inverse_map
+            .iter()
+            .filter(|(_, refs)| refs.iter().any(|&r| self.ref_kind(r) == Some(kind)))
+            .map(|(&g, _)| g)
+            .collect()
+    }
+
+    /// Iterate over instructions, and the globals they reference.
+    ///
+    /// Entries are ordered by the instruction address.
+    pub fn iter_edges(&self) -> impl Iterator<Item = (Address, Address)> + '_ {
+        self.map.iter().map(|(&k, &v)| (k, v))
+    }
+
+    /// Iterate over globals, and the instructions referencing them.
+    ///
+    /// Entries are ordered by the global address.
+    pub fn iter_targets(&self) -> impl Iterator<Item = (Address, &[Address])> + '_ {
+        self.inverse_map.iter().map(|(&k, v)| (k, v.as_slice()))
+    }
+}
+
+/// Summarizes the disassembler state, rather than listing every reference.
+impl fmt::Debug for Disasm {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let range = match (self.globals.first(), self.globals.last()) {
+            (Some(first), Some(last)) => format!("{:x}..={:x}", first, last),
+            _ => "none".into(),
+        };
+
+        f.debug_struct("Disasm")
+            .field("references", &self.map.len())
+            .field("globals", &self.globals.len())
+            .field("writable_globals", &self.writable_globals().len())
+            .field("global_range", &format_args!("{}", range))
+            .field("modules", &self.modules.len())
+            .field("sections", &self.sections.len())
+            .finish()
+    }
+}
+
+/// Round an address up to a multiple of `align`.
+fn align_up(addr: umem, align: umem) -> umem {
+    addr.div_ceil(align) * align
+}
+
+/// Find pointers into a module within a buffer read from `addr`.
+fn pointers_in(
+    bytes: &[u8],
+    addr: umem,
+    (size_addr, little_endian): (usize, bool),
+    mod_start: Address,
+    mod_end: Address,
+) -> Vec<(Address, Address)> {
+    bytes
+        .chunks_exact(size_addr)
+        .enumerate()
+        .filter_map(|(i, buf)| {
+            let target = Address::from(bytes_to_u64(buf, little_endian));
+            if target >= mod_start && target < mod_end {
+                Some((Address::from(addr) + i * size_addr, target))
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::DummyView;
+
+    const BASE: u64 = 0x10000;
+
+    #[test]
+    fn pointers_in_reads_target_byte_order() {
+        let mut bytes = vec![0u8; 0x20];
+        bytes[0x8..0x10].copy_from_slice(&0x1010u64.to_be_bytes());
+        bytes[0x10..0x18].copy_from_slice(&0x1018u64.to_le_bytes());
+
+        let found = pointers_in(
+            &bytes,
+            0x1000,
+            (8, false),
+            Address::from(0x1000u64),
+            Address::from(0x2000u64),
+        );
+
+        assert_eq!(
+            found,
+            vec![(Address::from(0x1008u64), Address::from(0x1010u64))]
+        );
+    }
+
+    #[test]
+    fn unreadable_pages_are_skipped() {
+        let text = 0x300000;
+        let mut data = vec![0u8; 0x400000];
+
+        // mov rax, [rip + 0x100] before, and after the unreadable page of the first chunk, and in
+        // the following chunk.
+        let code = [0x48, 0x8b, 0x05, 0x00, 0x01, 0x00, 0x00];
+        for off in [0x10, 0x2010, 0x250000] {
+            data[off..(off + code.len())].copy_from_slice(&code);
+        }
+
+        // Pointers into the module before, and after the unreadable page of the data section.
+        for off in [text + 0x8, text + 0x2008] {
+            data[off..(off + 8)].copy_from_slice(&(BASE + 0x40).to_le_bytes());
+        }
+
+        let mut view = DummyView::new(BASE.into(), data);
+        view.add_module(
+            "dummy.exe",
+            BASE.into(),
+            0x400000,
+            &[
+                (".text", 0, text as umem),
+                (".rdata", text as umem, 0x100000),
+            ],
+        );
+        view.set_unreadable((BASE + 0x1000).into()..(BASE + 0x2000).into());
+        view.set_unreadable(
+            (BASE + text as u64 + 0x1000).into()..(BASE + text as u64 + 0x2000).into(),
+        );
+
+        let mut disasm = Disasm::default();
+        disasm
+            .collect_globals_with(&mut view, None, code_and_rodata_sections)
+            .unwrap();
+
+        let refs = |off: u64| (Address::from(BASE + off), Address::from(BASE + off + 0x107));
+        let mut expected = [0x10, 0x2010, 0x250000].map(refs).to_vec();
+        expected.extend(
+            [text + 0x8, text + 0x2008]
+                .map(|off| (Address::from(BASE + off as u64), Address::from(BASE + 0x40))),
+        );
+        expected.sort();
+
+        assert_eq!(
+            disasm
+                .map()
+                .iter()
+                .map(|(&k, &v)| (k, v))
+                .collect::<Vec<_>>(),
+            expected
+        );
+    }
+
+    #[test]
+    fn load_rebases_saved_globals() {
+        let mut data = vec![0u8; 0x1000];
+        // mov rax, [rip + 0x100]
+        data[0x10..0x17].copy_from_slice(&[0x48, 0x8b, 0x05, 0x00, 0x01, 0x00, 0x00]);
+
+        let mut view = DummyView::new(BASE.into(), data.clone());
+        view.add_module("dummy.exe", BASE.into(), 0x1000, &[(".text", 0, 0x1000)]);
+
+        let mut disasm = Disasm::default();
+        disasm.collect_globals(&mut view, None).unwrap();
+
+        let path = std::env::temp_dir().join(format!("scanflow-globals-{}", std::process::id()));
+        disasm.save(&path).unwrap();
+
+        // The module moved, as after a restart.
+        let moved = 0x50000u64;
+        let mut view = DummyView::new(moved.into(), data.clone());
+        view.add_module("dummy.exe", moved.into(), 0x1000, &[(".text", 0, 0x1000)]);
+
+        let mut loaded = Disasm::default();
+        loaded.load(&mut view, &path).unwrap();
+
+        assert_eq!(
+            loaded
+                .map()
+                .iter()
+                .map(|(&k, &v)| (k, v))
+                .collect::<Vec<_>>(),
+            vec![(Address::from(moved + 0x10), Address::from(moved + 0x117))]
+        );
+        assert_eq!(loaded.section_of((moved + 0x10).into()), Some(".text"));
+        assert!(loaded.complete());
+
+        // A different build of the module.
+        let mut view = DummyView::new(moved.into(), data);
+        view.add_module("dummy.exe", moved.into(), 0x2000, &[(".text", 0, 0x1000)]);
+
+        let err = Disasm::default().load(&mut view, &path);
+        std::fs::remove_file(&path).unwrap();
+        assert!(matches!(err, Err(Error::StaleData(_))));
+    }
+
+    #[test]
+    fn cancelled_collection_is_incomplete() {
+        let mut data = vec![0u8; 0x1000];
+        // mov rax, [rip + 0x100]
+        data[0x10..0x17].copy_from_slice(&[0x48, 0x8b, 0x05, 0x00, 0x01, 0x00, 0x00]);
+
+        let mut view = DummyView::new(BASE.into(), data);
+        view.add_module("dummy.exe", BASE.into(), 0x1000, &[(".text", 0, 0x1000)]);
+
+        let mut disasm = Disasm::default();
+        assert!(!disasm.complete());
+
+        disasm.collect_globals(&mut view, None).unwrap();
+        assert!(disasm.complete());
+
+        let cancel = CancelToken::default();
+        cancel.cancel();
+        disasm.set_cancel_token(Some(cancel));
+
+        disasm
+            .collect_globals_with(&mut view, None, code_and_data_sections)
+            .unwrap();
+        assert!(!disasm.complete());
+        assert!(disasm.map().is_empty());
+    }
 }
@@ -0,0 +1,103 @@
+//! Scanflow error types.
+//!
+//! Scanflow wraps memflow errors, while adding variants for failures specific to scanning, so that
+//! they can be told apart programmatically, and carry enough context for a descriptive message.
+
+use memflow::prelude::v1::{Address, ErrorKind};
+use std::fmt;
+
+/// Scanflow result type.
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Describes a scanflow error.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// Error coming from memflow.
+    Memflow(memflow::error::Error),
+    /// Value type with the given name does not exist.
+    UnknownType(String),
+    /// Value could not be parsed as the given type.
+    InvalidValue { typename: String, value: String },
+    /// Value is a number, but does not fit the given integer type.
+    ValueOutOfRange {
+        typename: String,
+        value: String,
+        min: i128,
+        max: u128,
+    },
+    /// Operation needs matches, but there are none.
+    NoMatches,
+    /// Operation needs an initial scan to have been performed.
+    NotScanned,
+    /// Match index is out of bounds.
+    InvalidIndex(usize),
+    /// Given address is not a known global variable.
+    GlobalNotFound(Address),
+    /// Module with the given name was not found.
+    ModuleNotFound(String),
+    /// Given address does not belong to any module.
+    AddressNotInModule(Address),
+    /// Argument is missing or malformed.
+    InvalidArgument(String),
+    /// Input/output error.
+    Io(String),
+    /// Persisted data is corrupt, or of an unsupported version.
+    InvalidFormat(String),
+    /// Persisted data does not correspond to the current state of the target.
+    StaleData(String),
+    /// Target process exited, or otherwise disappeared during the operation.
+    ProcessGone,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Memflow(e) => write!(f, "{}: {}", e.0.to_str(), e.1.to_str()),
+            Error::UnknownType(name) => write!(f, "unknown type `{}`", name),
+            Error::InvalidValue { typename, value } => {
+                write!(f, "`{}` is not a valid {} value", value, typename)
+            }
+            Error::ValueOutOfRange {
+                typename,
+                value,
+                min,
+                max,
+            } => write!(
+                f,
+                "`{}` is out of range for {}, which holds {} to {}",
+                value, typename, min, max
+            ),
+            Error::NoMatches => write!(f, "there are no matches"),
+            Error::NotScanned => write!(f, "no scan has been performed yet"),
+            Error::InvalidIndex(idx) => write!(f, "match index {} is out of bounds", idx),
+            Error::GlobalNotFound(addr) => write!(f, "{:x} is not a known global variable", addr),
+            Error::ModuleNotFound(name) => write!(f, "module `{}` not found", name),
+            Error::AddressNotInModule(addr) => write!(f, "{:x} is not inside any module", addr),
+            Error::InvalidArgument(desc) => write!(f, "invalid argument: {}", desc),
+            Error::Io(desc) => write!(f, "i/o error: {}", desc),
+            Error::InvalidFormat(desc) => write!(f, "invalid data format: {}", desc),
+            Error::StaleData(desc) => write!(f, "stale data: {}", desc),
+            Error::ProcessGone => write!(f, "the process is gone, it has likely exited"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<memflow::error::Error> for Error {
+    fn from(err: memflow::error::Error) -> Self {
+        Error::Memflow(err)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err.to_string())
+    }
+}
+
+impl From<ErrorKind> for Error {
+    fn from(kind: ErrorKind) -> Self {
+        Error::Memflow(kind.into())
+    }
+}
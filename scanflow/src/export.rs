@@ -0,0 +1,162 @@
+//! Export of scan results to external formats.
+
+use crate::pointer_map::PointerPath;
+use memflow::prelude::v1::*;
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+/// Export pointer chains to a GraphViz DOT graph.
+///
+/// Every address in the chains becomes a node, labeled relative to the module containing it.
+/// Static roots are labeled with the module they were found in. Edges are labeled with the offset
+/// added to the pointer before it is dereferenced. Nodes and edges shared by multiple chains are
+/// only emitted once, which makes common bases stand out.
+///
+/// # Arguments
+/// * `chains` - pointer chains to export
+/// * `modules` - modules to label addresses with
+pub fn chains_to_dot(chains: &[PointerPath], modules: &[ModuleInfo]) -> String {
+    let mut nodes = BTreeSet::new();
+    let mut edges = BTreeSet::new();
+
+    for chain in chains {
+        let bases = chain
+            .steps
+            .iter()
+            .map(|step| step.base)
+            .chain(Some(chain.target))
+            .collect::<Vec<_>>();
+
+        nodes.extend(bases.iter().copied());
+
+        for (w, off) in bases.windows(2).zip(chain.offsets()) {
+            edges.insert((w[0], w[1], off));
+        }
+    }
+
+    let mut out = String::new();
+
+    writeln!(out, "digraph pointers {{").unwrap();
+    writeln!(out, "    node [shape=box, fontname=monospace];").unwrap();
+
+    for &node in &nodes {
+        let is_static = chains.iter().any(|c| c.root() == Some(node));
+        let is_target = chains.iter().any(|c| c.target == node);
+
+        let style = match (is_static, is_target) {
+            (true, _) => ", style=filled, fillcolor=lightblue",
+            (_, true) => ", style=filled, fillcolor=lightgreen",
+            _ => "",
+        };
+
+        let label = chains
+            .iter()
+            .filter(|c| c.root() == Some(node))
+            .find_map(PointerPath::module_root)
+            .map(|(module, offset)| format!("{}+{:x}", module, offset))
+            .unwrap_or_else(|| address_label(node, modules));
+
+        writeln!(out, "    \"{:x}\" [label=\"{}\"{}];", node, label, style).unwrap();
+    }
+
+    for &(from, to, off) in &edges {
+        writeln!(
+            out,
+            "    \"{:x}\" -> \"{:x}\" [label=\"{}\"];",
+            from,
+            to,
+            offset_label(off)
+        )
+        .unwrap();
+    }
+
+    writeln!(out, "}}").unwrap();
+
+    out
+}
+
+/// Export pointer chains in the textual pointer notation of Cheat Engine.
+///
+/// Every chain is written on its own line, see `chain_to_cheat_engine`. Chains with no steps are
+/// skipped.
+///
+/// # Arguments
+/// * `chains` - pointer chains to export
+/// * `modules` - modules to make the base addresses relative to
+pub fn chains_to_cheat_engine(chains: &[PointerPath], modules: &[ModuleInfo]) -> String {
+    let mut out = String::new();
+
+    for line in chains
+        .iter()
+        .filter_map(|chain| chain_to_cheat_engine(chain, modules))
+    {
+        writeln!(out, "{}", line).unwrap();
+    }
+
+    out
+}
+
+/// Format a pointer chain in the textual pointer notation of Cheat Engine.
+///
+/// Cheat Engine pointers start at the address holding the first pointer, followed by the offsets
+/// added after every dereference, for instance `"client.dll"+0x1234 -> +0x10 -> +0x8`. The
+/// address is written relative to the module of a static root, or else to the module containing
+/// it, if any.
+///
+/// Returns `None` if the chain has no steps.
+///
+/// # Arguments
+/// * `chain` - pointer chain to format
+/// * `modules` - modules to make the base address relative to
+pub fn chain_to_cheat_engine(chain: &PointerPath, modules: &[ModuleInfo]) -> Option<String> {
+    let (first, rest) = chain.steps.split_first()?;
+    let base = first.addr();
+
+    // When the chain was found, the module of a static root was loaded `offset` below the root.
+    let module_root = chain.module_root().and_then(|(module, offset)| {
+        let module_base = first.base.to_umem().checked_sub(offset)?;
+        Some((module.to_string(), base.to_umem().checked_sub(module_base)?))
+    });
+
+    let mut out = module_root
+        .or_else(|| {
+            modules
+                .iter()
+                .find(|m| m.base <= base && m.base + m.size > base)
+                .map(|m| (m.name.to_string(), base.to_umem() - m.base.to_umem()))
+        })
+        .map(|(module, off)| format!("\"{}\"+0x{:x}", module, off))
+        .unwrap_or_else(|| format!("0x{:x}", base));
+
+    for step in rest {
+        let sign = if step.offset < 0 { '-' } else { '+' };
+        write!(out, " -> {}0x{:x}", sign, step.offset.unsigned_abs()).unwrap();
+    }
+
+    Some(out)
+}
+
+/// Label an address relative to the module containing it, if any.
+///
+/// # Arguments
+/// * `addr` - address to label
+/// * `modules` - modules to label the address with
+pub fn address_label(addr: Address, modules: &[ModuleInfo]) -> String {
+    modules
+        .iter()
+        .find(|m| m.base <= addr && m.base + m.size > addr)
+        .map(|m| format!("{}+{:x}", m.name, addr.to_umem() - m.base.to_umem()))
+        .unwrap_or_else(|| format!("{:x}", addr))
+}
+
+/// Label an offset with its sign, in hex.
+///
+/// # Arguments
+/// * `off` - offset to label
+pub fn offset_label(off: isize) -> String {
+    if off < 0 {
+        format!("-{:x}", off.unsigned_abs())
+    } else {
+        format!("+{:x}", off)
+    }
+}
@@ -10,11 +10,25 @@
 //!
 //! 3. Create unique code signature that references one of the global variables with `Sigmaker`.
 //!
+//! `stable_pointer::find_stable_pointer` goes through the first two steps in one call, with
+//! defaults that suit most targets.
+//!
 //! It may be worth trying out `scanflow-cli` - a command line interface built specificly around
 //! this library.
 
+pub mod decoder;
 pub mod disasm;
+pub mod error;
+pub mod export;
 pub mod pbar;
+mod persist;
 pub mod pointer_map;
+pub mod regions;
 pub mod sigmaker;
+pub mod stable_pointer;
+pub mod task;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
 pub mod value_scanner;
+pub mod value_type;
+pub mod writer;
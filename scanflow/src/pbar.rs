@@ -7,6 +7,8 @@ use std::sync::{
 #[cfg(feature = "progress_bar")]
 use std::thread::{spawn, JoinHandle};
 
+use crate::task::Progress;
+
 /// Describes a progress bar.
 ///
 /// This structure is active only when `progress_bar` feature is enabled.
@@ -15,19 +17,55 @@ pub struct PBar {
     handle: Option<JoinHandle<()>>,
     #[cfg(feature = "progress_bar")]
     cnt: Arc<AtomicU64>,
+    #[cfg(feature = "progress_bar")]
+    total: Arc<AtomicU64>,
+    #[cfg(feature = "progress_bar")]
+    found: Arc<AtomicU64>,
+    max_length: u64,
+    progress: Option<Progress>,
+}
+
+impl PBar {
+    /// Additionally report the progress to a shared `Progress`, restarting it.
+    pub fn report_to(mut self, progress: Option<&Progress>) -> Self {
+        if let Some(progress) = progress {
+            progress.start(self.max_length);
+            self.progress = Some(progress.clone());
+        }
+
+        self
+    }
 }
 
 #[cfg(feature = "progress_bar")]
 impl PBar {
     pub fn new(max_length: u64, as_bytes: bool) -> Self {
+        Self::spawn(max_length, as_bytes, false)
+    }
+
+    /// Create a progress bar for filtering a list of items.
+    ///
+    /// Next to the progress, the bar displays how many of the `max_length` items were kept so far,
+    /// as reported through `add_found`.
+    pub fn new_filter(max_length: u64) -> Self {
+        Self::spawn(max_length, false, true)
+    }
+
+    fn spawn(max_length: u64, as_bytes: bool, show_found: bool) -> Self {
         let cnt = Arc::new(AtomicU64::new(0));
+        let total = Arc::new(AtomicU64::new(max_length));
+        let found = Arc::new(AtomicU64::new(0));
 
         let cnt2 = cnt.clone();
+        let total2 = total.clone();
+        let found2 = found.clone();
 
         Self {
             handle: Some(spawn(move || {
                 let mut pbar = pbr::ProgressBar::new(max_length);
                 let cnt = cnt2;
+                let total = total2;
+                let found = found2;
 
                 if as_bytes {
                     pbar.set_units(pbr::Units::Bytes);
@@ -44,15 +82,32 @@ impl PBar {
                         break;
                     }
 
+                    if show_found {
+                        pbar.message(&format!(
+                            "{} -> {} ",
+                            fmt_count(max_length),
+                            fmt_count(found.load(Ordering::Relaxed))
+                        ));
+                    }
+
+                    pbar.total = total.load(Ordering::Relaxed);
                     pbar.set(loaded);
                 }
             })),
             cnt,
+            total,
+            found,
+            max_length,
+            progress: None,
         }
     }
 
     pub fn add(&self, add: u64) {
         self.cnt.fetch_add(add, Ordering::Relaxed);
+
+        if let Some(progress) = &self.progress {
+            progress.add(add);
+        }
     }
 
     pub fn inc(&self) {
@@ -61,6 +116,26 @@ impl PBar {
 
     pub fn set(&self, value: u64) {
         self.cnt.store(value, Ordering::Relaxed);
+
+        if let Some(progress) = &self.progress {
+            progress.set(value);
+        }
+    }
+
+    /// Increase the maximum length of the bar.
+    ///
+    /// This is useful when the total amount of work is discovered along the way.
+    pub fn add_total(&self, add: u64) {
+        self.total.fetch_add(add, Ordering::Relaxed);
+
+        if let Some(progress) = &self.progress {
+            progress.add_total(add);
+        }
+    }
+
+    /// Report items that were kept by a filter.
+    pub fn add_found(&self, add: u64) {
+        self.found.fetch_add(add, Ordering::Relaxed);
     }
 
     pub fn finish(self) {}
@@ -76,15 +151,54 @@ impl Drop for PBar {
 
 #[cfg(not(feature = "progress_bar"))]
 impl PBar {
-    pub fn new(_max_length: u64, _as_bytes: bool) -> Self {
-        Self {}
+    pub fn new(max_length: u64, _as_bytes: bool) -> Self {
+        Self {
+            max_length,
+            progress: None,
+        }
+    }
+
+    pub fn add(&self, add: u64) {
+        if let Some(progress) = &self.progress {
+            progress.add(add);
+        }
+    }
+
+    pub fn inc(&self) {
+        self.add(1);
     }
 
-    pub fn add(&self, _add: u64) {}
+    pub fn set(&self, value: u64) {
+        if let Some(progress) = &self.progress {
+            progress.set(value);
+        }
+    }
 
-    pub fn inc(&self) {}
+    pub fn new_filter(max_length: u64) -> Self {
+        Self::new(max_length, false)
+    }
 
-    pub fn set(&self, _value: u64) {}
+    pub fn add_total(&self, add: u64) {
+        if let Some(progress) = &self.progress {
+            progress.add_total(add);
+        }
+    }
+
+    pub fn add_found(&self, _add: u64) {}
 
     pub fn finish(self) {}
 }
+
+/// Format a count with thousands separators.
+#[cfg(feature = "progress_bar")]
+fn fmt_count(count: u64) -> String {
+    let digits = count.to_string();
+
+    digits
+        .as_bytes()
+        .rchunks(3)
+        .rev()
+        .map(|c| std::str::from_utf8(c).unwrap())
+        .collect::<Vec<_>>()
+        .join(",")
+}
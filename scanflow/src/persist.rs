@@ -0,0 +1,107 @@
+//! Minimal versioned binary format for persisting scanflow state.
+//!
+//! Every file starts with a 4 byte magic, identifying the kind of stored state, followed by a
+//! format version. All integers are stored as little endian `u64`.
+
+use crate::error::{Error, Result};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+pub(crate) struct Writer {
+    out: BufWriter<File>,
+}
+
+impl Writer {
+    pub fn create(path: impl AsRef<Path>, magic: &[u8; 4], version: u32) -> Result<Self> {
+        let mut ret = Self {
+            out: BufWriter::new(File::create(path)?),
+        };
+        ret.out.write_all(magic)?;
+        ret.out.write_all(&version.to_le_bytes())?;
+        Ok(ret)
+    }
+
+    pub fn u64(&mut self, v: u64) -> Result<()> {
+        self.out.write_all(&v.to_le_bytes())?;
+        Ok(())
+    }
+
+    pub fn bytes(&mut self, v: &[u8]) -> Result<()> {
+        self.u64(v.len() as u64)?;
+        self.out.write_all(v)?;
+        Ok(())
+    }
+
+    pub fn str(&mut self, v: &str) -> Result<()> {
+        self.bytes(v.as_bytes())
+    }
+
+    pub fn finish(mut self) -> Result<()> {
+        self.out.flush()?;
+        Ok(())
+    }
+}
+
+pub(crate) struct Reader {
+    inp: BufReader<File>,
+}
+
+impl Reader {
+    pub fn open(path: impl AsRef<Path>, magic: &[u8; 4], version: u32) -> Result<Self> {
+        let mut ret = Self {
+            inp: BufReader::new(File::open(path)?),
+        };
+
+        let mut file_magic = [0; 4];
+        ret.read(&mut file_magic)?;
+
+        if &file_magic != magic {
+            return Err(Error::InvalidFormat("unexpected file type".into()));
+        }
+
+        let mut file_version = [0; 4];
+        ret.read(&mut file_version)?;
+        let file_version = u32::from_le_bytes(file_version);
+
+        if file_version != version {
+            return Err(Error::InvalidFormat(format!(
+                "unsupported version {} (expected {})",
+                file_version, version
+            )));
+        }
+
+        Ok(ret)
+    }
+
+    fn read(&mut self, buf: &mut [u8]) -> Result<()> {
+        self.inp
+            .read_exact(buf)
+            .map_err(|_| Error::InvalidFormat("unexpected end of file".into()))
+    }
+
+    pub fn u64(&mut self) -> Result<u64> {
+        let mut buf = [0; 8];
+        self.read(&mut buf)?;
+        Ok(u64::from_le_bytes(buf))
+    }
+
+    pub fn bytes(&mut self) -> Result<Vec<u8>> {
+        let len = self.u64()?;
+        let mut buf = vec![];
+
+        // Do not trust the length to preallocate, corrupt files could make us run out of memory.
+        (&mut self.inp).take(len).read_to_end(&mut buf)?;
+
+        if buf.len() as u64 != len {
+            return Err(Error::InvalidFormat("unexpected end of file".into()));
+        }
+
+        Ok(buf)
+    }
+
+    pub fn str(&mut self) -> Result<String> {
+        String::from_utf8(self.bytes()?)
+            .map_err(|_| Error::InvalidFormat("invalid utf-8 string".into()))
+    }
+}
@@ -1,10 +1,168 @@
+use crate::error::{Error, Result};
 use crate::pbar::PBar;
+use crate::persist::{Reader, Writer};
+use crate::task::{CancelToken, Progress, ScanHandle};
+use crate::value_scanner::{
+    bytes_to_u64, default_scan_range, filter_region_sizes, read_with_retries, Deadline,
+    ScanEstimate,
+};
 use memflow::prelude::v1::*;
 use rayon::prelude::*;
 use rayon_tlsctx::ThreadLocalCtx;
 use std::cmp::Ordering;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
 use std::ops::Bound::Included;
+use std::ops::Range;
+use std::path::Path;
+use std::time::Duration;
+
+const FILE_MAGIC: &[u8; 4] = b"SFPM";
+const FILE_VERSION: u32 = 1;
+
+/// Score added per followed pointer when ranking chains, weighed against the sum of the offsets.
+const DEPTH_PENALTY: usize = 0x1000;
+
+/// Step of a `PointerPath`.
+///
+/// Adding the offset to the base gives the address of the next pointer, or of the target in the
+/// last step. Offsets are thus positive when the pointer, or the target lies after the base, as
+/// with fields of a structure the base points to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PointerStep {
+    /// Static address in the first step, and the address read from the previous pointer in the
+    /// following ones.
+    pub base: Address,
+    /// Signed distance from the base to the next pointer, or the target.
+    pub offset: isize,
+}
+
+impl PointerStep {
+    /// Get the address the step leads to.
+    pub fn addr(&self) -> Address {
+        self.base + self.offset
+    }
+}
+
+/// Where the root of a `PointerPath` lies.
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RootKind {
+    /// Within a module, thus at the same offset from the module base after a restart.
+    Static {
+        /// Name of the module containing the root.
+        module: String,
+        /// Offset of the root from the module base.
+        offset: umem,
+    },
+    /// Outside of all modules, such as on the heap, thus likely elsewhere after a restart.
+    Dynamic,
+}
+
+/// Prints static roots relative to their module, such as `game.exe+1a2b0`.
+impl fmt::Display for RootKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RootKind::Static { module, offset } => write!(f, "{}+{:x}", module, offset),
+            RootKind::Dynamic => write!(f, "dynamic"),
+        }
+    }
+}
+
+/// Pointer chain from a static address to a target, as found by `PointerMap::find_matches`.
+///
+/// The target is reached by taking the base of the first step, then repeatedly adding the offset,
+/// and reading the pointer at the resulting address, except in the last step, which leads to the
+/// target itself.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PointerPath {
+    /// Address the chain led to when it was found.
+    pub target: Address,
+    /// Steps of the chain, starting from the static one.
+    pub steps: Vec<PointerStep>,
+    /// Whether the root lies within a module, and where, if module ranges were set with
+    /// `PointerMap::set_module_ranges`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub root_kind: Option<RootKind>,
+}
+
+impl PointerPath {
+    /// Get the static address the chain starts at.
+    pub fn root(&self) -> Option<Address> {
+        self.steps.first().map(|step| step.base)
+    }
+
+    /// Get the name of the module the chain starts in, and the offset of the root from its base.
+    ///
+    /// Returns `None` unless the root is tagged as `RootKind::Static`.
+    pub fn module_root(&self) -> Option<(&str, umem)> {
+        match &self.root_kind {
+            Some(RootKind::Static { module, offset }) => Some((module, *offset)),
+            _ => None,
+        }
+    }
+
+    /// Iterate over the offsets of the steps.
+    pub fn offsets(&self) -> impl Iterator<Item = isize> + '_ {
+        self.steps.iter().map(|step| step.offset)
+    }
+
+    /// Get the number of pointers followed to reach the target.
+    pub fn depth(&self) -> usize {
+        self.steps.len().saturating_sub(1)
+    }
+
+    /// Follow the chain in current memory.
+    ///
+    /// See `resolve_chain`.
+    ///
+    /// # Arguments
+    /// * `mem` - memory to read the pointers from
+    /// * `size_addr` - size of a pointer
+    pub fn resolve(&self, mem: &mut impl MemoryView, size_addr: usize) -> Result<Vec<Address>> {
+        resolve_chain(mem, &self.steps, size_addr)
+    }
+
+    /// Move a static root to where its module is loaded now.
+    ///
+    /// The root is put at its offset from the current base of the module it was found in, thus
+    /// the chain can be followed after the module moved, such as after a restart of the target.
+    /// Chains with other roots are returned unchanged.
+    ///
+    /// Fails with `Error::ModuleNotFound` if the module of a static root is not loaded.
+    ///
+    /// # Arguments
+    /// * `modules` - modules currently loaded
+    pub fn rebase(&self, modules: &[ModuleInfo]) -> Result<PointerPath> {
+        let mut ret = self.clone();
+
+        if let Some((name, offset)) = self.module_root() {
+            let module = modules
+                .iter()
+                .find(|m| m.name.eq_ignore_ascii_case(name))
+                .ok_or_else(|| Error::ModuleNotFound(name.to_string()))?;
+
+            if let Some(first) = ret.steps.first_mut() {
+                first.base = module.base + offset;
+            }
+        }
+
+        Ok(ret)
+    }
+}
+
+/// Prints every step as `{base} + ({offset})`, followed by the target, for instance
+/// `7ff6a1c0 + (16) => 2a0c0 + (8) => 2a108`.
+impl fmt::Display for PointerPath {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for step in &self.steps {
+            write!(f, "{:x} + ({}) => ", step.base, step.offset)?;
+        }
+        write!(f, "{:x}", self.target)
+    }
+}
 
 /// Describes pointer map state.
 ///
@@ -12,22 +170,244 @@ use std::ops::Bound::Included;
 ///
 /// It essentially allows to find links between memory locations.
 #[derive(Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(remote = "Self")
+)]
 pub struct PointerMap {
     map: BTreeMap<Address, Address>,
+    #[cfg_attr(feature = "serde", serde(skip))]
     inverse_map: BTreeMap<Address, Vec<Address>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
     pointers: Vec<Address>,
+    relative: bool,
+    read_retries: u32,
+    timeout: Option<Duration>,
+    timed_out: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    cancel: Option<CancelToken>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    progress: Option<Progress>,
+    pointer_size: Option<usize>,
+    chunk_size: usize,
+    min_region_size: Option<umem>,
+    max_region_size: Option<umem>,
+    record_page_types: bool,
+    /// Names, and address ranges of modules, to tag chain roots as static, or dynamic.
+    #[cfg_attr(feature = "serde", serde(default))]
+    module_ranges: Vec<(String, Range<Address>)>,
+    /// Regions the last map was built from, if page types are recorded.
+    regions: Vec<MemoryRange>,
+}
+
+/// Only the forward map is stored, the inverse map, and the pointers are rebuilt from it.
+#[cfg(feature = "serde")]
+impl serde::Serialize for PointerMap {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> std::result::Result<S::Ok, S::Error> {
+        PointerMap::serialize(self, serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for PointerMap {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> std::result::Result<Self, D::Error> {
+        let mut ret = PointerMap::deserialize(deserializer)?;
+        ret.rebuild_inverse();
+        Ok(ret)
+    }
 }
 
 impl PointerMap {
+    /// Enable or disable relative pointer reconstruction.
+    ///
+    /// When enabled, `create_map` additionally interprets every 4 bytes as a signed 32-bit offset
+    /// relative to their own address. This allows to find links in structures that use relative,
+    /// or compressed pointers, at the cost of roughly doubling the amount of candidate checks, and
+    /// more false positives. Locations that hold a valid absolute pointer are not reinterpreted.
+    pub fn set_relative(&mut self, relative: bool) {
+        self.relative = relative;
+    }
+
+    /// Check whether relative pointer reconstruction is enabled.
+    pub fn relative(&self) -> bool {
+        self.relative
+    }
+
+    /// Set how many times failed page reads are retried.
+    ///
+    /// Some connectors occasionally fail reads that succeed when tried again. Retrying prevents
+    /// pointers on such pages from being silently missed. Default is 0, i.e. no retries.
+    pub fn set_read_retries(&mut self, read_retries: u32) {
+        self.read_retries = read_retries;
+    }
+
+    /// Get how many times failed page reads are retried.
+    pub fn read_retries(&self) -> u32 {
+        self.read_retries
+    }
+
+    /// Set how much memory `create_map` reads at once.
+    ///
+    /// Larger reads mean fewer round-trips, which speeds up the build considerably on high
+    /// latency connectors, at the cost of more memory per thread, and of losing the whole chunk if
+    /// a read fails. The size is rounded up to whole pages. Default is a single page (`0x1000`).
+    pub fn set_chunk_size(&mut self, chunk_size: usize) {
+        self.chunk_size = chunk_size;
+    }
+
+    /// Get how much memory `create_map` reads at once.
+    pub fn chunk_size(&self) -> usize {
+        std::cmp::max(self.chunk_size, 1).div_ceil(0x1000) * 0x1000
+    }
+
+    /// Skip regions smaller than the given size in `create_map`.
+    ///
+    /// Pointers in skipped regions are neither found, nor considered valid targets. `None`
+    /// disables the limit, which is the default.
+    pub fn set_min_region_size(&mut self, min_region_size: Option<umem>) {
+        self.min_region_size = min_region_size;
+    }
+
+    /// Get the size of the smallest region `create_map` goes through.
+    pub fn min_region_size(&self) -> Option<umem> {
+        self.min_region_size
+    }
+
+    /// Skip regions larger than the given size in `create_map`.
+    ///
+    /// Pointers in skipped regions are neither found, nor considered valid targets. `None`
+    /// disables the limit, which is the default.
+    pub fn set_max_region_size(&mut self, max_region_size: Option<umem>) {
+        self.max_region_size = max_region_size;
+    }
+
+    /// Get the size of the largest region `create_map` goes through.
+    pub fn max_region_size(&self) -> Option<umem> {
+        self.max_region_size
+    }
+
+    /// Record the page type of every pointer location in `create_map`.
+    ///
+    /// Pointers in writable memory are real variables, which can be repointed, while pointers in
+    /// read-only memory are constant tables, or relocations. The page types are taken from the
+    /// regions the map is built from. Default is off.
+    pub fn set_record_page_types(&mut self, record_page_types: bool) {
+        self.record_page_types = record_page_types;
+    }
+
+    /// Check whether `create_map` records the page types of pointer locations.
+    pub fn record_page_types(&self) -> bool {
+        self.record_page_types
+    }
+
+    /// Set the address ranges of modules, to tag the roots of found chains with.
+    ///
+    /// Chains found by `find_matches` get their `root_kind` set to `RootKind::Static`, with the
+    /// module name, and the offset of the root from its base, if the root lies within any of the
+    /// ranges, and to `RootKind::Dynamic` otherwise. Static roots survive restarts of the target,
+    /// relative to their module base. With no ranges, which is the default, roots are not tagged.
+    ///
+    /// # Arguments
+    /// * `module_ranges` - names, and `[base, base + size)` ranges of the modules
+    pub fn set_module_ranges(&mut self, module_ranges: Vec<(String, Range<Address>)>) {
+        self.module_ranges = module_ranges;
+    }
+
+    /// Get the names, and address ranges of modules chain roots are tagged with.
+    pub fn module_ranges(&self) -> &[(String, Range<Address>)] {
+        &self.module_ranges
+    }
+
+    /// Set a wall-clock limit for `create_map`.
+    ///
+    /// Once the limit is reached, no more pages are read, and the map is built from the pointers
+    /// found so far, with `timed_out` set. Such a map is incomplete, and may miss links. `None`
+    /// disables the limit, which is the default.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    /// Get the wall-clock limit of `create_map`.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    /// Check whether the last `create_map` was cut short by the timeout, or cancellation.
+    pub fn timed_out(&self) -> bool {
+        self.timed_out
+    }
+
+    /// Allow map builds, and searches to be cancelled with a `CancelToken`.
+    ///
+    /// A cancelled `create_map` returns like one that timed out, while a cancelled search returns
+    /// the chains found so far. The token is not reset by the map. `None` disables cancellation,
+    /// which is the default.
+    pub fn set_cancel_token(&mut self, cancel: Option<CancelToken>) {
+        self.cancel = cancel;
+    }
+
+    /// Get the token map builds, and searches can be cancelled with.
+    pub fn cancel_token(&self) -> Option<&CancelToken> {
+        self.cancel.as_ref()
+    }
+
+    /// Override the pointer size used by `create_map_auto`.
+    ///
+    /// This is needed when the pointer size does not follow from the process architecture, for
+    /// instance, with emulated 32-bit processes, whose pointers are 4 bytes, even though they may
+    /// be reported as 64-bit ones. `None` derives the size from the architecture, which is the
+    /// default.
+    pub fn set_pointer_size(&mut self, pointer_size: Option<usize>) {
+        self.pointer_size = pointer_size;
+    }
+
+    /// Get the overridden pointer size, if any.
+    pub fn pointer_size_override(&self) -> Option<usize> {
+        self.pointer_size
+    }
+
+    /// Get the pointer size of a process, taking the override into account.
+    ///
+    /// # Arguments
+    /// * `proc` - process to get the pointer size of
+    pub fn pointer_size(&self, proc: &impl Process) -> usize {
+        self.pointer_size
+            .unwrap_or_else(|| ArchitectureObj::from(proc.info().proc_arch).size_addr())
+    }
+
+    /// Report the progress of map builds, and searches to a shared `Progress`.
+    ///
+    /// This allows to poll the progress from another thread, independently of the `progress_bar`
+    /// feature. `None` stops the reporting, which is the default.
+    pub fn set_progress(&mut self, progress: Option<Progress>) {
+        self.progress = progress;
+    }
+
+    /// Get the shared progress map builds report to.
+    pub fn progress(&self) -> Option<&Progress> {
+        self.progress.as_ref()
+    }
+
     /// Reset the pointer map state.
     pub fn reset(&mut self) {
         self.map.clear();
         self.inverse_map.clear();
         self.pointers.clear();
+        self.regions.clear();
+        self.timed_out = false;
     }
 
     /// Create the pointer map state.
     ///
+    /// Goes through the default address range of the process architecture, see
+    /// `value_scanner::default_scan_range`.
+    ///
     /// # Arguments
     /// * `mem` - memory to scan for pointers in
     /// * `size_addr` - size of a pointer (4 bytes on 32 bit machines, 8 bytes on 64 bit machines).
@@ -35,66 +415,123 @@ impl PointerMap {
         &mut self,
         proc: &mut (impl Process + MemoryView + Clone),
         size_addr: usize,
+    ) -> Result<()> {
+        let range = default_scan_range(proc);
+        self.create_map_in_range(proc, size_addr, range)
+    }
+
+    /// Create the pointer map state from the memory within an address range.
+    ///
+    /// Both the pointers, and the addresses they point to have to lie within `range`.
+    ///
+    /// # Arguments
+    /// * `mem` - memory to scan for pointers in
+    /// * `size_addr` - size of a pointer (4 bytes on 32 bit machines, 8 bytes on 64 bit machines).
+    /// * `range` - addresses to scan
+    pub fn create_map_in_range(
+        &mut self,
+        proc: &mut (impl Process + MemoryView + Clone),
+        size_addr: usize,
+        range: Range<Address>,
     ) -> Result<()> {
         self.reset();
 
-        // TODO: replace with VAD
-        let mem_map = proc.mapped_mem_range_vec(
-            mem::mb(16) as _,
-            Address::null(),
-            ((1 as umem) << 47).into(),
-        );
+        let mem_map = self.memory_map(proc, range);
 
         let pb = PBar::new(
             mem_map
                 .iter()
-                .map(|CTup3(_, size, _)| size.to_umem() as u64)
+                .map(|CTup3(_, size, _)| size.to_umem())
                 .sum::<u64>(),
             true,
-        );
+        )
+        .report_to(self.progress.as_ref());
+
+        let relative = self.relative;
+        let little_endian = proc.metadata().little_endian;
+        let read_retries = self.read_retries;
+        let deadline = Deadline::new(self.timeout, self.cancel.as_ref());
+        let chunk_size = self.chunk_size();
+
+        let is_mapped = |out_addr: Address| {
+            mem_map
+                .binary_search_by(|&CTup3(a, s, _)| {
+                    if out_addr >= a && out_addr < a + s {
+                        Ordering::Equal
+                    } else {
+                        a.cmp(&out_addr)
+                    }
+                })
+                .is_ok()
+        };
 
         let ctx = ThreadLocalCtx::new_locked(move || proc.clone());
-        let ctx_buf = ThreadLocalCtx::new(|| vec![0; 0x1000 + size_addr - 1]);
+        let ctx_buf = ThreadLocalCtx::new(|| vec![0; chunk_size + size_addr - 1]);
 
         self.map
             .par_extend(mem_map.par_iter().flat_map(|&CTup3(address, size, _)| {
                 (0..size)
-                    .into_iter()
-                    .step_by(0x1000)
+                    .step_by(chunk_size)
                     .par_bridge()
                     .filter_map(|off| {
+                        if deadline.expired() {
+                            return None;
+                        }
+
                         let mut mem = unsafe { ctx.get() };
                         let mut buf = unsafe { ctx_buf.get() };
 
-                        mem.read_raw_into(address + off, buf.as_mut_slice())
-                            .data_part()
-                            .ok()?;
+                        // Pointers may cross the end of the chunk, thus read `size_addr - 1` bytes
+                        // past it. Those may not be readable at the end of a region, in which case
+                        // only the chunk itself is used.
+                        let len = std::cmp::min(chunk_size as umem, size - off) as usize;
+
+                        let buf = if mem
+                            .read_raw_into(address + off, &mut buf[..(len + size_addr - 1)])
+                            .is_ok()
+                        {
+                            &buf[..(len + size_addr - 1)]
+                        } else if read_with_retries(
+                            &mut *mem,
+                            address + off,
+                            &mut buf[..len],
+                            read_retries,
+                            &deadline,
+                        ) {
+                            &buf[..len]
+                        } else {
+                            return None;
+                        };
 
-                        pb.add(0x1000);
+                        pb.add(len as u64);
 
                         let ret = buf
                             .windows(size_addr)
                             .enumerate()
                             .filter_map(|(o, buf)| {
                                 let address = address + off + o;
-                                let mut arr = [0; 8];
-                                // TODO: Fix for Big Endian
-                                arr[0..buf.len()].copy_from_slice(buf);
-                                let out_addr = Address::from(u64::from_le_bytes(arr));
-                                if mem_map
-                                    .binary_search_by(|&CTup3(a, s, _)| {
-                                        if out_addr >= a && out_addr < a + s {
-                                            Ordering::Equal
-                                        } else {
-                                            a.cmp(&out_addr)
+                                let out_addr = Address::from(bytes_to_u64(buf, little_endian));
+                                if is_mapped(out_addr) {
+                                    return Some((address, out_addr));
+                                }
+
+                                if relative && buf.len() >= 4 {
+                                    let delta = bytes_to_u64(&buf[0..4], little_endian) as u32
+                                        as i32
+                                        as imem;
+                                    // Offsets within the value itself are almost always small
+                                    // integers, not pointers.
+                                    if delta.abs() >= 4 {
+                                        let out_addr = Address::from(
+                                            (address.to_umem() as imem).wrapping_add(delta) as umem,
+                                        );
+                                        if is_mapped(out_addr) {
+                                            return Some((address, out_addr));
                                         }
-                                    })
-                                    .is_ok()
-                                {
-                                    Some((address, out_addr))
-                                } else {
-                                    None
+                                    }
                                 }
+
+                                None
                             })
                             .collect::<Vec<_>>()
                             .into_par_iter();
@@ -106,15 +543,153 @@ impl PointerMap {
                     .into_par_iter()
             }));
 
+        self.rebuild_inverse();
+        self.timed_out = deadline.hit();
+
+        if self.record_page_types {
+            self.regions = mem_map;
+        }
+
+        pb.finish();
+
+        deadline.check_gone()
+    }
+
+    /// Save the pointer map to a file.
+    ///
+    /// Besides the pointers, the file stores whether relative pointers were included, and the
+    /// regions the map was built from, if page types were recorded. Settings are not saved.
+    ///
+    /// # Arguments
+    /// * `path` - path of the file to write
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let mut w = Writer::create(path, FILE_MAGIC, FILE_VERSION)?;
+
+        w.u64(self.relative as u64)?;
+        w.u64(self.timed_out as u64)?;
+
+        w.u64(self.regions.len() as u64)?;
+        for &CTup3(base, size, page_type) in &self.regions {
+            w.u64(base.to_umem())?;
+            w.u64(size)?;
+            w.u64(page_type.bits() as u64)?;
+        }
+
+        w.u64(self.map.len() as u64)?;
+        for (&k, &v) in &self.map {
+            w.u64(k.to_umem())?;
+            w.u64(v.to_umem())?;
+        }
+
+        w.finish()
+    }
+
+    /// Load a pointer map previously saved with `save`, replacing the current one.
+    ///
+    /// The inverse map, and the list of pointers are rebuilt from the loaded map. Files that are
+    /// corrupt, or of another version are rejected with `Error::InvalidFormat`, leaving the current
+    /// map untouched.
+    ///
+    /// Returns the number of pointers loaded.
+    ///
+    /// # Arguments
+    /// * `path` - path of the file to read
+    pub fn load(&mut self, path: impl AsRef<Path>) -> Result<usize> {
+        let mut r = Reader::open(path, FILE_MAGIC, FILE_VERSION)?;
+
+        let relative = r.u64()? != 0;
+        let timed_out = r.u64()? != 0;
+
+        let mut regions = vec![];
+
+        for _ in 0..r.u64()? {
+            let base = Address::from(r.u64()?);
+            let size = r.u64()?;
+            let page_type = PageType::from_bits_truncate(r.u64()? as u8);
+            regions.push(CTup3(base, size, page_type));
+        }
+
+        let mut map = BTreeMap::new();
+
+        for _ in 0..r.u64()? {
+            let k = Address::from(r.u64()?);
+            let v = Address::from(r.u64()?);
+            map.insert(k, v);
+        }
+
+        self.reset();
+        self.map = map;
+        self.regions = regions;
+        self.relative = relative;
+        self.timed_out = timed_out;
+        self.rebuild_inverse();
+
+        Ok(self.map.len())
+    }
+
+    fn rebuild_inverse(&mut self) {
+        self.inverse_map.clear();
+
         for (&k, &v) in &self.map {
             self.inverse_map.entry(v).or_default().push(k);
         }
 
         self.pointers = self.map.keys().copied().collect();
+    }
 
-        pb.finish();
+    /// Create the pointer map state, with the pointer size of the process.
+    ///
+    /// The pointer size is derived from the process architecture, unless overridden with
+    /// `set_pointer_size`. Use `create_map` for views with no known architecture.
+    ///
+    /// # Arguments
+    /// * `mem` - memory to scan for pointers in
+    pub fn create_map_auto(
+        &mut self,
+        proc: &mut (impl Process + MemoryView + Clone),
+    ) -> Result<()> {
+        let size_addr = self.pointer_size(proc);
+        self.create_map(proc, size_addr)
+    }
 
-        Ok(())
+    /// Create the pointer map state on a background thread.
+    ///
+    /// This works like `create_map`, but returns immediately. The map, and the memory object are
+    /// moved into the background thread, and handed back by `ScanHandle::join`, alongside the
+    /// result. Progress is reported to the map's `Progress`, which is created if none is set.
+    ///
+    /// # Arguments
+    /// * `mem` - memory to scan for pointers in
+    /// * `size_addr` - size of a pointer (4 bytes on 32 bit machines, 8 bytes on 64 bit machines).
+    pub fn spawn_create_map<T: Process + MemoryView + Clone + Send + 'static>(
+        mut self,
+        mut proc: T,
+        size_addr: usize,
+    ) -> ScanHandle<(Self, T, Result<()>)> {
+        let progress = self.progress.get_or_insert_with(Default::default).clone();
+
+        ScanHandle::spawn(progress, move || {
+            let ret = self.create_map(&mut proc, size_addr);
+            (self, proc, ret)
+        })
+    }
+
+    /// Estimate the amount of memory `create_map` would go through.
+    ///
+    /// # Arguments
+    /// * `mem` - memory to estimate the pointer scan of
+    pub fn estimate(&self, proc: &mut (impl Process + MemoryView)) -> ScanEstimate {
+        let range = default_scan_range(proc);
+        ScanEstimate::from_ranges(&self.memory_map(proc, range))
+    }
+
+    fn memory_map(&self, proc: &mut impl Process, range: Range<Address>) -> Vec<MemoryRange> {
+        // TODO: replace with VAD
+        let mut regions = proc.mapped_mem_range_vec(mem::mb(16) as _, range.start, range.end);
+
+        filter_region_sizes(&mut regions, self.min_region_size, self.max_region_size);
+
+        regions
     }
 
     /// Get the forward pointer map.
@@ -132,6 +707,118 @@ impl PointerMap {
         &self.pointers
     }
 
+    /// Iterate over pointer locations, and the addresses they point to.
+    ///
+    /// Entries are ordered by the pointer location.
+    pub fn iter_edges(&self) -> impl Iterator<Item = (Address, Address)> + '_ {
+        self.map.iter().map(|(&k, &v)| (k, v))
+    }
+
+    /// Iterate over pointed to addresses, and the locations of pointers pointing to them.
+    ///
+    /// Entries are ordered by the pointed to address.
+    pub fn iter_targets(&self) -> impl Iterator<Item = (Address, &[Address])> + '_ {
+        self.inverse_map.iter().map(|(&k, v)| (k, v.as_slice()))
+    }
+
+    /// Get the page type of a pointer location.
+    ///
+    /// Returns `None` if page types were not recorded, or the address is outside the regions
+    /// the map was built from.
+    ///
+    /// # Arguments
+    /// * `addr` - pointer location to get the page type of
+    pub fn page_type(&self, addr: Address) -> Option<PageType> {
+        let idx = self
+            .regions
+            .partition_point(|&CTup3(base, _, _)| base <= addr)
+            .checked_sub(1)?;
+
+        match self.regions[idx] {
+            CTup3(base, size, page_type) if addr < base + size => Some(page_type),
+            _ => None,
+        }
+    }
+
+    /// Iterate over pointer locations, the addresses they point to, and the page types of the
+    /// locations.
+    ///
+    /// Page types are `None` unless recorded, see `set_record_page_types`. Entries are ordered by
+    /// the pointer location.
+    pub fn iter_edges_with_page_types(
+        &self,
+    ) -> impl Iterator<Item = (Address, Address, Option<PageType>)> + '_ {
+        self.map
+            .iter()
+            .map(move |(&k, &v)| (k, v, self.page_type(k)))
+    }
+
+    /// Sort pointer chains so that the ones rooted in writable memory come first.
+    ///
+    /// The order is otherwise kept. Chains whose root page type is unknown are treated as
+    /// writable, thus this does nothing unless page types were recorded.
+    ///
+    /// # Arguments
+    /// * `chains` - pointer chains to sort
+    pub fn sort_chains_by_root(&self, chains: &mut [PointerPath]) {
+        chains.sort_by_key(|chain| {
+            chain
+                .root()
+                .and_then(|root| self.page_type(root))
+                .is_some_and(|t| !t.intersects(PageType::WRITEABLE | PageType::UNKNOWN))
+        });
+    }
+
+    /// Remove redundant pointer chains, and sort the rest best first.
+    ///
+    /// Chains with the same root, offsets, and target are redundant, only the best ranked of them
+    /// is kept. Roots are compared relative to their module if static, and by address otherwise,
+    /// so that chains from different globals with the same offsets are all kept. Chains
+    /// are ranked by a score, summing the absolute offsets, and a penalty for every followed
+    /// pointer, thus shorter chains, and chains with smaller offsets come first. Among equal
+    /// scores, chains with a static root come first, the order is otherwise kept.
+    ///
+    /// # Arguments
+    /// * `chains` - pointer chains to rank
+    pub fn rank_chains(mut chains: Vec<PointerPath>) -> Vec<PointerPath> {
+        chains.sort_by_cached_key(|chain| {
+            let offsets = chain
+                .offsets()
+                .map(|off| off.unsigned_abs())
+                .fold(0usize, usize::saturating_add);
+            let score = offsets.saturating_add(chain.depth().saturating_mul(DEPTH_PENALTY));
+            (score, chain.root_kind == Some(RootKind::Dynamic))
+        });
+
+        let mut seen = BTreeSet::new();
+        chains.retain(|chain| {
+            let root = match &chain.root_kind {
+                Some(kind @ RootKind::Static { .. }) => (Some(kind.clone()), None),
+                _ => (None, chain.root()),
+            };
+
+            seen.insert((root, chain.offsets().collect::<Vec<_>>(), chain.target))
+        });
+
+        chains
+    }
+
+    /// Tag a chain root with the module it lies within, if module ranges are set.
+    fn root_kind(&self, root: Address) -> Option<RootKind> {
+        if self.module_ranges.is_empty() {
+            return None;
+        }
+
+        match self.module_ranges.iter().find(|(_, r)| r.contains(&root)) {
+            Some((module, r)) => Some(RootKind::Static {
+                module: module.clone(),
+                offset: root.to_umem() - r.start.to_umem(),
+            }),
+            None => Some(RootKind::Dynamic),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
     fn walk_down_range(
         &self,
         addr: Address,
@@ -139,11 +826,14 @@ impl PointerMap {
         max_levels: usize,
         level: usize,
         startpoints: &[Address],
-        out: &mut Vec<(Address, Vec<(Address, isize)>)>,
-        (final_addr, tmp): (Address, &mut Vec<(Address, isize)>),
+        out: &mut Vec<PointerPath>,
+        (final_addr, tmp): (Address, &mut Vec<PointerStep>),
         pb: &PBar,
-        (pb_start, pb_end): (f32, f32),
     ) {
+        if self.cancel.as_ref().is_some_and(CancelToken::is_cancelled) {
+            return;
+        }
+
         let min = Address::from(addr.to_umem().saturating_sub(urange as _));
         let max = Address::from(addr.to_umem().saturating_add(lrange as _));
 
@@ -171,34 +861,29 @@ impl PointerMap {
 
         // Push match if found
         if let Some(e) = m {
-            let off = signed_diff(addr, e);
-            let mut cloned = tmp.clone();
-            cloned.push((e, off));
-            cloned.reverse();
-            out.push((final_addr, cloned));
+            let offset = signed_diff(addr, e);
+            let mut steps = tmp.clone();
+            steps.push(PointerStep { base: e, offset });
+            steps.reverse();
+            out.push(PointerPath {
+                target: final_addr,
+                steps,
+                root_kind: self.root_kind(e),
+            });
         }
 
         // Recurse downwards if possible
         if level < max_levels {
-            let mut last = min;
             for (&k, vec) in self.inverse_map.range((Included(&min), Included(&max))) {
-                // Calculate the starting fraction
-                let frac_start = (last - min) as f32 / (max - min) as f32;
-                let new_start = pb_start + (pb_end - pb_start) * frac_start;
-
-                // Calculate the ending fraction
-                let frac_end = (k - min) as f32 / (max - min) as f32;
-                let new_end = pb_start + (pb_end - pb_start) * frac_end;
-
-                last = k;
-
-                let off = signed_diff(addr, k);
-                tmp.push((k, off));
+                tmp.push(PointerStep {
+                    base: k,
+                    offset: signed_diff(addr, k),
+                });
 
-                // Calculate how much space each subitem uses in the fraction
-                let part = (new_end - new_start) / vec.len() as f32;
+                // Every pointer is a unit of work, which we only learn about now
+                pb.add_total(vec.len() as u64);
 
-                for (i, &v) in vec.iter().enumerate() {
+                for &v in vec.iter() {
                     self.walk_down_range(
                         v,
                         (lrange, urange),
@@ -208,19 +893,13 @@ impl PointerMap {
                         out,
                         (final_addr, tmp),
                         pb,
-                        (
-                            new_start + part * i as f32,
-                            new_start + part * (i + 1) as f32,
-                        ),
                     );
                 }
                 tmp.pop();
-
-                if (new_end - pb_start) >= 0.00001 {
-                    pb.set((new_end * 100000.0).round() as u64);
-                }
             }
         }
+
+        pb.inc();
     }
 
     /// Find matches from specific entry point addresses.
@@ -237,14 +916,14 @@ impl PointerMap {
         max_depth: usize,
         search_for: &[Address],
         entry_points: &[Address],
-    ) -> Vec<(Address, Vec<(Address, isize)>)> {
+    ) -> Vec<PointerPath> {
         let mut matches = vec![];
 
-        let pb = PBar::new(100000, false);
+        // Progress is counted in visited pointers. The total starts at the number of targets, and
+        // grows as more pointers are discovered while walking down.
+        let pb = PBar::new(search_for.len() as u64, false).report_to(self.progress.as_ref());
 
-        let part = 1.0 / search_for.len() as f32;
-
-        matches.par_extend(search_for.par_iter().enumerate().flat_map(|(i, &m)| {
+        matches.par_extend(search_for.par_iter().flat_map(|&m| {
             let mut matches = vec![];
 
             self.walk_down_range(
@@ -256,11 +935,8 @@ impl PointerMap {
                 &mut matches,
                 (m, &mut vec![]),
                 &pb,
-                (part * i as f32, part * (i + 1) as f32),
             );
 
-            pb.set((100000.0 * part * (i + 1) as f32).round() as u64);
-
             matches.into_par_iter()
         }));
 
@@ -281,14 +957,413 @@ impl PointerMap {
         range: (usize, usize),
         max_depth: usize,
         search_for: &[Address],
-    ) -> Vec<(Address, Vec<(Address, isize)>)> {
+    ) -> Vec<PointerPath> {
         self.find_matches_addrs(range, max_depth, search_for, &self.pointers)
     }
 }
 
+/// Summarizes the pointer map state, rather than listing every pointer.
+impl fmt::Debug for PointerMap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn range<V>(map: &BTreeMap<Address, V>) -> String {
+            match (map.keys().next(), map.keys().next_back()) {
+                (Some(first), Some(last)) => format!("{:x}..={:x}", first, last),
+                _ => "none".into(),
+            }
+        }
+
+        f.debug_struct("PointerMap")
+            .field("pointers", &self.map.len())
+            .field("targets", &self.inverse_map.len())
+            .field("pointer_range", &format_args!("{}", range(&self.map)))
+            .field(
+                "target_range",
+                &format_args!("{}", range(&self.inverse_map)),
+            )
+            .field("relative", &self.relative)
+            .field("timed_out", &self.timed_out)
+            .finish()
+    }
+}
+
+/// Follow a pointer chain in current memory.
+///
+/// The first offset is added to the static base, and each subsequent offset to the pointer read
+/// at the previous address. The bases of the following steps are not used, since they are
+/// replaced with the pointers read.
+///
+/// Returns the address reached after every step, the last one being where the chain leads to.
+///
+/// # Arguments
+/// * `mem` - memory to read the pointers from
+/// * `steps` - steps of the chain
+/// * `size_addr` - size of a pointer
+pub fn resolve_chain(
+    mem: &mut impl MemoryView,
+    steps: &[PointerStep],
+    size_addr: usize,
+) -> Result<Vec<Address>> {
+    let (first, rest) = steps
+        .split_first()
+        .ok_or_else(|| Error::InvalidArgument("empty pointer chain".into()))?;
+
+    let little_endian = mem.metadata().little_endian;
+    let mut addr = first.addr();
+    let mut steps = vec![addr];
+
+    for &PointerStep { offset: off, .. } in rest {
+        let mut buf = [0; 8];
+        mem.read_raw_into(addr, &mut buf[..size_addr]).data_part()?;
+        addr = Address::from(bytes_to_u64(&buf[..size_addr], little_endian)) + off;
+        steps.push(addr);
+    }
+
+    Ok(steps)
+}
+
+/// Read the pointers stored at the given addresses.
+///
+/// This turns matches that are pointers themselves into the addresses they point to, which can
+/// then be searched for with `PointerMap::find_matches`, to find chains to the referenced object,
+/// rather than to the pointer. Addresses that can not be read, or hold a null pointer are skipped,
+/// and duplicate targets are removed.
+///
+/// Returns the targets, sorted by address.
+///
+/// # Arguments
+/// * `mem` - memory to read the pointers from
+/// * `addrs` - addresses of the pointers
+/// * `size_addr` - size of a pointer
+pub fn deref_targets(
+    mem: &mut impl MemoryView,
+    addrs: &[Address],
+    size_addr: usize,
+) -> Vec<Address> {
+    let little_endian = mem.metadata().little_endian;
+
+    addrs
+        .iter()
+        .filter_map(|&addr| {
+            let mut buf = [0; 8];
+            mem.read_raw_into(addr, &mut buf[..size_addr])
+                .data_part()
+                .ok()?;
+            Some(Address::from(bytes_to_u64(
+                &buf[..size_addr],
+                little_endian,
+            )))
+        })
+        .filter(|addr| !addr.is_null())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// Keep the pointer chains that still lead to one of the targets.
+///
+/// Every chain is resolved in current memory, which is useful after the state of the target has
+/// changed, to weed out chains that only worked by coincidence. Static roots are rebased on the
+/// current base of their module first, see `PointerPath::rebase`, thus chains survive restarts of
+/// the target, while other roots are followed from their absolute address. Chains that can not be
+/// resolved, or whose module is no longer loaded are dropped. The kept chains have their steps,
+/// and final address updated.
+///
+/// # Arguments
+/// * `mem` - memory to read the pointers from
+/// * `modules` - modules currently loaded
+/// * `chains` - chains to check
+/// * `targets` - addresses the chains should lead to, usually the current value scanner matches
+/// * `size_addr` - size of a pointer
+pub fn rescan_chains(
+    mem: &mut impl MemoryView,
+    modules: &[ModuleInfo],
+    chains: &[PointerPath],
+    targets: &[Address],
+    size_addr: usize,
+) -> Vec<PointerPath> {
+    let targets = targets.iter().copied().collect::<BTreeSet<_>>();
+
+    chains
+        .iter()
+        .filter_map(|chain| {
+            let mut chain = chain.rebase(modules).ok()?;
+            let addrs = chain.resolve(mem, size_addr).ok()?;
+            let addr = *addrs.last()?;
+
+            if !targets.contains(&addr) {
+                return None;
+            }
+
+            // The bases following the root are the pointers read along the way.
+            for (step, &addr) in chain.steps.iter_mut().zip(&addrs).skip(1) {
+                step.base = addr - step.offset;
+            }
+
+            chain.target = addr;
+
+            Some(chain)
+        })
+        .collect()
+}
+
 pub fn signed_diff(a: Address, b: Address) -> isize {
     a.to_umem()
         .checked_sub(b.to_umem())
         .map(|a| a as isize)
         .unwrap_or_else(|| -((b - a) as isize))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::DummyView;
+
+    const BASE: u64 = 0x10000;
+
+    #[test]
+    fn create_map_finds_pointers() {
+        let mut data = vec![0u8; 0x2000];
+        data[0x8..0x10].copy_from_slice(&(BASE + 0x1800).to_le_bytes());
+        data[0x1800..0x1808].copy_from_slice(&(BASE + 0x40).to_le_bytes());
+        // Points past the end of the view, thus is not a pointer.
+        data[0x100..0x108].copy_from_slice(&(BASE + 0x2000).to_le_bytes());
+
+        let mut view = DummyView::new(BASE.into(), data);
+        let mut map = PointerMap::default();
+
+        map.create_map(&mut view, 8).unwrap();
+
+        assert_eq!(
+            map.map().iter().map(|(&k, &v)| (k, v)).collect::<Vec<_>>(),
+            vec![
+                (Address::from(BASE + 0x8), Address::from(BASE + 0x1800)),
+                (Address::from(BASE + 0x1800), Address::from(BASE + 0x40)),
+            ]
+        );
+    }
+
+    #[test]
+    fn create_map_reads_big_endian_pointers() {
+        let mut data = vec![0u8; 0x2000];
+        data[0x8..0x10].copy_from_slice(&(BASE + 0x1800).to_be_bytes());
+        data[0x1800..0x1808].copy_from_slice(&(BASE + 0x40).to_be_bytes());
+
+        let mut view = DummyView::new(BASE.into(), data);
+        view.set_little_endian(false);
+
+        let mut map = PointerMap::default();
+        map.create_map(&mut view, 8).unwrap();
+
+        assert_eq!(
+            map.map().iter().map(|(&k, &v)| (k, v)).collect::<Vec<_>>(),
+            vec![
+                (Address::from(BASE + 0x8), Address::from(BASE + 0x1800)),
+                (Address::from(BASE + 0x1800), Address::from(BASE + 0x40)),
+            ]
+        );
+
+        let steps = [
+            PointerStep {
+                base: Address::from(BASE + 0x8),
+                offset: 0,
+            },
+            PointerStep {
+                base: Address::from(BASE + 0x1800),
+                offset: 0,
+            },
+            PointerStep {
+                base: Address::from(BASE + 0x40),
+                offset: 0x10,
+            },
+        ];
+
+        assert_eq!(
+            resolve_chain(&mut view, &steps, 8).unwrap().last(),
+            Some(&Address::from(BASE + 0x50))
+        );
+    }
+
+    #[test]
+    fn find_matches_tags_module_roots() {
+        let mut data = vec![0u8; 0x2000];
+        data[0x8..0x10].copy_from_slice(&(BASE + 0x1800).to_le_bytes());
+        data[0x1800..0x1808].copy_from_slice(&(BASE + 0x40).to_le_bytes());
+
+        let mut view = DummyView::new(BASE.into(), data);
+        let mut map = PointerMap::default();
+        map.create_map(&mut view, 8).unwrap();
+        map.set_module_ranges(vec![(
+            "dummy.exe".into(),
+            Address::from(BASE)..Address::from(BASE + 0x1000),
+        )]);
+
+        let chains = map.find_matches_addrs(
+            (0, 0x10),
+            3,
+            &[Address::from(BASE + 0x40)],
+            &[Address::from(BASE + 0x8), Address::from(BASE + 0x1800)],
+        );
+
+        let roots = chains
+            .iter()
+            .map(|c| (c.root().unwrap(), c.root_kind.clone()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            roots,
+            vec![
+                (Address::from(BASE + 0x1800), Some(RootKind::Dynamic)),
+                (
+                    Address::from(BASE + 0x8),
+                    Some(RootKind::Static {
+                        module: "dummy.exe".into(),
+                        offset: 0x8
+                    })
+                ),
+            ]
+        );
+        assert_eq!(chains[1].module_root(), Some(("dummy.exe", 0x8)));
+    }
+
+    #[test]
+    fn rebase_moves_static_roots() {
+        let mut data = vec![0u8; 0x2000];
+        data[0x8..0x10].copy_from_slice(&(BASE + 0x1800).to_le_bytes());
+
+        let mut view = DummyView::new(BASE.into(), data);
+        view.add_module("dummy.exe", BASE.into(), 0x1000, &[]);
+        let modules = view.module_list().unwrap();
+
+        // Found while the module was loaded at 0x50000.
+        let chain = PointerPath {
+            target: Address::from(0x51810u64),
+            steps: vec![
+                PointerStep {
+                    base: Address::from(0x50008u64),
+                    offset: 0,
+                },
+                PointerStep {
+                    base: Address::from(0x51800u64),
+                    offset: 0x10,
+                },
+            ],
+            root_kind: Some(RootKind::Static {
+                module: "DUMMY.EXE".into(),
+                offset: 0x8,
+            }),
+        };
+
+        let rebased = chain.rebase(&modules).unwrap();
+        assert_eq!(rebased.root(), Some(Address::from(BASE + 0x8)));
+        assert_eq!(
+            rebased.resolve(&mut view, 8).unwrap().last(),
+            Some(&Address::from(BASE + 0x1810))
+        );
+
+        let dynamic = PointerPath {
+            root_kind: Some(RootKind::Dynamic),
+            ..chain.clone()
+        };
+        assert_eq!(dynamic.rebase(&modules).unwrap(), dynamic);
+
+        assert!(matches!(
+            chain.rebase(&[]),
+            Err(Error::ModuleNotFound(name)) if name == "DUMMY.EXE"
+        ));
+    }
+
+    #[test]
+    fn rescan_chains_follows_moved_modules() {
+        let mut data = vec![0u8; 0x2000];
+        data[0x8..0x10].copy_from_slice(&(BASE + 0x1800).to_le_bytes());
+
+        let mut view = DummyView::new(BASE.into(), data);
+        view.add_module("dummy.exe", BASE.into(), 0x1000, &[]);
+        let modules = view.module_list().unwrap();
+
+        // Found while the module was loaded at 0x50000, before a restart.
+        let chain = PointerPath {
+            target: Address::from(0x52010u64),
+            steps: vec![
+                PointerStep {
+                    base: Address::from(0x50008u64),
+                    offset: 0,
+                },
+                PointerStep {
+                    base: Address::from(0x52000u64),
+                    offset: 0x10,
+                },
+            ],
+            root_kind: Some(RootKind::Static {
+                module: "dummy.exe".into(),
+                offset: 0x8,
+            }),
+        };
+
+        let dynamic = PointerPath {
+            root_kind: Some(RootKind::Dynamic),
+            ..chain.clone()
+        };
+
+        let kept = rescan_chains(
+            &mut view,
+            &modules,
+            &[chain, dynamic],
+            &[Address::from(BASE + 0x1810)],
+            8,
+        );
+
+        assert_eq!(kept.len(), 1);
+        assert_eq!(kept[0].target, Address::from(BASE + 0x1810));
+        assert_eq!(
+            kept[0].steps,
+            vec![
+                PointerStep {
+                    base: Address::from(BASE + 0x8),
+                    offset: 0,
+                },
+                PointerStep {
+                    base: Address::from(BASE + 0x1800),
+                    offset: 0x10,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn rank_chains_keeps_different_roots() {
+        let chain = |root: u64, offset: umem, target: u64| PointerPath {
+            target: Address::from(target),
+            steps: vec![
+                PointerStep {
+                    base: Address::from(root),
+                    offset: 0x10,
+                },
+                PointerStep {
+                    base: Address::from(0x20000u64),
+                    offset: 0x8,
+                },
+            ],
+            root_kind: Some(RootKind::Static {
+                module: "dummy.exe".into(),
+                offset,
+            }),
+        };
+
+        let ranked = PointerMap::rank_chains(vec![
+            chain(0x10100, 0x100, 0x20008),
+            chain(0x10200, 0x200, 0x20008),
+            // Same root, offsets, and target as the first chain.
+            chain(0x10100, 0x100, 0x20008),
+            chain(0x10100, 0x100, 0x30008),
+        ]);
+
+        assert_eq!(
+            ranked
+                .iter()
+                .map(|c| (c.module_root().unwrap().1, c.target.to_umem()))
+                .collect::<Vec<_>>(),
+            vec![(0x100, 0x20008), (0x200, 0x20008), (0x100, 0x30008)]
+        );
+    }
+}
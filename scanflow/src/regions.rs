@@ -0,0 +1,121 @@
+//! Classification of memory regions.
+//!
+//! Processes rarely say what their memory regions are used for. The classification here is a
+//! heuristic based on page types, module ranges, and region placement, good enough to narrow down
+//! scans, but not to be relied upon for anything else.
+
+use crate::error::Result;
+use crate::value_scanner::default_scan_range;
+use memflow::prelude::v1::*;
+use std::fmt;
+
+/// Largest region still considered a thread stack.
+const MAX_STACK_SIZE: umem = 8 << 20;
+
+/// Best guess of what a memory region is used for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RegionClass {
+    /// Thread stack.
+    Stack,
+    /// Dynamically allocated memory.
+    Heap,
+    /// Image of a loaded module.
+    Module,
+    /// Anything else, such as read-only mappings.
+    Other,
+}
+
+impl RegionClass {
+    /// Parse a region class from its name.
+    ///
+    /// # Arguments
+    /// * `name` - one of `stack`, `heap`, `module`, or `other`
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "stack" => Some(RegionClass::Stack),
+            "heap" => Some(RegionClass::Heap),
+            "module" => Some(RegionClass::Module),
+            "other" => Some(RegionClass::Other),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for RegionClass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            RegionClass::Stack => "stack",
+            RegionClass::Heap => "heap",
+            RegionClass::Module => "module",
+            RegionClass::Other => "other",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Classify memory regions of a process.
+///
+/// Regions overlapping a module are module images. Of the remaining writeable regions, small ones
+/// above all modules are taken for stacks, which matches the usual layout on Linux, and the rest
+/// for heap. Stacks of Windows processes are placed below the modules, thus they are classified as
+/// heap. Regions of unknown page type are treated as writeable.
+///
+/// Returns a class for every region, in the same order.
+///
+/// # Arguments
+/// * `proc` - process the regions belong to
+/// * `regions` - regions to classify
+pub fn classify_regions<T: Process>(
+    proc: &mut T,
+    regions: &[MemoryRange],
+) -> Result<Vec<RegionClass>> {
+    let modules = proc
+        .module_list()?
+        .into_iter()
+        .map(|m| (m.base, m.base + m.size))
+        .collect::<Vec<_>>();
+
+    let modules_end = modules.iter().map(|&(_, end)| end).max();
+
+    Ok(regions
+        .iter()
+        .map(|&CTup3(base, size, page_type)| {
+            let end = base + size;
+
+            if modules.iter().any(|&(s, e)| s < end && base < e) {
+                RegionClass::Module
+            } else if !page_type.intersects(PageType::WRITEABLE | PageType::UNKNOWN) {
+                RegionClass::Other
+            } else if size <= MAX_STACK_SIZE && modules_end.is_some_and(|e| base >= e) {
+                RegionClass::Stack
+            } else {
+                RegionClass::Heap
+            }
+        })
+        .collect())
+}
+
+/// List the memory regions of a process that belong to a class.
+///
+/// Regions are listed without merging adjacent ones, so that every region is classified on its
+/// own.
+///
+/// # Arguments
+/// * `proc` - process to list the regions of
+/// * `class` - class of the regions to keep
+pub fn regions_of_class<T: Process + MemoryView>(
+    proc: &mut T,
+    class: RegionClass,
+) -> Result<Vec<MemoryRange>> {
+    let range = default_scan_range(proc);
+    let regions = proc.mapped_mem_range_vec(0, range.start, range.end);
+    let classes = classify_regions(proc, &regions)?;
+
+    Ok(regions
+        .into_iter()
+        .zip(classes)
+        .filter(|&(_, c)| c == class)
+        .map(|(r, _)| r)
+        .collect())
+}
@@ -3,26 +3,58 @@ use memflow::prelude::v1::*;
 use iced_x86::{Code, ConstantOffsets, Decoder, DecoderOptions, Instruction, OpKind, Register};
 
 use crate::disasm::Disasm;
+use crate::error::{Error, Result};
+use crate::task::CancelToken;
+
+use rayon::prelude::*;
+use rayon_tlsctx::ThreadLocalCtx;
+use std::fmt;
+
+/// Default limit of the signature length in bytes.
+pub const DEFAULT_MAX_SIG_LENGTH: usize = 128;
+
+/// Code signatures are checked to be unique within.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SigScope {
+    /// Text sections of the module containing the signed code.
+    #[default]
+    Module,
+    /// Text sections of all modules of the process.
+    Process,
+}
+
+impl fmt::Display for SigScope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            SigScope::Module => "module",
+            SigScope::Process => "process",
+        };
 
-const MAX_SIG_LENGTH: usize = 128;
+        write!(f, "{}", name)
+    }
+}
 
 struct Sigstate<'a> {
     start_ip: Address,
-    buf: &'a [u8; MAX_SIG_LENGTH],
+    buf: &'a [u8],
     decoder: Decoder<'a>,
     instrs: Vec<(Instruction, ConstantOffsets)>,
     mask: Vec<u8>,
+    /// Set when decoding hit an invalid instruction. Such signature can not grow any further,
+    /// and since it was not unique before decoding broke, it never will be.
+    invalid: bool,
 }
 
 impl Sigstate<'_> {
     fn add_single_instr(&mut self) -> bool {
-        if !self.decoder.can_decode() {
+        if self.invalid || !self.decoder.can_decode() {
             return false;
         }
 
         let instr = self.decoder.decode();
 
         if instr.code() == Code::INVALID {
+            self.invalid = true;
             false
         } else {
             let constant_offsets = self.decoder.get_constant_offsets(&instr);
@@ -55,7 +87,7 @@ impl Sigstate<'_> {
         | Ok(OpKind::FarBranch16)
         | Ok(OpKind::FarBranch32) = instr.try_op_kind(0)
         {
-            Self::mask_branch(&offsets, mask, 1);
+            Self::mask_branch(offsets, mask, 1);
         }
     }
 
@@ -73,6 +105,25 @@ impl Sigstate<'_> {
         }
     }
 
+    /// Get the offset to the displacement, or immediate of the first instruction.
+    fn operand_offset(&self) -> Option<usize> {
+        let (instr, offsets) = self.instrs.first()?;
+
+        // Displacements relative to other registers do not reference globals.
+        let absolute_mem = matches!(
+            instr.memory_base(),
+            Register::EIP | Register::RIP | Register::None
+        );
+
+        if offsets.has_displacement() && absolute_mem {
+            Some(offsets.displacement_offset())
+        } else if offsets.has_immediate() {
+            Some(offsets.immediate_offset())
+        } else {
+            None
+        }
+    }
+
     fn mask_mem(offsets: &ConstantOffsets, mask: &mut [u8]) {
         if offsets.has_displacement() {
             let off = offsets.displacement_offset();
@@ -86,97 +137,429 @@ impl Sigstate<'_> {
     }
 }
 
+/// Text format of a code signature.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SigFormat {
+    /// IDA style, such as `48 8B ? ?`.
+    #[default]
+    Ida,
+    /// x64dbg style, such as `48 8B ?? ??`.
+    X64Dbg,
+    /// C byte array, and mask, such as `{ 0x48, 0x8B, 0x00, 0x00 }, "xx??"`.
+    CByteArrayWithMask,
+    /// Escaped string, and mask, such as `"\x48\x8B\x00\x00", "xx??"`.
+    CodeStyle,
+}
+
+impl SigFormat {
+    /// Parse a signature format from its name.
+    ///
+    /// # Arguments
+    /// * `name` - one of `ida`, `x64dbg`, `c`, or `code`
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "ida" => Some(SigFormat::Ida),
+            "x64dbg" => Some(SigFormat::X64Dbg),
+            "c" => Some(SigFormat::CByteArrayWithMask),
+            "code" => Some(SigFormat::CodeStyle),
+            _ => None,
+        }
+    }
+
+    /// Format signature bytes, and their mask.
+    ///
+    /// Wildcard bytes are written as `?` in IDA style, and `??` in x64dbg style. The C, and code
+    /// styles write them as zero bytes, and mark them with `?` in the mask, while other bytes are
+    /// marked with `x`.
+    ///
+    /// # Arguments
+    /// * `bytes` - signature bytes
+    /// * `mask` - signature mask, with wildcard bytes marked as `0`
+    pub fn format(self, bytes: &[u8], mask: &[u8]) -> String {
+        let masked = bytes
+            .iter()
+            .zip(mask.iter())
+            .map(|(&b, &m)| if m == 0 { None } else { Some(b) });
+
+        let mask_str = || {
+            mask.iter()
+                .map(|&m| if m == 0 { '?' } else { 'x' })
+                .collect::<String>()
+        };
+
+        match self {
+            SigFormat::Ida | SigFormat::X64Dbg => {
+                let wildcard = if self == SigFormat::Ida { "?" } else { "??" };
+
+                masked
+                    .map(|b| match b {
+                        Some(b) => format!("{:02X}", b),
+                        None => wildcard.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            }
+            SigFormat::CByteArrayWithMask => format!(
+                "{{ {} }}, \"{}\"",
+                masked
+                    .map(|b| format!("0x{:02X}", b.unwrap_or(0)))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                mask_str()
+            ),
+            SigFormat::CodeStyle => format!(
+                "\"{}\", \"{}\"",
+                masked
+                    .map(|b| format!("\\x{:02X}", b.unwrap_or(0)))
+                    .collect::<String>(),
+                mask_str()
+            ),
+        }
+    }
+}
+
+impl fmt::Display for SigFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            SigFormat::Ida => "ida",
+            SigFormat::X64Dbg => "x64dbg",
+            SigFormat::CByteArrayWithMask => "c",
+            SigFormat::CodeStyle => "code",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+/// Code signature produced by the `Sigmaker`.
+///
+/// The signature carries the module and its sections the signature was validated against, which
+/// allows to relocate it after the module has been loaded at a different address.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Signature {
+    /// Signature bytes.
+    pub pattern: Vec<u8>,
+    /// Signature mask. Wildcard bytes are marked as `0`, others as `0xff`.
+    pub mask: Vec<u8>,
+    /// Address of the first instruction of the signature.
+    pub ip: Address,
+    /// Number of instructions the signature spans.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub instr_count: usize,
+    /// Name of the module the signature was validated against.
+    pub module: String,
+    /// Module-relative `(offset, size)` ranges of the text sections of the module, which the
+    /// signature is unique within.
+    pub sections: Vec<(umem, umem)>,
+    /// Offset from the start of the signature to the displacement, or immediate referencing the
+    /// global, if the signature was made for one.
+    ///
+    /// This is the IDA-style `+N` of the signature, which allows to read the reference directly
+    /// from a match.
+    pub operand_offset: Option<usize>,
+}
+
+impl Signature {
+    /// Get the number of wildcard bytes in the signature.
+    pub fn wildcards(&self) -> usize {
+        self.mask.iter().filter(|&&m| m == 0).count()
+    }
+
+    /// Format the signature in the given style.
+    ///
+    /// # Arguments
+    /// * `format` - text format to use
+    pub fn format(&self, format: SigFormat) -> String {
+        format.format(&self.pattern, &self.mask)
+    }
+}
+
+/// Prints the signature in IDA style.
+impl fmt::Display for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.format(SigFormat::Ida))
+    }
+}
+
 /// Sigmaker state.
 ///
 /// Sigmaker allows to find IDA-style code signatures for various global variables.
 #[derive(Default)]
-pub struct Sigmaker {}
+pub struct Sigmaker {
+    cancel: Option<CancelToken>,
+}
 
 impl Sigmaker {
+    /// Allow signature searches to be cancelled with a `CancelToken`.
+    ///
+    /// A cancelled search stops checking signatures, and keeps the ones found unique so far. The
+    /// token is not reset. `None` disables cancellation, which is the default.
+    pub fn set_cancel_token(&mut self, cancel: Option<CancelToken>) {
+        self.cancel = cancel;
+    }
+
+    /// Get the token signature searches can be cancelled with.
+    pub fn cancel_token(&self) -> Option<&CancelToken> {
+        self.cancel.as_ref()
+    }
+
+    fn cancelled(&self) -> bool {
+        self.cancel.as_ref().is_some_and(CancelToken::is_cancelled)
+    }
+
     fn has_unique_matches(
+        &self,
         states: &[Sigstate],
-        mem: &mut impl MemoryView,
+        mem: &mut (impl MemoryView + Clone),
         ranges: &[(Address, umem)],
-        out: &mut Vec<String>,
+        max_sig_length: usize,
+        out: &mut Vec<(Address, Vec<u8>, Vec<u8>)>,
     ) -> Result<bool> {
-        let mut sigs: Vec<_> = states
+        let sigs: Vec<_> = states
             .iter()
-            .map(|s| (s.start_ip, s.buf, &s.mask, 0))
+            .filter(|s| !s.invalid && !s.mask.is_empty())
+            .map(|s| (s.start_ip, s.buf, &s.mask[..]))
             .collect();
 
-        const CHUNK_SIZE: usize = size::kb(4);
-        let mut buf = vec![0; CHUNK_SIZE + MAX_SIG_LENGTH - 1];
+        let dup_matches = self.dup_matches(&sigs, mem, ranges, max_sig_length)?;
 
-        for &(addr, size) in ranges {
-            for off in (0..size).step_by(CHUNK_SIZE) {
-                let addr = addr + off;
-                mem.read_raw_into(addr, buf.as_mut_slice()).data_part()?;
-
-                for (off, w) in buf.windows(MAX_SIG_LENGTH).enumerate() {
-                    let addr = addr + off;
-                    for (start_ip, bytes, mask, dup_matches) in sigs.iter_mut() {
-                        let win_iter = w.iter().zip(mask.iter()).map(|(&w, &m)| w & m);
-                        let bytes_iter = bytes.iter().zip(mask.iter()).map(|(&w, &m)| w & m);
-                        if win_iter.eq(bytes_iter) && addr != *start_ip {
-                            *dup_matches += 1;
-                        }
-                    }
-                }
-            }
+        // Counts of a cancelled search are partial, and would make any signature look unique.
+        if self.cancelled() {
+            return Ok(false);
         }
 
         let mut has_unique = false;
 
-        for (_, buf, mask, dup_matches) in sigs {
+        for ((start_ip, buf, mask), dup_matches) in sigs.into_iter().zip(dup_matches) {
             if dup_matches == 0 {
                 has_unique = true;
-                out.push(Self::bytes_to_string(buf, mask));
+                out.push((start_ip, buf[..mask.len()].to_vec(), mask.to_vec()));
             }
         }
 
         Ok(has_unique)
     }
 
-    fn bytes_to_string(bytes: &[u8], mask: &[u8]) -> String {
-        bytes
+    /// Count the matches of every signature within the ranges, other than at its own address.
+    ///
+    /// * `sigs` - address, bytes, and mask of every signature
+    /// * `mem` - memory to scan
+    /// * `ranges` - `(base, size)` ranges to scan
+    /// * `max_sig_length` - maximum length of the signatures in bytes
+    fn dup_matches(
+        &self,
+        sigs: &[(Address, &[u8], &[u8])],
+        mem: &mut (impl MemoryView + Clone),
+        ranges: &[(Address, umem)],
+        max_sig_length: usize,
+    ) -> Result<Vec<usize>> {
+        const CHUNK_SIZE: usize = size::kb(4);
+
+        let chunks: Vec<Address> = ranges
             .iter()
-            .zip(mask.iter())
-            .map(|(&b, &m)| {
-                if m == 0 {
-                    "?".to_string()
-                } else {
-                    format!("{:02X}", b)
-                }
-            })
-            .collect::<Vec<_>>()
-            .join(" ")
+            .flat_map(|&(addr, size)| (0..size).step_by(CHUNK_SIZE).map(move |off| addr + off))
+            .collect();
+
+        let ctx = ThreadLocalCtx::new_locked(move || mem.clone());
+        let ctx_buf = ThreadLocalCtx::new(|| vec![0; CHUNK_SIZE + max_sig_length - 1]);
+
+        // Every thread counts duplicate matches of each signature, and the counts are summed up.
+        chunks
+            .par_iter()
+            .try_fold(
+                || vec![0usize; sigs.len()],
+                |mut dup_matches, &addr| {
+                    if self.cancelled() {
+                        return Ok(dup_matches);
+                    }
+
+                    let mut mem = unsafe { ctx.get() };
+                    let mut buf = unsafe { ctx_buf.get() };
+
+                    mem.read_raw_into(addr, buf.as_mut_slice()).data_part()?;
+
+                    for (off, w) in buf.windows(max_sig_length).enumerate() {
+                        let addr = addr + off;
+                        for ((start_ip, bytes, mask), dup) in sigs.iter().zip(&mut dup_matches) {
+                            if Self::masked_eq(w, bytes, mask) && addr != *start_ip {
+                                *dup += 1;
+                            }
+                        }
+                    }
+
+                    Ok(dup_matches)
+                },
+            )
+            .try_reduce(
+                || vec![0usize; sigs.len()],
+                |mut a, b| {
+                    a.iter_mut().zip(b).for_each(|(a, b)| *a += b);
+                    Ok(a)
+                },
+            )
+            .map_err(|e: memflow::error::Error| Error::from(e))
+    }
+
+    fn masked_eq(window: &[u8], bytes: &[u8], mask: &[u8]) -> bool {
+        let win_iter = window.iter().zip(mask.iter()).map(|(&w, &m)| w & m);
+        let bytes_iter = bytes.iter().zip(mask.iter()).map(|(&w, &m)| w & m);
+        win_iter.eq(bytes_iter)
     }
 
     /// Find code signatures for the given target global.
     ///
+    /// Signatures are grown one instruction at a time until they are unique, but not past
+    /// `max_sig_length` bytes, usually `DEFAULT_MAX_SIG_LENGTH`. Instructions that need more
+    /// context to be told apart yield no signature.
+    ///
     /// * `process` - target profcess
     /// * `disasm` - instance to disassembler state
     /// * `target_global` - target global variable to sig
+    /// * `max_sig_length` - maximum length of the signatures in bytes
+    /// * `scope` - code the signatures have to be unique within
     pub fn find_sigs(
-        process: &mut (impl Process + MemoryView),
+        &self,
+        process: &mut (impl Process + MemoryView + Clone),
         disasm: &Disasm,
         target_global: Address,
-    ) -> Result<Vec<String>> {
+        max_sig_length: usize,
+        scope: SigScope,
+    ) -> Result<Vec<Signature>> {
         let addrs = disasm
             .inverse_map()
             .get(&target_global)
-            .ok_or(ErrorKind::InvalidArgument)?;
+            .ok_or(Error::GlobalNotFound(target_global))?;
+
+        // Instructions referencing the global do not necessarily live in the same module as the
+        // global itself, thus group them by the module containing the code.
+        let modules = process.module_list()?;
 
+        let mut groups: Vec<(&ModuleInfo, Vec<Address>)> = vec![];
+
+        for &a in addrs {
+            if let Some(m) = modules.iter().find(|m| m.base <= a && m.base + m.size > a) {
+                match groups.iter_mut().find(|(gm, _)| gm.base == m.base) {
+                    Some((_, group)) => group.push(a),
+                    None => groups.push((m, vec![a])),
+                }
+            }
+        }
+
+        if groups.is_empty() {
+            return Err(Error::AddressNotInModule(addrs[0]));
+        }
+
+        let mut out = vec![];
+
+        for (module, addrs) in groups {
+            if self.cancelled() {
+                break;
+            }
+
+            out.extend(self.find_sigs_in_module(
+                process,
+                module,
+                &addrs,
+                true,
+                max_sig_length,
+                scope,
+            )?);
+        }
+
+        Ok(out)
+    }
+
+    /// Find the best code signature for any of the given target globals.
+    ///
+    /// Signatures are searched for every target, and ranked by their length first, and their
+    /// number of wildcards second, the shortest one with the fewest wildcards winning. All
+    /// signatures are unique within `scope`. Targets for which no signature can be made are
+    /// skipped, and only if none of them yields one, the error of the last failed target is
+    /// returned.
+    ///
+    /// * `process` - target process
+    /// * `disasm` - instance to disassembler state
+    /// * `targets` - candidate global variables to sig
+    /// * `max_sig_length` - maximum length of the signatures in bytes
+    /// * `scope` - code the signatures have to be unique within
+    pub fn find_best_sig(
+        &self,
+        process: &mut (impl Process + MemoryView + Clone),
+        disasm: &Disasm,
+        targets: &[Address],
+        max_sig_length: usize,
+        scope: SigScope,
+    ) -> Result<Signature> {
+        let mut best: Option<Signature> = None;
+        let mut err = Error::InvalidArgument(if targets.is_empty() {
+            "no targets to sig".into()
+        } else {
+            "no unique signature found".into()
+        });
+
+        for &target in targets {
+            if self.cancelled() {
+                break;
+            }
+
+            match self.find_sigs(process, disasm, target, max_sig_length, scope) {
+                Ok(sigs) => {
+                    for sig in sigs {
+                        let key = |s: &Signature| (s.pattern.len(), s.wildcards());
+
+                        if best.as_ref().map_or(true, |b| key(&sig) < key(b)) {
+                            best = Some(sig);
+                        }
+                    }
+                }
+                Err(e) => err = e,
+            }
+        }
+
+        best.ok_or(err)
+    }
+
+    /// Find a code signature for the instructions at the given address.
+    ///
+    /// Unlike `find_sigs`, this does not need a global variable, and instead anchors the
+    /// signature on the instruction stream starting at `addr`. This is useful for signing function
+    /// prologues, or any other code address.
+    ///
+    /// Returns `None` if no unique signature could be built.
+    ///
+    /// * `process` - target process
+    /// * `addr` - address of the first instruction of the signature
+    /// * `max_sig_length` - maximum length of the signature in bytes
+    /// * `scope` - code the signature has to be unique within
+    pub fn find_sig_at(
+        &self,
+        process: &mut (impl Process + MemoryView + Clone),
+        addr: Address,
+        max_sig_length: usize,
+        scope: SigScope,
+    ) -> Result<Option<Signature>> {
         let module = process
             .module_list()?
             .into_iter()
-            .find(|m| m.base <= target_global && m.base + m.size > target_global)
-            .ok_or(ErrorKind::ModuleNotFound)?;
+            .find(|m| m.base <= addr && m.base + m.size > addr)
+            .ok_or(Error::AddressNotInModule(addr))?;
 
+        Ok(self
+            .find_sigs_in_module(process, &module, &[addr], false, max_sig_length, scope)?
+            .into_iter()
+            .next())
+    }
+
+    /// Get the `(base, size)` ranges of the text sections of a module.
+    fn text_ranges(
+        process: &mut impl Process,
+        module: &ModuleInfo,
+    ) -> Result<Vec<(Address, umem)>> {
         let mut ranges = vec![];
 
         process.module_section_list_callback(
-            &module,
+            module,
             (&mut |s: SectionInfo| {
                 if s.is_text() {
                     ranges.push((s.base, s.size));
@@ -186,8 +569,46 @@ impl Sigmaker {
                 .into(),
         )?;
 
-        let mut bufs: Vec<(Address, [u8; MAX_SIG_LENGTH])> =
-            addrs.iter().map(|&a| (a, [0; MAX_SIG_LENGTH])).collect();
+        Ok(ranges)
+    }
+
+    fn find_sigs_in_module(
+        &self,
+        process: &mut (impl Process + MemoryView + Clone),
+        module: &ModuleInfo,
+        addrs: &[Address],
+        with_operand: bool,
+        max_sig_length: usize,
+        scope: SigScope,
+    ) -> Result<Vec<Signature>> {
+        if max_sig_length == 0 {
+            return Err(Error::InvalidArgument(
+                "maximum signature length must be positive".into(),
+            ));
+        }
+
+        let ranges = Self::text_ranges(process, module)?;
+
+        // Signatures are relocated within their own module, but may be checked against others.
+        let mut unique_ranges = ranges.clone();
+
+        if scope == SigScope::Process {
+            for m in process.module_list()? {
+                if m.base == module.base {
+                    continue;
+                }
+
+                match Self::text_ranges(process, &m) {
+                    Ok(r) => unique_ranges.extend(r),
+                    Err(e) => log::debug!("unable to list sections of {}: {}", m.name, e),
+                }
+            }
+        }
+
+        let mut bufs: Vec<(Address, Vec<u8>)> = addrs
+            .iter()
+            .map(|&a| (a, vec![0; max_sig_length]))
+            .collect();
 
         let mut read_list: Vec<_> = bufs
             .iter_mut()
@@ -204,13 +625,14 @@ impl Sigmaker {
             .iter()
             .map(|(start_ip, buf)| {
                 let mut decoder = Decoder::new(bitness, buf, DecoderOptions::NONE);
-                decoder.set_ip(start_ip.to_umem() as u64);
+                decoder.set_ip(start_ip.to_umem());
                 Sigstate {
                     start_ip: *start_ip,
                     buf,
                     decoder,
                     instrs: vec![],
                     mask: vec![],
+                    invalid: false,
                 }
             })
             .collect();
@@ -224,11 +646,225 @@ impl Sigmaker {
                     added = true;
                 }
             }
-            if !added || Self::has_unique_matches(&states, process, &ranges, &mut out)? {
+            if !added
+                || self.cancelled()
+                || self.has_unique_matches(
+                    &states,
+                    process,
+                    &unique_ranges,
+                    max_sig_length,
+                    &mut out,
+                )?
+            {
                 break;
             }
         }
 
+        let sections: Vec<_> = ranges
+            .iter()
+            .map(|&(base, size)| (base.to_umem() - module.base.to_umem(), size))
+            .collect();
+
+        Ok(out
+            .into_iter()
+            .map(|(ip, pattern, mask)| {
+                let state = states.iter().find(|s| s.start_ip == ip);
+
+                Signature {
+                    pattern,
+                    mask,
+                    ip,
+                    instr_count: state.map_or(0, |s| s.instrs.len()),
+                    module: module.name.to_string(),
+                    sections: sections.clone(),
+                    // Signatures start at the instruction referencing the global.
+                    operand_offset: state
+                        .filter(|_| with_operand)
+                        .and_then(Sigstate::operand_offset),
+                }
+            })
+            .collect())
+    }
+
+    /// Find all locations of a signature within the module it was created for.
+    ///
+    /// The module is looked up by name and the signature's sections are rebased on the module's
+    /// current base address, thus allowing to relocate the signature after ASLR.
+    ///
+    /// * `process` - target process
+    /// * `module_name` - name of the module to search in
+    /// * `sig` - signature to look for
+    pub fn scan_signature_in_module(
+        process: &mut (impl Process + MemoryView),
+        module_name: &str,
+        sig: &Signature,
+    ) -> Result<Vec<Address>> {
+        let module = process
+            .module_by_name(module_name)
+            .map_err(|_| Error::ModuleNotFound(module_name.to_string()))?;
+
+        let sig_len = sig.pattern.len();
+
+        if sig_len == 0 {
+            return Ok(vec![]);
+        }
+
+        const CHUNK_SIZE: usize = size::kb(4);
+        let mut buf = vec![0; CHUNK_SIZE + sig_len - 1];
+
+        let mut out = vec![];
+
+        for &(off, size) in &sig.sections {
+            let start = module.base + off;
+            let end = start + size;
+
+            for off in (0..size).step_by(CHUNK_SIZE) {
+                let addr = start + off;
+                process
+                    .read_raw_into(addr, buf.as_mut_slice())
+                    .data_part()?;
+
+                out.extend(
+                    buf.windows(sig_len)
+                        .enumerate()
+                        .map(|(o, w)| (addr + o, w))
+                        .take_while(|&(a, _)| a < end)
+                        .filter(|(_, w)| Self::masked_eq(w, &sig.pattern, &sig.mask))
+                        .map(|(a, _)| a),
+                );
+            }
+        }
+
         Ok(out)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{alphabet_bytes, DummyView};
+
+    const BASE: u64 = 0x10000;
+
+    #[test]
+    fn sig_formats() {
+        let bytes = [0x48, 0x8b, 0x05, 0x10];
+        let mask = [0xff, 0xff, 0, 0];
+
+        assert_eq!(SigFormat::Ida.format(&bytes, &mask), "48 8B ? ?");
+        assert_eq!(SigFormat::X64Dbg.format(&bytes, &mask), "48 8B ?? ??");
+        assert_eq!(
+            SigFormat::CByteArrayWithMask.format(&bytes, &mask),
+            r#"{ 0x48, 0x8B, 0x00, 0x00 }, "xx??""#
+        );
+        assert_eq!(
+            SigFormat::CodeStyle.format(&bytes, &mask),
+            r#""\x48\x8B\x00\x00", "xx??""#
+        );
+    }
+
+    #[test]
+    fn cancelled_search_finds_no_sig() {
+        let mut data = vec![0u8; 0x1000];
+        // mov rax, [rip + 0x10]; nop
+        data[0x200..0x208].copy_from_slice(&[0x48, 0x8b, 0x05, 0x10, 0x00, 0x00, 0x00, 0x90]);
+
+        let mut view = DummyView::new(BASE.into(), data);
+        view.add_module("dummy.exe", BASE.into(), 0x1000, &[(".text", 0, 0x1000)]);
+
+        let mut sigmaker = Sigmaker::default();
+        let addr = (BASE + 0x200).into();
+        assert!(sigmaker
+            .find_sig_at(&mut view, addr, 16, SigScope::Module)
+            .unwrap()
+            .is_some());
+
+        let cancel = CancelToken::default();
+        cancel.cancel();
+        sigmaker.set_cancel_token(Some(cancel));
+
+        assert!(sigmaker
+            .find_sig_at(&mut view, addr, 16, SigScope::Module)
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn no_sig_past_invalid_instructions() {
+        // mov rax, [rip + disp]; nop
+        let prefix = [0x48, 0x8b, 0x05, 0x10, 0x00, 0x00, 0x00, 0x90];
+
+        let mut data = vec![0u8; 0x1000];
+        data[0x100..0x108].copy_from_slice(&prefix);
+        // `push es` is invalid in 64-bit mode, thus decoding breaks before the prefix is unique.
+        data[0x108..0x110].copy_from_slice(&[0x06; 8]);
+        // The same prefix is followed by `add [rax], al` here, which does make it unique.
+        data[0x200..0x208].copy_from_slice(&prefix);
+
+        let mut view = DummyView::new(BASE.into(), data);
+        view.add_module("dummy.exe", BASE.into(), 0x1000, &[(".text", 0, 0x1000)]);
+
+        let sig = Sigmaker::default()
+            .find_sig_at(&mut view, (BASE + 0x100).into(), 16, SigScope::Module)
+            .unwrap();
+        assert!(sig.is_none());
+
+        let sig = Sigmaker::default()
+            .find_sig_at(&mut view, (BASE + 0x200).into(), 16, SigScope::Module)
+            .unwrap()
+            .unwrap();
+        assert_eq!(sig.ip, Address::from(BASE + 0x200));
+        assert_eq!(sig.format(SigFormat::Ida), "48 8B 05 ? ? ? ? 90 00 00");
+    }
+
+    #[test]
+    fn parallel_dup_matches_match_serial() {
+        // Signatures recur across chunks with such a small alphabet.
+        let mut data = alphabet_bytes(0x9e37_79b9, 0x5000, &[0x90, 0xcc, 0x48]);
+
+        // A signature straddling the first chunk boundary, and copies of it in later chunks.
+        let pattern = [0x48, 0x8b, 0x05, 0x11, 0x22, 0x33, 0x44, 0xc3];
+        for off in [0xffc, 0x2345, 0x3ff9] {
+            data[off..(off + pattern.len())].copy_from_slice(&pattern);
+        }
+
+        let text = 0x4000;
+        let max_sig_length = 16;
+
+        let full = [0xff; 8];
+        let masked = [0xff, 0xff, 0xff, 0, 0, 0, 0, 0xff];
+        let sigs: Vec<(Address, &[u8], &[u8])> = vec![
+            ((BASE + 0xffc).into(), &data[0xffc..0x1004], &full),
+            ((BASE + 0x2345).into(), &data[0x2345..0x234d], &masked),
+            ((BASE + 0x10).into(), &data[0x10..0x12], &full[..2]),
+            ((BASE + 0x100).into(), &data[0x100..0x103], &masked[..3]),
+            ((BASE + 0x3001).into(), &data[0x3001..0x3002], &full[..1]),
+        ];
+
+        let serial = sigs
+            .iter()
+            .map(|&(start_ip, bytes, mask)| {
+                (0..text)
+                    .filter(|&off| {
+                        let w = &data[off..(off + mask.len())];
+                        Sigmaker::masked_eq(w, bytes, mask)
+                            && BASE + off as u64 != start_ip.to_umem()
+                    })
+                    .count()
+            })
+            .collect::<Vec<_>>();
+
+        let mut view = DummyView::new(BASE.into(), data.clone());
+        let parallel = Sigmaker::default()
+            .dup_matches(
+                &sigs,
+                &mut view,
+                &[(BASE.into(), text as umem)],
+                max_sig_length,
+            )
+            .unwrap();
+
+        assert_eq!(parallel, serial);
+        assert_eq!(parallel[0], 2);
+    }
+}
@@ -0,0 +1,139 @@
+//! Finding stable pointers to a value in one go.
+//!
+//! This wires the usual workflow together - scanning for a value, building a pointer map,
+//! collecting globals, and scanning for offsets from them - with defaults that suit most targets.
+//! Use the individual pieces directly for finer control.
+
+use crate::disasm::{Disasm, RefKind};
+use crate::error::{Error, Result};
+use crate::pointer_map::{PointerMap, PointerPath};
+use crate::value_scanner::ValueScanner;
+use crate::value_type::parse_value;
+use memflow::prelude::v1::*;
+use std::collections::BTreeSet;
+
+/// Largest number of value matches to find pointers for.
+const MAX_TARGETS: usize = 256;
+
+/// Largest offset from a pointer to the field it leads to.
+const MAX_OFFSET: usize = 0x800;
+
+/// Deepest pointer chain to look for.
+const MAX_DEPTH: usize = 4;
+
+/// Number of paths returned.
+const MAX_PATHS: usize = 10;
+
+/// Find stable pointer paths to a value.
+///
+/// Scans the process for the value, and looks for pointer chains to the matches starting at
+/// globals in writable sections of the modules. Values with too many matches are rejected, since
+/// they would take too long to find pointers for; narrow them down with a `ValueScanner`, and use
+/// `find_stable_pointer_to` instead.
+///
+/// Returns the best few paths, as ranked by `find_stable_pointer_to`.
+///
+/// # Arguments
+///
+/// * `proc` - process to search
+/// * `value_type` - name of the type of the value, such as `i32`
+/// * `value` - value to look for
+pub fn find_stable_pointer<T: Process + MemoryView + Clone>(
+    proc: &mut T,
+    value_type: &str,
+    value: &str,
+) -> Result<Vec<PointerPath>> {
+    let data = parse_value(value, value_type, proc.metadata().little_endian)?;
+
+    let mut value_scanner = ValueScanner::default();
+    value_scanner.scan_for(proc, &data)?;
+
+    let matches = value_scanner.matches();
+
+    if matches.is_empty() {
+        return Err(Error::NoMatches);
+    }
+
+    if matches.len() > MAX_TARGETS {
+        return Err(Error::InvalidArgument(format!(
+            "value has {} matches, narrow them down to at most {} first",
+            matches.len(),
+            MAX_TARGETS
+        )));
+    }
+
+    find_stable_pointer_to(proc, matches)
+}
+
+/// Find stable pointer paths to addresses.
+///
+/// Builds a pointer map, collects the globals of all modules, and scans for pointer chains from
+/// the globals in writable sections to the targets. The chains are ranked by their length first,
+/// then by whether their global is referenced by a `lea` instruction, which usually marks the base
+/// of a structure, and finally by the sum of their offsets, short and tight chains winning.
+///
+/// Returns the best few paths. Their roots are tagged with the module they lie in, and the offset
+/// from its base, thus they can be followed after the module moved with `PointerPath::rebase`.
+///
+/// # Arguments
+///
+/// * `proc` - process to search
+/// * `targets` - addresses to find pointers to
+pub fn find_stable_pointer_to<T: Process + MemoryView + Clone>(
+    proc: &mut T,
+    targets: &[Address],
+) -> Result<Vec<PointerPath>> {
+    let modules = proc.module_list()?;
+
+    let mut pointer_map = PointerMap::default();
+    pointer_map.create_map_auto(proc)?;
+    pointer_map.set_module_ranges(
+        modules
+            .iter()
+            .map(|m| (m.name.to_string(), m.base..m.base + m.size))
+            .collect(),
+    );
+
+    let mut disasm = Disasm::default();
+    disasm.collect_globals(proc, None)?;
+
+    let lea = disasm
+        .globals_by_kind(RefKind::Lea)
+        .into_iter()
+        .collect::<BTreeSet<_>>();
+
+    let chains = pointer_map.find_matches_addrs(
+        (0, MAX_OFFSET),
+        MAX_DEPTH,
+        targets,
+        &disasm.writable_globals(),
+    );
+
+    let mut paths = chains
+        .into_iter()
+        .filter(|chain| chain.module_root().is_some())
+        .map(|chain| {
+            let lea = chain.root().is_some_and(|root| lea.contains(&root));
+            let offsets = chain.offsets().map(isize::unsigned_abs).sum::<usize>();
+
+            ((chain.steps.len(), !lea, offsets), chain)
+        })
+        .collect::<Vec<_>>();
+
+    paths.sort_by_key(|(key, _)| *key);
+
+    let mut seen = BTreeSet::new();
+
+    let paths = paths
+        .into_iter()
+        .map(|(_, path)| path)
+        .filter(|path| seen.insert((path.root_kind.clone(), path.offsets().collect::<Vec<_>>())))
+        .take(MAX_PATHS)
+        .collect::<Vec<_>>();
+
+    if paths.is_empty() {
+        return Err(Error::NoMatches);
+    }
+
+    Ok(paths)
+}
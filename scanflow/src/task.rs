@@ -0,0 +1,120 @@
+//! Running scans without blocking the caller.
+
+use std::sync::{
+    atomic::{AtomicBool, AtomicU64, Ordering},
+    Arc,
+};
+use std::thread::{self, JoinHandle};
+
+/// Shared progress of an operation.
+///
+/// Clones refer to the same counters, thus progress can be polled from another thread, while the
+/// operation is running. Operations consisting of multiple passes restart the counters at the
+/// beginning of every pass.
+#[derive(Clone, Debug, Default)]
+pub struct Progress {
+    done: Arc<AtomicU64>,
+    total: Arc<AtomicU64>,
+}
+
+impl Progress {
+    /// Get the amount of work done in the current pass.
+    pub fn done(&self) -> u64 {
+        self.done.load(Ordering::Relaxed)
+    }
+
+    /// Get the total amount of work of the current pass.
+    ///
+    /// The total may grow while the operation is running, if the work is discovered along the
+    /// way, such as with pointer chain searches.
+    pub fn total(&self) -> u64 {
+        self.total.load(Ordering::Relaxed)
+    }
+
+    /// Get the completed fraction of the current pass, between 0 and 1.
+    pub fn fraction(&self) -> f64 {
+        match self.total() {
+            0 => 0.0,
+            total => (self.done() as f64 / total as f64).min(1.0),
+        }
+    }
+
+    pub(crate) fn start(&self, total: u64) {
+        self.done.store(0, Ordering::Relaxed);
+        self.total.store(total, Ordering::Relaxed);
+    }
+
+    pub(crate) fn add(&self, add: u64) {
+        self.done.fetch_add(add, Ordering::Relaxed);
+    }
+
+    pub(crate) fn set(&self, value: u64) {
+        self.done.store(value, Ordering::Relaxed);
+    }
+
+    pub(crate) fn add_total(&self, add: u64) {
+        self.total.fetch_add(add, Ordering::Relaxed);
+    }
+}
+
+/// Token to cancel operations from another thread, or a signal handler.
+///
+/// Clones refer to the same flag. Operations treat a cancellation like an expired timeout: no more
+/// work is started, and the results gathered so far are kept. The token stays cancelled until it
+/// is reset, thus it has to be reset before the next operation.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    /// Cancel the running operation.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Check whether the token was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+
+    /// Clear the cancellation.
+    pub fn reset(&self) {
+        self.0.store(false, Ordering::Relaxed);
+    }
+}
+
+/// Handle to an operation running on a background thread.
+///
+/// Returned by the `spawn_` functions, which take ownership of the scanner, and the memory object,
+/// and hand them back alongside the result, once the operation is joined.
+pub struct ScanHandle<R> {
+    progress: Progress,
+    handle: JoinHandle<R>,
+}
+
+impl<R: Send + 'static> ScanHandle<R> {
+    pub(crate) fn spawn(progress: Progress, f: impl FnOnce() -> R + Send + 'static) -> Self {
+        Self {
+            progress,
+            handle: thread::spawn(f),
+        }
+    }
+
+    /// Get the progress of the operation.
+    pub fn progress(&self) -> &Progress {
+        &self.progress
+    }
+
+    /// Check whether the operation has finished, in which case `join` will not block.
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    /// Wait for the operation to finish, and get its output.
+    ///
+    /// If the operation panicked, the panic is resumed on the calling thread.
+    pub fn join(self) -> R {
+        self.handle
+            .join()
+            .unwrap_or_else(|e| std::panic::resume_unwind(e))
+    }
+}
@@ -0,0 +1,336 @@
+//! Utilities for testing and benchmarking scanflow without a live target.
+
+use memflow::prelude::v1::*;
+use std::ops::Range;
+use std::sync::{Arc, RwLock};
+
+/// In-memory `MemoryView`, backed by a byte buffer.
+///
+/// The buffer is mapped at a configurable base address, and accesses outside of it fail just like
+/// accesses to unmapped memory would. Clones share the same buffer, thus writes through one clone
+/// are visible in the others, the same way as with views of a real process.
+///
+/// The view also implements `Process`, exposing the buffer as the only mapped memory range, and
+/// the modules added with `add_module`, so that it can be passed to the scanners directly.
+#[derive(Clone)]
+pub struct DummyView {
+    base: Address,
+    data: Arc<RwLock<Vec<u8>>>,
+    mem_map: MemoryMap<(Address, umem)>,
+    info: ProcessInfo,
+    modules: Vec<(ModuleInfo, Vec<SectionInfo>)>,
+    unreadable: Vec<Range<Address>>,
+    little_endian: bool,
+}
+
+impl DummyView {
+    /// Create a new view.
+    ///
+    /// # Arguments
+    /// * `base` - address the first byte of `data` is mapped at
+    /// * `data` - contents of the view
+    pub fn new(base: Address, data: Vec<u8>) -> Self {
+        let mut mem_map = MemoryMap::new();
+        mem_map.push_remap(base, data.len() as umem, Address::null());
+
+        Self {
+            base,
+            data: Arc::new(RwLock::new(data)),
+            mem_map,
+            info: ProcessInfo {
+                address: Address::null(),
+                pid: 0,
+                state: ProcessState::Alive,
+                name: "dummy".into(),
+                path: "dummy".into(),
+                command_line: "".into(),
+                sys_arch: ArchitectureIdent::X86(64, false),
+                proc_arch: ArchitectureIdent::X86(64, false),
+                dtb1: Address::invalid(),
+                dtb2: Address::invalid(),
+            },
+            modules: vec![],
+            unreadable: vec![],
+            little_endian: true,
+        }
+    }
+
+    /// Add a module to the view.
+    ///
+    /// The module, and its sections are only listed, their memory is that of the view.
+    ///
+    /// # Arguments
+    /// * `name` - name of the module
+    /// * `base` - address the module is loaded at
+    /// * `size` - size of the module image
+    /// * `sections` - name, module-relative offset, and size of every section
+    pub fn add_module(
+        &mut self,
+        name: &str,
+        base: Address,
+        size: umem,
+        sections: &[(&str, umem, umem)],
+    ) {
+        let info = ModuleInfo {
+            address: base,
+            parent_process: Address::null(),
+            base,
+            size,
+            name: name.into(),
+            path: name.into(),
+            arch: self.info.proc_arch,
+        };
+
+        let sections = sections
+            .iter()
+            .map(|&(name, off, size)| SectionInfo {
+                name: name.into(),
+                base: base + off,
+                size,
+            })
+            .collect();
+
+        self.modules.push((info, sections));
+    }
+
+    /// Make reads touching a range fail.
+    ///
+    /// The range stays mapped, but reads overlapping it fail as a whole with a hard error, the way
+    /// reads of guard pages fail with some connectors.
+    ///
+    /// # Arguments
+    /// * `range` - addresses that can not be read
+    pub fn set_unreadable(&mut self, range: Range<Address>) {
+        self.unreadable.push(range);
+    }
+
+    /// Set the byte order reported in the view metadata.
+    ///
+    /// The view is little-endian by default.
+    ///
+    /// # Arguments
+    /// * `little_endian` - whether the view is little-endian
+    pub fn set_little_endian(&mut self, little_endian: bool) {
+        self.little_endian = little_endian;
+    }
+
+    /// Get the base address of the view.
+    pub fn base(&self) -> Address {
+        self.base
+    }
+
+    /// Get the size of the view in bytes.
+    pub fn size(&self) -> usize {
+        self.data.read().unwrap().len()
+    }
+
+    /// Get a copy of the current view contents.
+    pub fn data(&self) -> Vec<u8> {
+        self.data.read().unwrap().clone()
+    }
+
+    /// List the mapped memory ranges.
+    ///
+    /// This has the same signature as `MapsFn`, so it can be used with
+    /// `ValueScanner::initial_regions`, in place of process memory maps.
+    ///
+    /// # Arguments
+    /// * `gap_size` - unused, the view is a single contiguous range
+    /// * `start` - lower bound of the ranges to list
+    /// * `end` - upper bound of the ranges to list
+    pub fn maps(&mut self, _gap_size: imem, start: Address, end: Address) -> Vec<MemoryRange> {
+        let lower = std::cmp::max(self.base, start);
+        let upper = std::cmp::min(self.base + self.size(), end);
+
+        if lower < upper {
+            vec![CTup3(lower, (upper - lower) as umem, PageType::default())]
+        } else {
+            vec![]
+        }
+    }
+}
+
+impl MemoryView for DummyView {
+    fn read_raw_iter(
+        &mut self,
+        MemOps {
+            inp,
+            mut out,
+            out_fail,
+        }: ReadRawMemOps,
+    ) -> Result<()> {
+        let data = self.data.read().unwrap();
+        let unreadable = &self.unreadable;
+        let mut failed = false;
+
+        let inp = inp.inspect(|CTup3(addr, _, buf)| {
+            let end = *addr + buf.len();
+            failed |= unreadable.iter().any(|r| r.start < end && *addr < r.end);
+        });
+
+        let iter = self.mem_map.map_base_iter(inp, out_fail);
+
+        for CTup3((off, _), meta_addr, mut buf) in iter {
+            let off = off.to_umem() as usize;
+            let len = buf.len();
+            buf.copy_from_slice(&data[off..(off + len)]);
+            opt_call(out.as_deref_mut(), CTup2(meta_addr, buf));
+        }
+
+        if failed {
+            Err(Error(ErrorOrigin::Memory, ErrorKind::UnableToReadMemory))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn write_raw_iter(
+        &mut self,
+        MemOps {
+            inp,
+            mut out,
+            out_fail,
+        }: WriteRawMemOps,
+    ) -> Result<()> {
+        let mut data = self.data.write().unwrap();
+        let iter = self.mem_map.map_base_iter(inp, out_fail);
+
+        for CTup3((off, _), meta_addr, buf) in iter {
+            let off = off.to_umem() as usize;
+            data[off..(off + buf.len())].copy_from_slice(&buf);
+            opt_call(out.as_deref_mut(), CTup2(meta_addr, buf));
+        }
+
+        Ok(())
+    }
+
+    fn metadata(&self) -> MemoryViewMetadata {
+        let size = self.size() as umem;
+
+        MemoryViewMetadata {
+            max_address: self.base + size.saturating_sub(1),
+            real_size: size,
+            readonly: false,
+            little_endian: self.little_endian,
+            arch_bits: 64,
+        }
+    }
+}
+
+impl Process for DummyView {
+    fn state(&mut self) -> ProcessState {
+        ProcessState::Alive
+    }
+
+    fn set_dtb(&mut self, _dtb1: Address, _dtb2: Address) -> Result<()> {
+        Ok(())
+    }
+
+    fn module_address_list_callback(
+        &mut self,
+        _target_arch: Option<&ArchitectureIdent>,
+        mut callback: ModuleAddressCallback,
+    ) -> Result<()> {
+        for (info, _) in &self.modules {
+            if !callback.call(ModuleAddressInfo {
+                address: info.address,
+                arch: info.arch,
+            }) {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn module_by_address(
+        &mut self,
+        address: Address,
+        _architecture: ArchitectureIdent,
+    ) -> Result<ModuleInfo> {
+        self.modules
+            .iter()
+            .find(|(info, _)| info.address == address)
+            .map(|(info, _)| info.clone())
+            .ok_or(Error(ErrorOrigin::OsLayer, ErrorKind::ModuleNotFound))
+    }
+
+    fn primary_module_address(&mut self) -> Result<Address> {
+        Err(Error(ErrorOrigin::OsLayer, ErrorKind::ModuleNotFound))
+    }
+
+    fn module_import_list_callback(
+        &mut self,
+        _info: &ModuleInfo,
+        _callback: ImportCallback,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    fn module_export_list_callback(
+        &mut self,
+        _info: &ModuleInfo,
+        _callback: ExportCallback,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    fn module_section_list_callback(
+        &mut self,
+        info: &ModuleInfo,
+        mut callback: SectionCallback,
+    ) -> Result<()> {
+        let (_, sections) = self
+            .modules
+            .iter()
+            .find(|(m, _)| m.base == info.base)
+            .ok_or(Error(ErrorOrigin::OsLayer, ErrorKind::ModuleNotFound))?;
+
+        for section in sections {
+            if !callback.call(section.clone()) {
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn info(&self) -> &ProcessInfo {
+        &self.info
+    }
+
+    fn mapped_mem_range(
+        &mut self,
+        gap_size: imem,
+        start: Address,
+        end: Address,
+        mut out: MemoryRangeCallback,
+    ) {
+        for range in self.maps(gap_size, start, end) {
+            if !out.call(range) {
+                break;
+            }
+        }
+    }
+}
+
+/// Generate deterministic pseudo-random bytes from a small alphabet.
+///
+/// Small alphabets make patterns recur, and overlap, which exercises the match paths of the
+/// scanners, while the same seed always gives the same bytes.
+///
+/// # Arguments
+/// * `seed` - nonzero xorshift state to start from
+/// * `len` - number of bytes to generate
+/// * `alphabet` - bytes to pick from
+pub fn alphabet_bytes(seed: u32, len: usize, alphabet: &[u8]) -> Vec<u8> {
+    let mut state = seed;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 17;
+            state ^= state << 5;
+            alphabet[state as usize % alphabet.len()]
+        })
+        .collect()
+}
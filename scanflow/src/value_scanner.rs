@@ -1,7 +1,574 @@
+use crate::error::{Error, Result};
 use crate::pbar::PBar;
+use crate::persist::{Reader, Writer};
+use crate::pointer_map::signed_diff;
+use crate::regions::{regions_of_class, RegionClass};
+use crate::task::{CancelToken, Progress, ScanHandle};
+use crate::value_type::{int_width, parse_value};
 use memflow::prelude::v1::*;
 use rayon::prelude::*;
 use rayon_tlsctx::ThreadLocalCtx;
+use std::collections::BTreeSet;
+use std::convert::{TryFrom, TryInto};
+use std::ops::Range;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+const FILE_MAGIC: &[u8; 4] = b"SFVS";
+const FILE_VERSION: u32 = 1;
+
+/// Function that returns memory regions of a memory object.
+///
+/// Arguments are the gap size to merge regions across, and the start and end addresses.
+///
+/// Scans that need to know the memory layout have an `_in_regions` variant, which works on any
+/// `MemoryView`, such as core dumps, or raw physical memory. The regions to pass are listed with
+/// this function by `ValueScanner::initial_regions`. The variants without the suffix are
+/// shorthands for processes, which use `process_maps`, or `process_maps_without_modules`, if
+/// modules are skipped.
+pub type MapsFn<T> = fn(&mut T, imem, Address, Address) -> Vec<MemoryRange>;
+
+/// List the mapped memory regions of a process.
+///
+/// This is the `MapsFn` used by the process-only scanning functions.
+///
+/// # Arguments
+///
+/// * `proc` - process to list the regions of
+/// * `gap_size` - gap size to merge regions across
+/// * `start` - lower bound of the regions to list
+/// * `end` - upper bound of the regions to list
+pub fn process_maps<T: Process>(
+    proc: &mut T,
+    gap_size: imem,
+    start: Address,
+    end: Address,
+) -> Vec<MemoryRange> {
+    proc.mapped_mem_range_vec(gap_size, start, end)
+}
+
+/// List the mapped memory regions of a process, excluding loaded modules.
+///
+/// This is the `MapsFn` used by the process-only scanning functions when modules are skipped.
+/// Regions partially covered by a module are cut down to the parts outside of it. If modules can
+/// not be listed, all regions are returned.
+///
+/// # Arguments
+///
+/// * `proc` - process to list the regions of
+/// * `gap_size` - gap size to merge regions across
+/// * `start` - lower bound of the regions to list
+/// * `end` - upper bound of the regions to list
+pub fn process_maps_without_modules<T: Process>(
+    proc: &mut T,
+    gap_size: imem,
+    start: Address,
+    end: Address,
+) -> Vec<MemoryRange> {
+    let regions = proc.mapped_mem_range_vec(gap_size, start, end);
+
+    let mut modules = match proc.module_list() {
+        Ok(modules) => modules
+            .into_iter()
+            .map(|m| (m.base, m.base + m.size))
+            .collect::<Vec<_>>(),
+        Err(e) => {
+            log::warn!("unable to list modules, scanning them anyway: {}", e);
+            return regions;
+        }
+    };
+
+    modules.sort_by_key(|&(base, _)| base);
+
+    let mut out = vec![];
+
+    for CTup3(base, size, page_type) in regions {
+        let end = base + size;
+        let mut cur = base;
+
+        for &(m_start, m_end) in &modules {
+            if m_end <= cur || m_start >= end {
+                continue;
+            }
+
+            if m_start > cur {
+                out.push(CTup3(cur, (m_start - cur) as umem, page_type));
+            }
+
+            cur = std::cmp::max(cur, m_end);
+        }
+
+        if cur < end {
+            out.push(CTup3(cur, (end - cur) as umem, page_type));
+        }
+    }
+
+    out
+}
+
+/// Get the address range scans of memory go through by default.
+///
+/// The range is derived from the pointer width of the architecture in the view metadata, which is
+/// that of the process architecture for process views: 32 bit processes span 4 GiB, while 64 bit
+/// ones are limited to the lower 47 bits, which is where user space resides on common operating
+/// systems.
+///
+/// # Arguments
+///
+/// * `mem` - memory to get the range of
+pub fn default_scan_range(mem: &impl MemoryView) -> Range<Address> {
+    let bits = match mem.metadata().arch_bits {
+        32 => 32,
+        _ => 47,
+    };
+
+    Address::null()..Address::from((1 as umem) << bits)
+}
+
+/// Describes how much work a scan would need to do.
+///
+/// This is computed from the memory region map alone, without reading any memory.
+#[derive(Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScanEstimate {
+    /// Total number of bytes in all regions that would be scanned.
+    pub total_bytes: umem,
+    /// Number of memory regions that would be scanned.
+    pub region_count: usize,
+}
+
+impl ScanEstimate {
+    /// Create an estimate from a list of memory regions.
+    pub fn from_ranges(ranges: &[MemoryRange]) -> Self {
+        Self {
+            total_bytes: ranges.iter().map(|CTup3(_, size, _)| *size).sum(),
+            region_count: ranges.len(),
+        }
+    }
+}
+
+/// Describes how scanned data is compared against memory.
+///
+/// Folding modes compare text case-insensitively, by lowercasing both the scanned data, and every
+/// candidate in memory before the comparison.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CaseFolding {
+    /// Compare bytes exactly.
+    #[default]
+    Exact,
+    /// Fold ASCII letters, for UTF-8 and other ASCII compatible text.
+    Ascii,
+    /// Fold every 16-bit code unit, or surrogate pair, for UTF-16 text.
+    Utf16,
+}
+
+impl CaseFolding {
+    /// Fold the given data.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - data to fold
+    /// * `little_endian` - byte order of UTF-16 code units
+    pub fn fold(self, data: &[u8], little_endian: bool) -> Vec<u8> {
+        match self {
+            CaseFolding::Exact => data.to_vec(),
+            CaseFolding::Ascii => data.to_ascii_lowercase(),
+            CaseFolding::Utf16 => {
+                let mut units = data
+                    .chunks_exact(2)
+                    .map(|unit| unit_from_bytes(unit, little_endian))
+                    .collect::<Vec<_>>();
+
+                let mut i = 0;
+                while i < units.len() {
+                    i += fold_utf16_at(&mut units, i);
+                }
+
+                let mut out = units
+                    .into_iter()
+                    .flat_map(|unit| unit_to_bytes(unit, little_endian))
+                    .collect::<Vec<_>>();
+
+                // Trailing odd byte is kept as is
+                out.extend_from_slice(&data[(data.len() & !1)..]);
+                out
+            }
+        }
+    }
+
+    /// Check whether data matches an already folded pattern.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - unfolded data, usually read from memory
+    /// * `pattern` - folded pattern to compare against
+    /// * `little_endian` - byte order of UTF-16 code units
+    pub fn matches(self, data: &[u8], pattern: &[u8], little_endian: bool) -> bool {
+        if data.len() != pattern.len() {
+            return false;
+        }
+
+        match self {
+            CaseFolding::Exact => data == pattern,
+            CaseFolding::Ascii => data
+                .iter()
+                .zip(pattern)
+                .all(|(a, b)| a.to_ascii_lowercase() == *b),
+            CaseFolding::Utf16 => {
+                let split = data.len() & !1;
+                let mut units = [0; 2];
+                let mut i = 0;
+
+                while i < split {
+                    // Surrogate pairs are folded together, thus look at up to two units at a time
+                    let cnt = std::cmp::min(2, (split - i) / 2);
+                    for (j, unit) in units.iter_mut().enumerate().take(cnt) {
+                        *unit = unit_from_bytes(&data[(i + j * 2)..], little_endian);
+                    }
+
+                    let used = fold_utf16_at(&mut units[..cnt], 0);
+
+                    for (j, &unit) in units.iter().enumerate().take(used) {
+                        let off = i + j * 2;
+                        if unit != unit_from_bytes(&pattern[off..], little_endian) {
+                            return false;
+                        }
+                    }
+
+                    i += used * 2;
+                }
+
+                data[split..] == pattern[split..]
+            }
+        }
+    }
+}
+
+/// Describes how float values in memory are compared against the value scanned for.
+///
+/// Games often display floats truncated, or rounded to integers, thus the displayed value does not
+/// pin down the stored one. The integer modes match every float that would be displayed as the
+/// target, without having to guess a tolerance.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FloatMatch {
+    /// Compare values exactly, at the precision of the scanned type.
+    #[default]
+    Exact,
+    /// Match values at most the given distance away from the target.
+    Epsilon(f64),
+    /// Match values whose integer part, rounded towards negative infinity, equals the target.
+    Truncated,
+    /// Match values that round to the target.
+    Rounded,
+}
+
+impl FloatMatch {
+    /// Check whether a value matches the target.
+    ///
+    /// NaN, and infinite values never match, unless compared exactly.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - value found in memory
+    /// * `target` - value scanned for
+    pub fn matches(self, value: f64, target: f64) -> bool {
+        match self {
+            FloatMatch::Exact => value == target,
+            FloatMatch::Epsilon(eps) => (value - target).abs() <= eps,
+            FloatMatch::Truncated => value.is_finite() && value.floor() == target,
+            FloatMatch::Rounded => value.is_finite() && value.round() == target,
+        }
+    }
+}
+
+fn unit_from_bytes(buf: &[u8], little_endian: bool) -> u16 {
+    let buf = [buf[0], buf[1]];
+    if little_endian {
+        u16::from_le_bytes(buf)
+    } else {
+        u16::from_be_bytes(buf)
+    }
+}
+
+fn unit_to_bytes(unit: u16, little_endian: bool) -> [u8; 2] {
+    if little_endian {
+        unit.to_le_bytes()
+    } else {
+        unit.to_be_bytes()
+    }
+}
+
+/// Lowercase the UTF-16 code unit, or surrogate pair at `idx` in place.
+///
+/// Returns the number of units consumed, 2 for a valid surrogate pair, 1 otherwise. Unpaired
+/// surrogates are left as is.
+fn fold_utf16_at(units: &mut [u16], idx: usize) -> usize {
+    let (hi, lo) = (units[idx], units.get(idx + 1).copied());
+
+    match lo {
+        Some(lo) if (0xd800..0xdc00).contains(&hi) && (0xdc00..0xe000).contains(&lo) => {
+            let c = 0x10000 + (((hi as u32) - 0xd800) << 10) + ((lo as u32) - 0xdc00);
+            let mut lower = char::from_u32(c).into_iter().flat_map(char::to_lowercase);
+
+            if let (Some(l), None) = (lower.next(), lower.next()) {
+                let mut buf = [0; 2];
+                if let [hi, lo] = l.encode_utf16(&mut buf) {
+                    units[idx] = *hi;
+                    units[idx + 1] = *lo;
+                }
+            }
+
+            2
+        }
+        _ => {
+            units[idx] = fold_utf16(hi);
+            1
+        }
+    }
+}
+
+/// Lowercase a single UTF-16 code unit, if it maps to a single code unit.
+fn fold_utf16(unit: u16) -> u16 {
+    if unit < 0x80 {
+        return (unit as u8).to_ascii_lowercase() as u16;
+    }
+
+    let mut lower = match char::from_u32(unit as u32) {
+        Some(c) => c.to_lowercase(),
+        None => return unit,
+    };
+
+    match (lower.next(), lower.next()) {
+        (Some(c), None) if (c as u32) <= 0xffff => c as u16,
+        _ => unit,
+    }
+}
+
+/// Set operation to combine the matches of two value scanners with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MergeOp {
+    /// Keep matches of either scanner.
+    Union,
+    /// Keep matches of both scanners.
+    Intersection,
+    /// Keep matches of the first scanner, that are not matches of the second.
+    Difference,
+}
+
+/// Change of a value since the last scan, as filtered by `filter_delta`.
+///
+/// Values are compared as integers, and differences wrap around at their width, thus a counter
+/// overflowing between two scans still counts as increased.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DeltaKind {
+    /// The value is different.
+    Changed,
+    /// The value is the same.
+    Unchanged,
+    /// The value is larger.
+    Increased,
+    /// The value is smaller.
+    Decreased,
+    /// The value is larger by exactly the given amount.
+    IncreasedBy(i64),
+    /// The value is smaller by exactly the given amount.
+    DecreasedBy(i64),
+}
+
+impl DeltaKind {
+    /// Check whether a value changed as required.
+    ///
+    /// # Arguments
+    ///
+    /// * `prev` - value during the last scan
+    /// * `cur` - current value
+    /// * `width` - width of the values in bytes, up to 8
+    pub fn matches(self, prev: u64, cur: u64, width: usize) -> bool {
+        let pred = match self {
+            DeltaKind::Changed => return !SeqPred::StepBy(0).matches(prev, cur, width),
+            DeltaKind::Unchanged => SeqPred::StepBy(0),
+            DeltaKind::Increased => SeqPred::MonotonicIncreasing,
+            DeltaKind::Decreased => SeqPred::MonotonicDecreasing,
+            DeltaKind::IncreasedBy(n) => SeqPred::StepBy(n),
+            DeltaKind::DecreasedBy(n) => SeqPred::StepBy(n.wrapping_neg()),
+        };
+
+        pred.matches(prev, cur, width)
+    }
+}
+
+/// Relation successive samples of a value must have in `scan_for_sequence`.
+///
+/// Values are compared as integers, and differences wrap around at their width, thus a counter
+/// overflowing between two samples still counts as increasing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SeqPred {
+    /// Every sample is larger than the previous one.
+    MonotonicIncreasing,
+    /// Every sample is smaller than the previous one.
+    MonotonicDecreasing,
+    /// Every sample differs from the previous one by exactly the given step. A step of 0 keeps
+    /// values that stay constant.
+    StepBy(i64),
+}
+
+impl SeqPred {
+    /// Check whether two successive samples are related as required.
+    ///
+    /// # Arguments
+    ///
+    /// * `prev` - earlier sample
+    /// * `cur` - later sample
+    /// * `width` - width of the values in bytes, up to 8
+    pub fn matches(self, prev: u64, cur: u64, width: usize) -> bool {
+        let bits = width as u32 * 8;
+        let mask = u64::MAX >> (64 - bits);
+        let diff = cur.wrapping_sub(prev) & mask;
+        // Sign extend the difference from the width of the values.
+        let diff = ((diff << (64 - bits)) as i64) >> (64 - bits);
+
+        match self {
+            SeqPred::MonotonicIncreasing => diff > 0,
+            SeqPred::MonotonicDecreasing => diff < 0,
+            SeqPred::StepBy(step) => (diff.wrapping_sub(step) as u64) & mask == 0,
+        }
+    }
+}
+
+/// Comparison numeric values must pass in `scan_for_cmp`.
+///
+/// Bounds are values of the scanned type in native byte order, as parsed with the `value_type`
+/// module.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ScanPredicate {
+    /// Values larger than the bound.
+    Gt(Box<[u8]>),
+    /// Values smaller than the bound.
+    Lt(Box<[u8]>),
+    /// Values within the bounds, both inclusive.
+    Between(Box<[u8]>, Box<[u8]>),
+}
+
+impl ScanPredicate {
+    /// Parse a predicate of the given type.
+    ///
+    /// The input is either `>{value}`, `<{value}`, or `{lo}..{hi}`.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - predicate to parse
+    /// * `typename` - name of the type of the bounds
+    pub fn parse(input: &str, typename: &str) -> Result<Self> {
+        let input = input.trim();
+
+        let native = cfg!(target_endian = "little");
+
+        if let Some(v) = input.strip_prefix('>') {
+            Ok(ScanPredicate::Gt(parse_value(v.trim(), typename, native)?))
+        } else if let Some(v) = input.strip_prefix('<') {
+            Ok(ScanPredicate::Lt(parse_value(v.trim(), typename, native)?))
+        } else if let Some((lo, hi)) = input.split_once("..") {
+            Ok(ScanPredicate::Between(
+                parse_value(lo.trim(), typename, native)?,
+                parse_value(hi.trim(), typename, native)?,
+            ))
+        } else {
+            Err(Error::InvalidArgument(format!(
+                "expected >{{value}}, <{{value}}, or {{lo}}..{{hi}}, not `{}`",
+                input
+            )))
+        }
+    }
+}
+
+/// How the bytes of a numeric type are interpreted by comparison scans.
+#[derive(Clone, Copy)]
+enum NumKind {
+    Signed,
+    Unsigned,
+    Float,
+}
+
+impl NumKind {
+    /// Get the kind, and width of a type, if it can be compared.
+    ///
+    /// Hexadecimal integers, which accept values of either sign, are compared as unsigned.
+    fn of(typename: &str) -> Option<(NumKind, usize)> {
+        match typename {
+            "f32" => Some((NumKind::Float, 4)),
+            "f64" => Some((NumKind::Float, 8)),
+            _ => {
+                let width = int_width(typename).filter(|&w| w <= 16)?;
+
+                if typename.starts_with('i') {
+                    Some((NumKind::Signed, width))
+                } else {
+                    Some((NumKind::Unsigned, width))
+                }
+            }
+        }
+    }
+
+    /// Read a number of this kind.
+    fn read(self, buf: &[u8], little_endian: bool) -> Option<Num> {
+        match self {
+            NumKind::Signed => bytes_to_i128(buf, little_endian, true).map(Num::Int),
+            NumKind::Unsigned => Some(Num::UInt(bytes_to_u128(buf, little_endian))),
+            NumKind::Float => Some(Num::Float(bytes_to_f64(buf, little_endian))),
+        }
+    }
+}
+
+/// Number read by a comparison scan. Only numbers of the same kind are ever compared.
+#[derive(Clone, Copy, PartialEq, PartialOrd)]
+enum Num {
+    Int(i128),
+    UInt(u128),
+    Float(f64),
+}
+
+/// Settings of a `ValueScanner`, gathered in one place.
+///
+/// Every field corresponds to a setter of the scanner, and has the same default. Settings are
+/// taken with `ValueScanner::config`, and applied with `ValueScanner::set_config`, which allows to
+/// inspect them all at once, or to carry them over to another scanner.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ScanConfig {
+    /// See `ValueScanner::set_case_folding`.
+    pub case_folding: CaseFolding,
+    /// See `ValueScanner::set_read_retries`.
+    pub read_retries: u32,
+    /// See `ValueScanner::set_page_filter`.
+    pub page_filter: Option<PageType>,
+    /// See `ValueScanner::set_min_region_size`.
+    pub min_region_size: Option<umem>,
+    /// See `ValueScanner::set_max_region_size`.
+    pub max_region_size: Option<umem>,
+    /// See `ValueScanner::set_timeout`.
+    pub timeout: Option<Duration>,
+    /// See `ValueScanner::set_insertion_order`.
+    pub insertion_order: bool,
+    /// See `ValueScanner::set_store_values`.
+    pub store_values: bool,
+    /// See `ValueScanner::set_skip_modules`.
+    pub skip_modules: bool,
+    /// See `ValueScanner::set_alignment`. This holds the alignment as set, thus 0 is kept as is.
+    pub alignment: usize,
+}
+
+impl Default for ScanConfig {
+    fn default() -> Self {
+        ValueScanner::default().config()
+    }
+}
 
 /// Describes a value scanner state.
 ///
@@ -10,10 +577,29 @@ use rayon_tlsctx::ThreadLocalCtx;
 ///
 /// That match can then be joined with `PointerMap`'s offset scanner, alongside `Sigmaker` to
 /// create reliable code signature alongside offset tree for the variable.
-#[derive(Default)]
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ValueScanner {
     scanned: bool,
     matches: Vec<Address>,
+    /// Values of the matches at the time of the last scan, `value_size` bytes each.
+    values: Vec<u8>,
+    value_size: usize,
+    case_folding: CaseFolding,
+    read_retries: u32,
+    page_filter: Option<PageType>,
+    min_region_size: Option<umem>,
+    max_region_size: Option<umem>,
+    timeout: Option<Duration>,
+    timed_out: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    cancel: Option<CancelToken>,
+    insertion_order: bool,
+    skip_values: bool,
+    skip_modules: bool,
+    alignment: usize,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    progress: Option<Progress>,
     mem_map: Vec<MemoryRange>,
 }
 
@@ -22,139 +608,2900 @@ impl ValueScanner {
     pub fn reset(&mut self) {
         self.scanned = false;
         self.matches.clear();
+        self.values.clear();
+        self.value_size = 0;
+        self.timed_out = false;
         self.mem_map.clear();
     }
 
-    /// Scan for specific data in the value scanner.
+    /// Set how scanned data is compared against memory.
     ///
-    /// First call will scan entire memory range for data, while consequitive calls will filter the
-    /// data until its reset.
+    /// Exact comparison is the default. Folding modes allow to find text regardless of its case.
+    pub fn set_case_folding(&mut self, case_folding: CaseFolding) {
+        self.case_folding = case_folding;
+    }
+
+    /// Get how scanned data is compared against memory.
+    pub fn case_folding(&self) -> CaseFolding {
+        self.case_folding
+    }
+
+    /// Set how many times failed page reads are retried.
     ///
-    /// # Arguments
+    /// Some connectors occasionally fail reads that succeed when tried again. Retrying prevents
+    /// such pages from being silently skipped. Default is 0, i.e. no retries.
+    pub fn set_read_retries(&mut self, read_retries: u32) {
+        self.read_retries = read_retries;
+    }
+
+    /// Get how many times failed page reads are retried.
+    pub fn read_retries(&self) -> u32 {
+        self.read_retries
+    }
+
+    /// Set a wall-clock limit for every scan, or filter.
     ///
-    /// * `mem` - memory object to scan for values in
-    /// * `data` - data to scan or filter against
-    pub fn scan_for<T: Process + MemoryView + Clone>(
-        &mut self,
-        proc: &mut T,
-        data: &[u8],
-    ) -> Result<()> {
-        self.scan_for_2(proc, |p, a, b, c| p.mapped_mem_range_vec(a, b, c), data)
+    /// Once the limit is reached, no more memory is read, and the operation returns with the
+    /// results gathered so far, and `timed_out` set. The results are then incomplete: an initial
+    /// scan misses matches in the pages it did not reach, while a filter keeps the matches it did
+    /// not reach, without checking them. `None` disables the limit, which is the default.
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
     }
 
-    pub fn scan_for_2<T: MemoryView + Clone>(
-        &mut self,
-        proc: &mut T,
-        maps: fn(&mut T, imem, Address, Address) -> Vec<MemoryRange>,
-        data: &[u8],
-    ) -> Result<()> {
-        if !self.scanned {
-            self.mem_map = maps(
-                proc,
-                mem::mb(16) as _,
-                Address::null(),
-                ((1 as umem) << 47).into(),
-            );
+    /// Get the wall-clock limit of scans.
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
 
-            let pb = PBar::new(
-                self.mem_map
-                    .iter()
-                    .map(|CTup3(_, size, _)| *size as u64)
-                    .sum::<u64>(),
-                true,
-            );
+    /// Check whether the last scan, or filter was cut short by the timeout, or cancellation.
+    pub fn timed_out(&self) -> bool {
+        self.timed_out
+    }
 
-            let ctx = ThreadLocalCtx::new_locked(move || proc.clone());
-            let ctx_buf = ThreadLocalCtx::new(|| vec![0; 0x1000 + data.len() - 1]);
+    /// Allow scans, and filters to be cancelled with a `CancelToken`.
+    ///
+    /// A cancelled operation returns like one that timed out. The token is not reset by the
+    /// scanner. `None` disables cancellation, which is the default.
+    pub fn set_cancel_token(&mut self, cancel: Option<CancelToken>) {
+        self.cancel = cancel;
+    }
 
-            self.matches.par_extend(self.mem_map.par_iter().flat_map(
-                |&CTup3(address, size, _)| {
-                    (0..size)
-                        .into_iter()
-                        .step_by(0x1000)
-                        .par_bridge()
-                        .filter_map(|off| {
-                            let mut mem = unsafe { ctx.get() };
-                            let mut buf = unsafe { ctx_buf.get() };
-
-                            mem.read_raw_into(address + off, buf.as_mut_slice())
-                                .data_part()
-                                .ok()?;
-
-                            pb.add(0x1000);
-
-                            let ret = buf
-                                .windows(data.len())
-                                .enumerate()
-                                .filter_map(|(o, buf)| {
-                                    if buf == data {
-                                        Some(address + off + o)
-                                    } else {
-                                        None
-                                    }
-                                })
-                                .collect::<Vec<_>>()
-                                .into_par_iter();
-
-                            Some(ret)
-                        })
-                        .flatten()
-                        .collect::<Vec<_>>()
-                        .into_par_iter()
-                },
-            ));
+    /// Get the token scans can be cancelled with.
+    pub fn cancel_token(&self) -> Option<&CancelToken> {
+        self.cancel.as_ref()
+    }
 
-            self.scanned = true;
-            pb.finish();
-        } else {
-            const CHUNK_SIZE: usize = 0x100;
+    /// Report the progress of scans, and filters to a shared `Progress`.
+    ///
+    /// This allows to poll the progress from another thread, independently of the `progress_bar`
+    /// feature. `None` stops the reporting, which is the default.
+    pub fn set_progress(&mut self, progress: Option<Progress>) {
+        self.progress = progress;
+    }
 
-            let old_matches = std::mem::replace(&mut self.matches, vec![]);
+    /// Get the shared progress scans report to.
+    pub fn progress(&self) -> Option<&Progress> {
+        self.progress.as_ref()
+    }
 
-            let pb = PBar::new(old_matches.len() as u64, false);
+    /// Keep matches in the order they were found in, instead of sorting them by address.
+    ///
+    /// By default, matches are sorted in ascending order after the initial scan, which keeps their
+    /// indices stable across repeated scans, and allows `contains` to use a binary search. Since
+    /// the initial scan goes through memory in parallel, insertion order is not deterministic.
+    pub fn set_insertion_order(&mut self, insertion_order: bool) {
+        self.insertion_order = insertion_order;
+    }
 
-            let ctx = ThreadLocalCtx::new_locked(move || proc.clone());
-            let ctx_buf = ThreadLocalCtx::new(|| vec![0; CHUNK_SIZE * data.len()]);
+    /// Check whether matches are kept in the order they were found in.
+    pub fn insertion_order(&self) -> bool {
+        self.insertion_order
+    }
 
-            self.matches
-                .par_extend(old_matches.par_chunks(CHUNK_SIZE).flat_map(|chunk| {
-                    let mut mem = unsafe { ctx.get() };
-                    let mut buf = unsafe { ctx_buf.get() };
+    /// Only match data at addresses that are a multiple of `alignment`.
+    ///
+    /// Values of a type are usually placed at multiples of its size, thus aligning scans for them
+    /// skips the misaligned matches, which are almost always junk, and speeds the initial scan up.
+    /// This affects `scan_for`, and `count_matches`; scans of fixed width values are always
+    /// aligned to their width. The default of 0 is treated as 1, matching at every address.
+    pub fn set_alignment(&mut self, alignment: usize) {
+        self.alignment = alignment;
+    }
 
-                    if !data.is_empty() {
-                        let mut batcher = mem.batcher();
+    /// Get the alignment of the addresses scans match at.
+    pub fn alignment(&self) -> usize {
+        self.alignment.max(1)
+    }
 
-                        for (&a, buf) in chunk.iter().zip(buf.chunks_mut(data.len())) {
-                            batcher.read_raw_into(a, buf);
-                        }
-                    }
+    /// Set whether the values of the matches are stored alongside them.
+    ///
+    /// Values are stored by default, which allows filters to compare against the value seen by
+    /// the previous scan, and `previous_value` to return it without reading memory again. Not
+    /// storing them saves memory on scans with many matches. Turning storage off discards the
+    /// values stored so far.
+    ///
+    /// # Arguments
+    ///
+    /// * `store_values` - whether to store values
+    pub fn set_store_values(&mut self, store_values: bool) {
+        self.skip_values = !store_values;
 
-                    pb.add(chunk.len() as u64);
+        if !store_values {
+            self.values = vec![];
+        }
+    }
 
-                    let mut out = vec![];
+    /// Check whether the values of the matches are stored.
+    pub fn store_values(&self) -> bool {
+        !self.skip_values
+    }
 
-                    if !data.is_empty() {
-                        out.extend(
-                            chunk
-                                .iter()
-                                .zip(buf.chunks(data.len()))
-                                .filter_map(|(&a, buf)| if buf == data { Some(a) } else { None }),
-                        );
-                    }
+    /// Set whether the process shorthands skip loaded modules.
+    ///
+    /// Mutable state rarely lives in module images, and skipping them avoids matches in constant
+    /// data. This only affects the initial scans of functions taking a process, which then use
+    /// `process_maps_without_modules`. Modules are scanned by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `skip_modules` - whether to skip modules
+    pub fn set_skip_modules(&mut self, skip_modules: bool) {
+        self.skip_modules = skip_modules;
+    }
 
-                    out.into_par_iter()
-                }));
-            pb.finish();
+    /// Check whether the process shorthands skip loaded modules.
+    pub fn skip_modules(&self) -> bool {
+        self.skip_modules
+    }
+
+    /// Get the function the process shorthands list memory regions with.
+    pub fn process_maps_fn<T: Process>(&self) -> MapsFn<T> {
+        if self.skip_modules {
+            process_maps_without_modules
+        } else {
+            process_maps
         }
+    }
 
-        Ok(())
+    /// Limit initial scans to pages of specific type.
+    ///
+    /// Only regions having all of the given flags are scanned, for instance,
+    /// `PageType::WRITEABLE | PageType::NOEXEC` skips code and read-only data. Regions of unknown
+    /// type are always scanned. `None` scans all regions, which is the default.
+    pub fn set_page_filter(&mut self, page_filter: Option<PageType>) {
+        self.page_filter = page_filter;
     }
 
-    pub fn matches(&self) -> &Vec<Address> {
-        &self.matches
+    /// Get the page type initial scans are limited to.
+    pub fn page_filter(&self) -> Option<PageType> {
+        self.page_filter
     }
 
-    pub fn matches_mut(&mut self) -> &mut Vec<Address> {
-        &mut self.matches
+    /// Skip regions smaller than the given size in initial scans.
+    ///
+    /// Tiny regions rarely hold anything of interest. `None` disables the limit, which is the
+    /// default.
+    pub fn set_min_region_size(&mut self, min_region_size: Option<umem>) {
+        self.min_region_size = min_region_size;
+    }
+
+    /// Get the size of the smallest region initial scans go through.
+    pub fn min_region_size(&self) -> Option<umem> {
+        self.min_region_size
+    }
+
+    /// Skip regions larger than the given size in initial scans.
+    ///
+    /// Huge regions, such as memory mapped files, or reserved arenas, can dominate the scan time,
+    /// while rarely holding the value. `None` disables the limit, which is the default.
+    pub fn set_max_region_size(&mut self, max_region_size: Option<umem>) {
+        self.max_region_size = max_region_size;
+    }
+
+    /// Get the size of the largest region initial scans go through.
+    pub fn max_region_size(&self) -> Option<umem> {
+        self.max_region_size
+    }
+
+    /// Get all settings of the scanner.
+    pub fn config(&self) -> ScanConfig {
+        ScanConfig {
+            case_folding: self.case_folding,
+            read_retries: self.read_retries,
+            page_filter: self.page_filter,
+            min_region_size: self.min_region_size,
+            max_region_size: self.max_region_size,
+            timeout: self.timeout,
+            insertion_order: self.insertion_order,
+            store_values: self.store_values(),
+            skip_modules: self.skip_modules,
+            alignment: self.alignment,
+        }
+    }
+
+    /// Apply all settings at once.
+    ///
+    /// This works like calling every setter in turn, thus turning value storage off discards the
+    /// values stored so far.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - settings to apply
+    pub fn set_config(&mut self, config: &ScanConfig) {
+        self.set_case_folding(config.case_folding);
+        self.set_read_retries(config.read_retries);
+        self.set_page_filter(config.page_filter);
+        self.set_min_region_size(config.min_region_size);
+        self.set_max_region_size(config.max_region_size);
+        self.set_timeout(config.timeout);
+        self.set_insertion_order(config.insertion_order);
+        self.set_store_values(config.store_values);
+        self.set_skip_modules(config.skip_modules);
+        self.set_alignment(config.alignment);
+    }
+
+    /// Filter out regions not matching the page filter, or the region size limits.
+    ///
+    /// # Arguments
+    ///
+    /// * `regions` - regions to filter
+    pub fn filter_regions(&self, mut regions: Vec<MemoryRange>) -> Vec<MemoryRange> {
+        if let Some(filter) = self.page_filter {
+            regions.retain(|&CTup3(_, _, page_type)| {
+                page_type.contains(PageType::UNKNOWN) || page_type.contains(filter)
+            });
+        }
+
+        filter_region_sizes(&mut regions, self.min_region_size, self.max_region_size);
+
+        regions
+    }
+
+    /// Scan for specific data in the value scanner.
+    ///
+    /// First call will scan entire memory range for data, while consequitive calls will filter the
+    /// data until its reset.
+    ///
+    /// # Arguments
+    ///
+    /// * `proc` - memory object to scan for values in
+    /// * `data` - data to scan or filter against
+    pub fn scan_for<T: Process + MemoryView + Clone>(
+        &mut self,
+        proc: &mut T,
+        data: &[u8],
+    ) -> Result<()> {
+        let range = default_scan_range(proc);
+        self.scan_for_in_range(proc, data, range)
+    }
+
+    /// Scan for specific data within an address range, or filter existing matches by it.
+    ///
+    /// This works like `scan_for`, but the initial scan only goes through the regions within
+    /// `range`, instead of the default range of the process architecture. This allows to scan
+    /// kernel addresses, or to skip parts of the address space.
+    ///
+    /// # Arguments
+    ///
+    /// * `proc` - process to scan for values in
+    /// * `data` - data to scan or filter against
+    /// * `range` - addresses to perform the initial scan in
+    pub fn scan_for_in_range<T: Process + MemoryView + Clone>(
+        &mut self,
+        proc: &mut T,
+        data: &[u8],
+        range: Range<Address>,
+    ) -> Result<()> {
+        let regions = if self.scanned {
+            vec![]
+        } else {
+            Self::memory_regions_in_range(proc, self.process_maps_fn(), range)
+        };
+
+        self.scan_for_in_regions(proc, regions, data)
+    }
+
+    /// Scan for specific data within a module, or filter existing matches by it.
+    ///
+    /// This works like `scan_for`, but the initial scan only goes through the regions within the
+    /// image of the module named `module_name`, see `module_regions`. The module is scanned even
+    /// if modules are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `proc` - process to scan for values in
+    /// * `data` - data to scan or filter against
+    /// * `module_name` - name of the module to perform the initial scan in
+    pub fn scan_for_module<T: Process + MemoryView + Clone>(
+        &mut self,
+        proc: &mut T,
+        data: &[u8],
+        module_name: &str,
+    ) -> Result<()> {
+        let regions = if self.scanned {
+            vec![]
+        } else {
+            Self::module_regions(proc, module_name)?
+        };
+
+        self.scan_for_in_regions(proc, regions, data)
+    }
+
+    /// Scan for specific data in regions of a class, or filter existing matches by it.
+    ///
+    /// This works like `scan_for`, but the initial scan only goes through the regions classified
+    /// as `class`, for instance, only the heap. See `regions::classify_regions` for how regions are
+    /// classified.
+    ///
+    /// # Arguments
+    ///
+    /// * `proc` - process to scan for values in
+    /// * `class` - class of the regions to perform the initial scan in
+    /// * `data` - data to scan or filter against
+    pub fn scan_for_in_class<T: Process + MemoryView + Clone>(
+        &mut self,
+        proc: &mut T,
+        class: RegionClass,
+        data: &[u8],
+    ) -> Result<()> {
+        let regions = if self.scanned {
+            vec![]
+        } else {
+            regions_of_class(proc, class)?
+        };
+
+        self.scan_for_in_regions(proc, regions, data)
+    }
+
+    /// Scan for specific data, or filter existing matches by it.
+    ///
+    /// This works like `scan_for`, but lists the memory regions of the initial scan with `maps`.
+    ///
+    /// # Arguments
+    ///
+    /// * `proc` - memory object to scan for values in
+    /// * `maps` - function to list the memory regions with
+    /// * `data` - data to scan or filter against
+    #[deprecated(note = "use `scan_for_in_regions` with `initial_regions`")]
+    pub fn scan_for_2<T: MemoryView + Clone>(
+        &mut self,
+        proc: &mut T,
+        maps: MapsFn<T>,
+        data: &[u8],
+    ) -> Result<()> {
+        let regions = self.initial_regions(proc, maps);
+
+        self.scan_for_in_regions(proc, regions, data)
+    }
+
+    /// Scan for specific data within the given memory regions.
+    ///
+    /// This works like `scan_for`, but the first call will only go through `regions`.
+    /// Consecutive calls filter existing matches and ignore `regions`.
+    ///
+    /// # Arguments
+    ///
+    /// * `proc` - memory object to scan for values in
+    /// * `regions` - memory regions to perform the initial scan in
+    /// * `data` - data to scan or filter against
+    pub fn scan_for_in_regions<T: MemoryView + Clone>(
+        &mut self,
+        proc: &mut T,
+        regions: Vec<MemoryRange>,
+        data: &[u8],
+    ) -> Result<()> {
+        let folding = self.case_folding;
+        let little_endian = proc.metadata().little_endian;
+        let pattern = folding.fold(data, little_endian);
+
+        if !self.scanned {
+            self.mem_map = self.filter_regions(regions);
+
+            let deadline = Deadline::new(self.timeout, self.cancel.as_ref());
+            let store_values = self.store_values();
+            let align = self.alignment();
+
+            let pages = self.scan_pages(
+                proc,
+                &self.mem_map,
+                data.len(),
+                &deadline,
+                |address, buf| {
+                    let mut matches = vec![];
+                    let mut values = vec![];
+                    let start = first_aligned(address, align);
+
+                    if folding == CaseFolding::Exact {
+                        find_exact(buf, &pattern, start, align, |o| {
+                            matches.push(address + o);
+                            if store_values {
+                                values.extend_from_slice(&pattern);
+                            }
+                        });
+                    } else {
+                        for (o, buf) in buf
+                            .windows(data.len())
+                            .enumerate()
+                            .skip(start)
+                            .step_by(align)
+                        {
+                            if folding.matches(buf, &pattern, little_endian) {
+                                matches.push(address + o);
+                                if store_values {
+                                    values.extend_from_slice(buf);
+                                }
+                            }
+                        }
+                    }
+
+                    (matches, values)
+                },
+            );
+
+            for (matches, values) in pages {
+                self.matches.extend(matches);
+                self.values.extend(values);
+            }
+
+            self.value_size = data.len();
+            self.timed_out = deadline.hit();
+            self.sort_matches();
+
+            self.scanned = true;
+
+            deadline.check_gone()
+        } else {
+            self.refine(proc, data.len(), |_, cur| {
+                folding.matches(cur, &pattern, little_endian)
+            })
+        }
+    }
+
+    /// Scan for a byte pattern with wildcards, or filter existing matches by it.
+    ///
+    /// Wildcards, the `None` entries of the pattern, match any byte. This finds code, and data
+    /// whose bytes are only partially known, such as an instruction with an unknown displacement.
+    /// Matches are not aligned, and the matched bytes are stored as their values.
+    ///
+    /// # Arguments
+    ///
+    /// * `proc` - memory object to scan for the pattern in
+    /// * `pattern` - bytes to scan or filter against, `None` being a wildcard
+    pub fn scan_aob<T: Process + MemoryView + Clone>(
+        &mut self,
+        proc: &mut T,
+        pattern: &[Option<u8>],
+    ) -> Result<()> {
+        let regions = self.initial_regions(proc, self.process_maps_fn());
+        self.scan_aob_in_regions(proc, regions, pattern)
+    }
+
+    /// Scan for a byte pattern with wildcards within the given memory regions.
+    ///
+    /// This works like `scan_aob`, but the first call will only go through `regions`.
+    /// Consecutive calls filter existing matches and ignore `regions`.
+    ///
+    /// # Arguments
+    ///
+    /// * `proc` - memory object to scan for the pattern in
+    /// * `regions` - memory regions to perform the initial scan in
+    /// * `pattern` - bytes to scan or filter against, `None` being a wildcard
+    pub fn scan_aob_in_regions<T: MemoryView + Clone>(
+        &mut self,
+        proc: &mut T,
+        regions: Vec<MemoryRange>,
+        pattern: &[Option<u8>],
+    ) -> Result<()> {
+        if pattern.iter().all(Option::is_none) {
+            return Err(Error::InvalidArgument(
+                "pattern needs at least one byte that is not a wildcard".into(),
+            ));
+        }
+
+        if !self.scanned {
+            self.mem_map = self.filter_regions(regions);
+
+            let deadline = Deadline::new(self.timeout, self.cancel.as_ref());
+            let store_values = self.store_values();
+
+            let pages = self.scan_pages(
+                proc,
+                &self.mem_map,
+                pattern.len(),
+                &deadline,
+                |address, buf| {
+                    let mut matches = vec![];
+                    let mut values = vec![];
+
+                    find_masked(buf, pattern, |o| {
+                        matches.push(address + o);
+                        if store_values {
+                            values.extend_from_slice(&buf[o..(o + pattern.len())]);
+                        }
+                    });
+
+                    (matches, values)
+                },
+            );
+
+            for (matches, values) in pages {
+                self.matches.extend(matches);
+                self.values.extend(values);
+            }
+
+            self.value_size = pattern.len();
+            self.timed_out = deadline.hit();
+            self.sort_matches();
+
+            self.scanned = true;
+
+            deadline.check_gone()
+        } else {
+            self.refine(proc, pattern.len(), |_, cur| masked_eq(cur, pattern))
+        }
+    }
+
+    /// Scan for pointers to a specific address, or filter existing matches by it.
+    ///
+    /// The address is encoded with the pointer width of the process architecture, and the byte
+    /// order of the memory object, and scanned for like any other data. This finds the pointers to
+    /// a single address, without building a `PointerMap`.
+    ///
+    /// # Arguments
+    ///
+    /// * `proc` - memory object to scan for pointers in
+    /// * `target` - address the pointers point to
+    pub fn scan_for_pointer<T: Process + MemoryView + Clone>(
+        &mut self,
+        proc: &mut T,
+        target: Address,
+    ) -> Result<()> {
+        let size_addr = ArchitectureObj::from(proc.info().proc_arch).size_addr();
+        let regions = self.initial_regions(proc, self.process_maps_fn());
+        self.scan_for_pointer_in_regions(proc, regions, target, size_addr)
+    }
+
+    /// Scan for pointers to a specific address within the given memory regions.
+    ///
+    /// This works like `scan_for_pointer`, but the first call will only go through `regions`, and
+    /// the pointer width is taken explicitly. Consecutive calls filter existing matches and
+    /// ignore `regions`.
+    ///
+    /// # Arguments
+    ///
+    /// * `proc` - memory object to scan for pointers in
+    /// * `regions` - memory regions to perform the initial scan in
+    /// * `target` - address the pointers point to
+    /// * `size_addr` - size of a pointer, either 4 or 8 bytes
+    pub fn scan_for_pointer_in_regions<T: MemoryView + Clone>(
+        &mut self,
+        proc: &mut T,
+        regions: Vec<MemoryRange>,
+        target: Address,
+        size_addr: usize,
+    ) -> Result<()> {
+        let target = target.to_umem();
+        let little_endian = proc.metadata().little_endian;
+
+        let data = match size_addr {
+            4 => {
+                let target = u32::try_from(target).map_err(|_| {
+                    Error::InvalidArgument(format!(
+                        "{:x} does not fit into a 4 byte pointer",
+                        target
+                    ))
+                })?;
+
+                if little_endian {
+                    target.to_le_bytes().to_vec()
+                } else {
+                    target.to_be_bytes().to_vec()
+                }
+            }
+            8 if little_endian => target.to_le_bytes().to_vec(),
+            8 => target.to_be_bytes().to_vec(),
+            _ => {
+                return Err(Error::InvalidArgument(format!(
+                    "pointers are 4 or 8 bytes wide, not {}",
+                    size_addr
+                )))
+            }
+        };
+
+        self.scan_for_in_regions(proc, regions, &data)
+    }
+
+    /// Scan for a float value, or filter existing matches by it.
+    ///
+    /// This works like `scan_for`, but values are compared as floats, according to `mode`. The
+    /// initial scan only considers addresses aligned to `width`, since floats that are not are
+    /// rare, and the loose modes would produce plenty of garbage matches from unaligned bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `proc` - memory object to scan for values in
+    /// * `value` - value to scan or filter against
+    /// * `width` - width of the float in bytes, either 4 or 8
+    /// * `mode` - how values are compared against `value`
+    pub fn scan_for_float<T: Process + MemoryView + Clone>(
+        &mut self,
+        proc: &mut T,
+        value: f64,
+        width: usize,
+        mode: FloatMatch,
+    ) -> Result<()> {
+        let regions = self.initial_regions(proc, self.process_maps_fn());
+        self.scan_for_float_in_regions(proc, regions, value, width, mode)
+    }
+
+    /// Scan for a float value within the given memory regions.
+    ///
+    /// This works like `scan_for_float`, but the first call will only go through `regions`.
+    /// Consecutive calls filter existing matches and ignore `regions`.
+    ///
+    /// # Arguments
+    ///
+    /// * `proc` - memory object to scan for values in
+    /// * `regions` - memory regions to perform the initial scan in
+    /// * `value` - value to scan or filter against
+    /// * `width` - width of the float in bytes, either 4 or 8
+    /// * `mode` - how values are compared against `value`
+    pub fn scan_for_float_in_regions<T: MemoryView + Clone>(
+        &mut self,
+        proc: &mut T,
+        regions: Vec<MemoryRange>,
+        value: f64,
+        width: usize,
+        mode: FloatMatch,
+    ) -> Result<()> {
+        // Exact comparisons happen at the precision of the scanned type.
+        let target = match width {
+            4 => value as f32 as f64,
+            8 => value,
+            _ => {
+                return Err(Error::InvalidArgument(format!(
+                    "floats are 4 or 8 bytes wide, not {}",
+                    width
+                )))
+            }
+        };
+
+        let little_endian = proc.metadata().little_endian;
+
+        self.scan_with_in_regions(proc, regions, width, move |buf| {
+            mode.matches(bytes_to_f64(buf, little_endian), target)
+        })
+    }
+
+    /// Scan for an `f32` close to a value, or filter existing matches by it.
+    ///
+    /// Values are kept when `(value - target).abs() <= epsilon`, which finds coordinates, and other
+    /// values that are only known up to rounding. This is a shorthand for `scan_for_float` with
+    /// `FloatMatch::Epsilon`.
+    ///
+    /// # Arguments
+    ///
+    /// * `proc` - process to scan for values in
+    /// * `target` - value to scan or filter against
+    /// * `epsilon` - largest distance from `target` to keep values at
+    pub fn scan_f32_near<T: Process + MemoryView + Clone>(
+        &mut self,
+        proc: &mut T,
+        target: f32,
+        epsilon: f32,
+    ) -> Result<()> {
+        let mode = float_near(epsilon.into())?;
+        self.scan_for_float(proc, target.into(), 4, mode)
+    }
+
+    /// Scan for an `f64` close to a value, or filter existing matches by it.
+    ///
+    /// This works like `scan_f32_near`, but for double precision values.
+    ///
+    /// # Arguments
+    ///
+    /// * `proc` - process to scan for values in
+    /// * `target` - value to scan or filter against
+    /// * `epsilon` - largest distance from `target` to keep values at
+    pub fn scan_f64_near<T: Process + MemoryView + Clone>(
+        &mut self,
+        proc: &mut T,
+        target: f64,
+        epsilon: f64,
+    ) -> Result<()> {
+        let mode = float_near(epsilon)?;
+        self.scan_for_float(proc, target, 8, mode)
+    }
+
+    /// Scan for integers within a range of values, or filter existing matches by it.
+    ///
+    /// Every address aligned to `width` is read as an integer of the given width and signedness,
+    /// and kept if its value lies within `lo..=hi`. This finds values that are only roughly known,
+    /// such as a health bar that is about half full.
+    ///
+    /// # Arguments
+    ///
+    /// * `proc` - memory object to scan for values in
+    /// * `lo` - smallest value to keep
+    /// * `hi` - largest value to keep
+    /// * `width` - width of the integer in bytes, up to 16
+    /// * `signed` - whether the integer is signed
+    pub fn scan_for_range<T: Process + MemoryView + Clone>(
+        &mut self,
+        proc: &mut T,
+        lo: i128,
+        hi: i128,
+        width: usize,
+        signed: bool,
+    ) -> Result<()> {
+        let regions = self.initial_regions(proc, self.process_maps_fn());
+        self.scan_for_range_in_regions(proc, regions, lo, hi, width, signed)
+    }
+
+    /// Scan for integers within a range of values, within the given memory regions.
+    ///
+    /// This works like `scan_for_range`, but the first call will only go through `regions`.
+    /// Consecutive calls filter existing matches and ignore `regions`.
+    ///
+    /// # Arguments
+    ///
+    /// * `proc` - memory object to scan for values in
+    /// * `regions` - memory regions to perform the initial scan in
+    /// * `lo` - smallest value to keep
+    /// * `hi` - largest value to keep
+    /// * `width` - width of the integer in bytes, up to 16
+    /// * `signed` - whether the integer is signed
+    pub fn scan_for_range_in_regions<T: MemoryView + Clone>(
+        &mut self,
+        proc: &mut T,
+        regions: Vec<MemoryRange>,
+        lo: i128,
+        hi: i128,
+        width: usize,
+        signed: bool,
+    ) -> Result<()> {
+        if width == 0 || width > 16 || !width.is_power_of_two() {
+            return Err(Error::InvalidArgument(format!(
+                "range scans support widths of 1, 2, 4, 8, or 16 bytes, not {}",
+                width
+            )));
+        }
+
+        if lo > hi {
+            return Err(Error::InvalidArgument(format!(
+                "range {}..{} is empty",
+                lo, hi
+            )));
+        }
+
+        let little_endian = proc.metadata().little_endian;
+
+        self.scan_with_in_regions(proc, regions, width, move |buf| {
+            bytes_to_i128(buf, little_endian, signed).is_some_and(|v| lo <= v && v <= hi)
+        })
+    }
+
+    /// Scan for numbers passing a comparison, or filter existing matches by it.
+    ///
+    /// Every address aligned to the width of the type is read as a number of that type, and kept
+    /// if it passes `pred`. Integers are compared according to their signedness, floats by value,
+    /// NaN failing every comparison. This finds values that are only roughly known, such as a
+    /// timer that is above some value.
+    ///
+    /// # Arguments
+    ///
+    /// * `proc` - memory object to scan for values in
+    /// * `typename` - name of an integer, or float type, such as `i32`
+    /// * `pred` - comparison to keep values by
+    pub fn scan_for_cmp<T: Process + MemoryView + Clone>(
+        &mut self,
+        proc: &mut T,
+        typename: &str,
+        pred: &ScanPredicate,
+    ) -> Result<()> {
+        let regions = self.initial_regions(proc, self.process_maps_fn());
+        self.scan_for_cmp_in_regions(proc, regions, typename, pred)
+    }
+
+    /// Scan for numbers passing a comparison within the given memory regions.
+    ///
+    /// This works like `scan_for_cmp`, but the first call will only go through `regions`.
+    /// Consecutive calls filter existing matches and ignore `regions`.
+    ///
+    /// # Arguments
+    ///
+    /// * `proc` - memory object to scan for values in
+    /// * `regions` - memory regions to perform the initial scan in
+    /// * `typename` - name of an integer, or float type, such as `i32`
+    /// * `pred` - comparison to keep values by
+    pub fn scan_for_cmp_in_regions<T: MemoryView + Clone>(
+        &mut self,
+        proc: &mut T,
+        regions: Vec<MemoryRange>,
+        typename: &str,
+        pred: &ScanPredicate,
+    ) -> Result<()> {
+        let (kind, width) = NumKind::of(typename).ok_or_else(|| {
+            Error::InvalidArgument(format!(
+                "comparison scans require an integer, or float type, not `{}`",
+                typename
+            ))
+        })?;
+
+        let bound = |buf: &[u8]| {
+            if buf.len() != width {
+                return Err(Error::InvalidArgument(format!(
+                    "bound is {} bytes wide, but `{}` is {}",
+                    buf.len(),
+                    typename,
+                    width
+                )));
+            }
+
+            kind.read(buf, cfg!(target_endian = "little"))
+                .ok_or_else(|| Error::InvalidArgument("bound is not a valid number".into()))
+        };
+
+        let (lo, hi) = match pred {
+            ScanPredicate::Gt(v) => (Some(bound(v)?), None),
+            ScanPredicate::Lt(v) => (None, Some(bound(v)?)),
+            ScanPredicate::Between(lo, hi) => (Some(bound(lo)?), Some(bound(hi)?)),
+        };
+
+        if let (Some(lo), Some(hi)) = (lo, hi) {
+            if lo
+                .partial_cmp(&hi)
+                .map_or(true, |o| o == std::cmp::Ordering::Greater)
+            {
+                return Err(Error::InvalidArgument("range is empty".into()));
+            }
+        }
+
+        let inclusive = matches!(pred, ScanPredicate::Between(_, _));
+        let little_endian = proc.metadata().little_endian;
+
+        self.scan_with_in_regions(proc, regions, width, move |buf| {
+            kind.read(buf, little_endian).is_some_and(|v| {
+                let above = lo.map_or(true, |lo| v > lo || (inclusive && v == lo));
+                let below = hi.map_or(true, |hi| v < hi || (inclusive && v == hi));
+                above && below
+            })
+        })
+    }
+
+    /// Scan with a custom predicate, or filter existing matches by it.
+    ///
+    /// Every address aligned to `width` is read, and kept if `pred` returns true for its bytes.
+    /// This allows for arbitrary scan criteria, for which there is no built-in scan. The
+    /// predicate is called from multiple threads at once.
+    ///
+    /// # Arguments
+    ///
+    /// * `proc` - memory object to scan for values in
+    /// * `width` - size of the values in bytes, a power of two of up to 4096
+    /// * `pred` - function deciding whether to keep a value
+    pub fn scan_with<T: Process + MemoryView + Clone>(
+        &mut self,
+        proc: &mut T,
+        width: usize,
+        pred: impl Fn(&[u8]) -> bool + Sync,
+    ) -> Result<()> {
+        let regions = self.initial_regions(proc, self.process_maps_fn());
+        self.scan_with_in_regions(proc, regions, width, pred)
+    }
+
+    /// Scan with a custom predicate within the given memory regions.
+    ///
+    /// This works like `scan_with`, but the first call will only go through `regions`.
+    /// Consecutive calls filter existing matches and ignore `regions`. Values of the kept matches
+    /// are stored either way.
+    ///
+    /// # Arguments
+    ///
+    /// * `proc` - memory object to scan for values in
+    /// * `regions` - memory regions to perform the initial scan in
+    /// * `width` - size of the values in bytes, a power of two of up to 4096
+    /// * `pred` - function deciding whether to keep a value
+    pub fn scan_with_in_regions<T: MemoryView + Clone>(
+        &mut self,
+        proc: &mut T,
+        regions: Vec<MemoryRange>,
+        width: usize,
+        pred: impl Fn(&[u8]) -> bool + Sync,
+    ) -> Result<()> {
+        // Only such widths tile pages exactly, see the chunking below.
+        if !width.is_power_of_two() || width > 0x1000 {
+            return Err(Error::InvalidArgument(format!(
+                "scan width must be a power of two of up to 4096 bytes, not {}",
+                width
+            )));
+        }
+
+        if !self.scanned {
+            self.mem_map = self.filter_regions(regions);
+
+            let deadline = Deadline::new(self.timeout, self.cancel.as_ref());
+            let store_values = self.store_values();
+
+            // Aligned values never cross page boundaries, thus no overlap is needed.
+            let pages = self.scan_pages(proc, &self.mem_map, 1, &deadline, |address, buf| {
+                let mut matches = vec![];
+                let mut values = vec![];
+
+                for (i, buf) in buf.chunks_exact(width).enumerate() {
+                    if pred(buf) {
+                        matches.push(address + i * width);
+                        if store_values {
+                            values.extend_from_slice(buf);
+                        }
+                    }
+                }
+
+                (matches, values)
+            });
+
+            for (matches, values) in pages {
+                self.matches.extend(matches);
+                self.values.extend(values);
+            }
+
+            self.value_size = width;
+            self.timed_out = deadline.hit();
+            self.sort_matches();
+
+            self.scanned = true;
+
+            deadline.check_gone()
+        } else {
+            self.refine(proc, width, |_, cur| pred(cur))
+        }
+    }
+
+    /// Take a snapshot of all values of a width, to filter by how they change.
+    ///
+    /// Every address aligned to `width` becomes a match, with its current value stored, so that
+    /// `filter_delta` can subsequently keep the ones that changed in a certain way. This is the
+    /// starting point for values that are not known at all, such as a health bar without a
+    /// number. Every aligned unit of the readable pages keeps its address, and value, thus a
+    /// snapshot takes `(8 + width) / width` times the readable memory, for instance, three times
+    /// for 4 byte values, while unreadable pages take nothing. Narrow the regions down where
+    /// possible.
+    ///
+    /// Unlike `scan_for`, this always starts a new scan, discarding existing matches. Values have
+    /// to be stored, see `set_store_values`.
+    ///
+    /// # Arguments
+    ///
+    /// * `proc` - memory object to take the snapshot of
+    /// * `width` - width of the values in bytes
+    pub fn scan_unknown<T: Process + MemoryView + Clone>(
+        &mut self,
+        proc: &mut T,
+        width: usize,
+    ) -> Result<()> {
+        let regions = Self::memory_regions(proc, self.process_maps_fn());
+        self.scan_unknown_in_regions(proc, regions, width)
+    }
+
+    /// Take a snapshot of all values of a width within the given memory regions.
+    ///
+    /// This works like `scan_unknown`, but only goes through `regions`.
+    ///
+    /// # Arguments
+    ///
+    /// * `proc` - memory object to take the snapshot of
+    /// * `regions` - memory regions to take the snapshot of
+    /// * `width` - width of the values in bytes
+    pub fn scan_unknown_in_regions<T: MemoryView + Clone>(
+        &mut self,
+        proc: &mut T,
+        regions: Vec<MemoryRange>,
+        width: usize,
+    ) -> Result<()> {
+        if !self.store_values() {
+            return Err(Error::InvalidArgument(
+                "unknown value scans require values to be stored".into(),
+            ));
+        }
+
+        self.reset();
+        self.scan_with_in_regions(proc, regions, width, |_| true)
+    }
+
+    /// Find the addresses whose contents differ between two snapshots of the same memory.
+    ///
+    /// This is useful for targets that can not be paused interactively, such as core dumps
+    /// captured at different points in time. Both snapshots are walked through the memory
+    /// regions mapped in both of them, and compared in `width` byte units, aligned to the start of
+    /// the region. Units that differ become the matches, and their values in `b` are stored as
+    /// the previous values, thus `b` can be subsequently filtered as usual.
+    ///
+    /// Unlike `scan_for`, this always starts a new scan, discarding existing matches.
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - earlier snapshot
+    /// * `b` - later snapshot
+    /// * `width` - size of the compared units in bytes, for instance, the size of the value type
+    pub fn diff<T: Process + MemoryView + Clone>(
+        &mut self,
+        a: &mut T,
+        b: &mut T,
+        width: usize,
+    ) -> Result<()> {
+        let regions = Self::common_regions(a, self.process_maps_fn(), b, self.process_maps_fn());
+        self.diff_in_regions(a, b, regions, width)
+    }
+
+    /// Find the addresses whose contents differ between two snapshots, within the given regions.
+    ///
+    /// This works like `diff`, but only goes through `regions`.
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - earlier snapshot
+    /// * `b` - later snapshot
+    /// * `regions` - memory regions to compare
+    /// * `width` - size of the compared units in bytes
+    pub fn diff_in_regions<T: MemoryView + Clone>(
+        &mut self,
+        a: &mut T,
+        b: &mut T,
+        regions: Vec<MemoryRange>,
+        width: usize,
+    ) -> Result<()> {
+        self.compare_views(a, b, regions, width, |ua, ub| ua != ub)
+    }
+
+    /// Find the addresses holding the same contents in two different memory objects.
+    ///
+    /// This is useful for isolating state shared between related processes, such as two
+    /// instances of a game, or a client and a server. Both memory objects are walked through the
+    /// memory regions mapped in both of them, and compared in `width` byte units, aligned to the
+    /// start of the region. Units that are equal become the matches, and their values in `b` are
+    /// stored as the previous values, thus `b` can be subsequently filtered as usual.
+    ///
+    /// Units that are zero in both are skipped, since untouched memory is equal in any two
+    /// processes. To find the units that differ instead, use `diff`.
+    ///
+    /// Unlike `scan_for`, this always starts a new scan, discarding existing matches.
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - first memory object
+    /// * `b` - second memory object
+    /// * `width` - size of the compared units in bytes, for instance, the size of the value type
+    pub fn scan_equal_between<A, B>(&mut self, a: &mut A, b: &mut B, width: usize) -> Result<()>
+    where
+        A: Process + MemoryView + Clone,
+        B: Process + MemoryView + Clone,
+    {
+        let regions = Self::common_regions(a, self.process_maps_fn(), b, self.process_maps_fn());
+        self.scan_equal_between_in_regions(a, b, regions, width)
+    }
+
+    /// Find the addresses holding the same contents in two memory objects, within the given
+    /// regions.
+    ///
+    /// This works like `scan_equal_between`, but only goes through `regions`.
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - first memory object
+    /// * `b` - second memory object
+    /// * `regions` - memory regions to compare
+    /// * `width` - size of the compared units in bytes
+    pub fn scan_equal_between_in_regions<A, B>(
+        &mut self,
+        a: &mut A,
+        b: &mut B,
+        regions: Vec<MemoryRange>,
+        width: usize,
+    ) -> Result<()>
+    where
+        A: MemoryView + Clone,
+        B: MemoryView + Clone,
+    {
+        self.compare_views(a, b, regions, width, |ua, ub| {
+            ua == ub && ua.iter().any(|&b| b != 0)
+        })
+    }
+
+    /// Compare two memory objects unit by unit, keeping the units for which `keep` holds.
+    fn compare_views<A: MemoryView + Clone, B: MemoryView + Clone>(
+        &mut self,
+        a: &mut A,
+        b: &mut B,
+        regions: Vec<MemoryRange>,
+        width: usize,
+        keep: impl Fn(&[u8], &[u8]) -> bool + Sync,
+    ) -> Result<()> {
+        if width == 0 || width > 0x1000 {
+            return Err(Error::InvalidArgument(format!(
+                "unit width must be between 1 and 4096 bytes, got {}",
+                width
+            )));
+        }
+
+        self.reset();
+        self.mem_map = self.filter_regions(regions);
+
+        // Keep every read a whole number of units
+        let step = 0x1000 / width * width;
+
+        let pb = PBar::new(
+            self.mem_map
+                .iter()
+                .map(|CTup3(_, size, _)| *size)
+                .sum::<u64>(),
+            true,
+        )
+        .report_to(self.progress.as_ref());
+
+        let read_retries = self.read_retries;
+        let store_values = self.store_values();
+        let deadline = Deadline::new(self.timeout, self.cancel.as_ref());
+
+        let ctx_a = ThreadLocalCtx::new_locked(move || a.clone());
+        let ctx_b = ThreadLocalCtx::new_locked(move || b.clone());
+        let ctx_buf = ThreadLocalCtx::new(|| (vec![0; step], vec![0; step]));
+
+        let pages = self
+            .mem_map
+            .par_iter()
+            .flat_map(|&CTup3(address, size, _)| {
+                (0..size)
+                    .step_by(step)
+                    .par_bridge()
+                    .filter_map(|off| {
+                        if deadline.expired() {
+                            return None;
+                        }
+
+                        let mut mem_a = unsafe { ctx_a.get() };
+                        let mut mem_b = unsafe { ctx_b.get() };
+                        let mut bufs = unsafe { ctx_buf.get() };
+                        let (buf_a, buf_b) = &mut *bufs;
+
+                        let len = std::cmp::min(step as umem, size - off) as usize;
+                        let (buf_a, buf_b) = (&mut buf_a[..len], &mut buf_b[..len]);
+
+                        let addr = address + off;
+
+                        if !read_with_retries(&mut *mem_a, addr, buf_a, read_retries, &deadline)
+                            || !read_with_retries(&mut *mem_b, addr, buf_b, read_retries, &deadline)
+                        {
+                            return None;
+                        }
+
+                        pb.add(len as u64);
+
+                        let mut matches = vec![];
+                        let mut values = vec![];
+
+                        for (o, (ua, ub)) in buf_a
+                            .chunks_exact(width)
+                            .zip(buf_b.chunks_exact(width))
+                            .enumerate()
+                        {
+                            if keep(ua, ub) {
+                                matches.push(addr + o * width);
+                                if store_values {
+                                    values.extend_from_slice(ub);
+                                }
+                            }
+                        }
+
+                        Some((matches, values))
+                    })
+                    .collect::<Vec<_>>()
+                    .into_par_iter()
+            })
+            .collect::<Vec<_>>();
+
+        pb.finish();
+
+        for (matches, values) in pages {
+            self.matches.extend(matches);
+            self.values.extend(values);
+        }
+
+        self.value_size = width;
+        self.timed_out = deadline.hit();
+        self.sort_matches();
+        self.scanned = true;
+
+        deadline.check_gone()
+    }
+
+    /// Scan for specific data on a background thread.
+    ///
+    /// This works like `scan_for`, but returns immediately. The scanner, and the memory object are
+    /// moved into the background thread, and handed back by `ScanHandle::join`, alongside the
+    /// result of the scan. Progress is reported to the scanner's `Progress`, which is created if
+    /// none is set.
+    ///
+    /// # Arguments
+    ///
+    /// * `proc` - memory object to scan for values in
+    /// * `data` - data to scan or filter against
+    pub fn spawn_scan_for<T: Process + MemoryView + Clone + Send + 'static>(
+        self,
+        proc: T,
+        data: Vec<u8>,
+    ) -> ScanHandle<(Self, T, Result<()>)> {
+        let mut proc = proc;
+        let regions = self.initial_regions(&mut proc, self.process_maps_fn());
+        self.spawn_scan_for_in_regions(proc, regions, data)
+    }
+
+    /// Scan for specific data within the given memory regions on a background thread.
+    ///
+    /// This works like `spawn_scan_for`, but the first call will only go through `regions`.
+    /// Consecutive calls filter existing matches and ignore `regions`.
+    ///
+    /// # Arguments
+    ///
+    /// * `proc` - memory object to scan for values in
+    /// * `regions` - memory regions to perform the initial scan in
+    /// * `data` - data to scan or filter against
+    pub fn spawn_scan_for_in_regions<T: MemoryView + Clone + Send + 'static>(
+        mut self,
+        mut proc: T,
+        regions: Vec<MemoryRange>,
+        data: Vec<u8>,
+    ) -> ScanHandle<(Self, T, Result<()>)> {
+        let progress = self.progress.get_or_insert_with(Default::default).clone();
+
+        ScanHandle::spawn(progress, move || {
+            let ret = self.scan_for_in_regions(&mut proc, regions, &data);
+            (self, proc, ret)
+        })
+    }
+
+    /// Scan for data in a small window around an address.
+    ///
+    /// This is useful for confirming the layout of a structure, after one of its fields has been
+    /// found. The scanner state is left untouched.
+    ///
+    /// Returns offsets of the found data relative to `center`.
+    ///
+    /// # Arguments
+    ///
+    /// * `proc` - memory object to scan for values in
+    /// * `center` - address to scan around
+    /// * `radius` - maximum distance between `center` and the start of found data
+    /// * `data` - data to scan for
+    pub fn scan_near(
+        &self,
+        proc: &mut impl MemoryView,
+        center: Address,
+        radius: usize,
+        data: &[u8],
+    ) -> Result<Vec<isize>> {
+        if data.is_empty() {
+            return Err(Error::InvalidArgument("no data to scan for".into()));
+        }
+
+        let little_endian = proc.metadata().little_endian;
+        let pattern = self.case_folding.fold(data, little_endian);
+
+        let start = Address::from(center.to_umem().saturating_sub(radius as umem));
+        let len = (center - start) as usize + radius + data.len();
+
+        let mut buf = vec![0; len];
+        proc.read_raw_into(start, &mut buf).data_part()?;
+
+        Ok(buf
+            .windows(data.len())
+            .enumerate()
+            .filter(|(_, w)| self.case_folding.matches(w, &pattern, little_endian))
+            .map(|(o, _)| signed_diff(start + o, center))
+            .collect())
+    }
+
+    /// Count the locations holding specific data, without storing them.
+    ///
+    /// Before the initial scan, this goes through all memory, like `scan_for` would, but only
+    /// counts the matches. Afterwards, it counts how many of the current matches would be kept by
+    /// the next filter. Either way, the scanner state is left untouched.
+    ///
+    /// # Arguments
+    ///
+    /// * `proc` - memory object to scan for values in
+    /// * `data` - data to count
+    pub fn count_matches<T: Process + MemoryView + Clone>(
+        &mut self,
+        proc: &mut T,
+        data: &[u8],
+    ) -> Result<usize> {
+        let regions = self.initial_regions(proc, self.process_maps_fn());
+        self.count_matches_in_regions(proc, regions, data)
+    }
+
+    /// Count the locations holding specific data within the given memory regions.
+    ///
+    /// This works like `count_matches`, but before the initial scan only `regions` are counted in.
+    ///
+    /// # Arguments
+    ///
+    /// * `proc` - memory object to scan for values in
+    /// * `regions` - memory regions to count the matches in
+    /// * `data` - data to count
+    pub fn count_matches_in_regions<T: MemoryView + Clone>(
+        &mut self,
+        proc: &mut T,
+        regions: Vec<MemoryRange>,
+        data: &[u8],
+    ) -> Result<usize> {
+        if data.is_empty() {
+            return Err(Error::InvalidArgument("no data to count".into()));
+        }
+
+        let folding = self.case_folding;
+        let little_endian = proc.metadata().little_endian;
+        let pattern = folding.fold(data, little_endian);
+
+        let count = AtomicUsize::new(0);
+        let deadline = Deadline::new(self.timeout, self.cancel.as_ref());
+
+        if !self.scanned {
+            let regions = self.filter_regions(regions);
+            let align = self.alignment();
+
+            self.scan_pages(proc, &regions, data.len(), &deadline, |address, buf| {
+                let start = first_aligned(address, align);
+
+                let found = if folding == CaseFolding::Exact {
+                    let mut found = 0;
+                    find_exact(buf, &pattern, start, align, |_| found += 1);
+                    found
+                } else {
+                    buf.windows(data.len())
+                        .skip(start)
+                        .step_by(align)
+                        .filter(|buf| folding.matches(buf, &pattern, little_endian))
+                        .count()
+                };
+                count.fetch_add(found, Ordering::Relaxed);
+            });
+        } else {
+            let pb = PBar::new_filter(self.matches.len() as u64).report_to(self.progress.as_ref());
+
+            Self::read_matches(
+                proc,
+                &self.matches,
+                data.len(),
+                &deadline,
+                |_, chunk, buf| {
+                    let found = buf
+                        .into_iter()
+                        .flat_map(|buf| buf.chunks(data.len()))
+                        .filter(|buf| folding.matches(buf, &pattern, little_endian))
+                        .count();
+                    count.fetch_add(found, Ordering::Relaxed);
+                    pb.add_found(found as u64);
+                    pb.add(chunk.len() as u64);
+                },
+            );
+
+            pb.finish();
+        }
+
+        self.timed_out = deadline.hit();
+        deadline.check_gone()?;
+
+        Ok(count.into_inner())
+    }
+
+    /// Go through all pages of the given regions.
+    ///
+    /// `f` is called with the address, and contents of every page that could be read, including
+    /// `window - 1` bytes past its end, so that data crossing page boundaries can be found. If the
+    /// bytes past the end can not be read, only the page itself is passed, so that no window ends
+    /// in stale buffer contents. Pages are skipped once `deadline` expires.
+    fn scan_pages<T: MemoryView + Clone, R: Send>(
+        &self,
+        proc: &mut T,
+        regions: &[MemoryRange],
+        window: usize,
+        deadline: &Deadline,
+        f: impl Fn(Address, &[u8]) -> R + Sync,
+    ) -> Vec<R> {
+        let pb = PBar::new(
+            regions.iter().map(|CTup3(_, size, _)| *size).sum::<u64>(),
+            true,
+        )
+        .report_to(self.progress.as_ref());
+
+        let read_retries = self.read_retries;
+
+        let ctx = ThreadLocalCtx::new_locked(move || proc.clone());
+        let ctx_buf = ThreadLocalCtx::new(|| vec![0; 0x1000 + window - 1]);
+
+        let ret = regions
+            .par_iter()
+            .flat_map(|&CTup3(address, size, _)| {
+                (0..size)
+                    .step_by(0x1000)
+                    .par_bridge()
+                    .filter_map(|off| {
+                        if deadline.expired() {
+                            return None;
+                        }
+
+                        let mut mem = unsafe { ctx.get() };
+                        let mut buf = unsafe { ctx_buf.get() };
+
+                        // Read the page alongside the overlap at once, and only fall back to the
+                        // page alone, if that fails, which is usually at the end of a region.
+                        let len = if mem.read_raw_into(address + off, &mut buf).is_ok() {
+                            buf.len()
+                        } else if read_with_retries(
+                            &mut *mem,
+                            address + off,
+                            &mut buf[..0x1000],
+                            read_retries,
+                            deadline,
+                        ) {
+                            0x1000
+                        } else {
+                            return None;
+                        };
+
+                        pb.add(0x1000);
+
+                        Some(f(address + off, &buf[..len]))
+                    })
+                    .collect::<Vec<_>>()
+                    .into_par_iter()
+            })
+            .collect();
+
+        pb.finish();
+
+        ret
+    }
+
+    /// Read `size` bytes at every address in `matches`.
+    ///
+    /// `f` is called for chunks of matches, with the index of the first match in the chunk, the
+    /// chunk itself, and the values read. Once `deadline` expires, chunks are no longer read, and
+    /// `f` gets `None` in place of the values.
+    fn read_matches<T: MemoryView + Clone, R: Send>(
+        proc: &mut T,
+        matches: &[Address],
+        size: usize,
+        deadline: &Deadline,
+        f: impl Fn(usize, &[Address], Option<&[u8]>) -> R + Sync,
+    ) -> Vec<R> {
+        const CHUNK_SIZE: usize = 0x100;
+
+        let ctx = ThreadLocalCtx::new_locked(move || proc.clone());
+        let ctx_buf = ThreadLocalCtx::new(|| vec![0; CHUNK_SIZE * size]);
+
+        matches
+            .par_chunks(CHUNK_SIZE)
+            .enumerate()
+            .map(|(i, chunk)| {
+                if deadline.expired() {
+                    return f(i * CHUNK_SIZE, chunk, None);
+                }
+
+                let mut mem = unsafe { ctx.get() };
+                let mut buf = unsafe { ctx_buf.get() };
+
+                if size > 0 {
+                    let mut batcher = mem.batcher();
+
+                    for (&a, buf) in chunk.iter().zip(buf.chunks_mut(size)) {
+                        batcher.read_raw_into(a, buf);
+                    }
+
+                    if let Err(PartialError::Error(e)) = batcher.commit_rw() {
+                        deadline.note_error(&e);
+                    }
+                }
+
+                // Values read from a target that is gone are garbage, thus treated as unread.
+                if deadline.check_gone().is_err() {
+                    return f(i * CHUNK_SIZE, chunk, None);
+                }
+
+                f(i * CHUNK_SIZE, chunk, Some(&buf[..(chunk.len() * size)]))
+            })
+            .collect()
+    }
+
+    /// Keep only the matches whose value changed in a certain way since the last scan.
+    ///
+    /// The current value of every match is read and compared against the value stored during the
+    /// previous scan or filter. Values are treated as integers of the given width, and the
+    /// difference wraps around, thus the filter works for both signed and unsigned values.
+    /// Together with `scan_unknown`, this finds values that are not known at all, only how they
+    /// change.
+    ///
+    /// # Arguments
+    ///
+    /// * `proc` - memory object to read values from
+    /// * `kind` - expected change between the previous and current values
+    /// * `width` - width of the value in bytes, up to 8
+    pub fn filter_delta<T: MemoryView + Clone>(
+        &mut self,
+        proc: &mut T,
+        kind: DeltaKind,
+        width: usize,
+    ) -> Result<()> {
+        if !self.scanned {
+            return Err(Error::NotScanned);
+        }
+
+        if width == 0 || width > 8 {
+            return Err(Error::InvalidArgument(format!(
+                "delta scans support widths of 1 to 8 bytes, not {}",
+                width
+            )));
+        }
+
+        if !self.has_values(width) {
+            return Err(Error::InvalidArgument(format!(
+                "no previous {} byte values are stored",
+                width
+            )));
+        }
+
+        let little_endian = proc.metadata().little_endian;
+
+        self.refine(proc, width, |prev, cur| {
+            let prev = bytes_to_u64(prev, little_endian);
+            let cur = bytes_to_u64(cur, little_endian);
+            kind.matches(prev, cur, width)
+        })
+    }
+
+    /// Keep only the matches whose value follows a pattern over time.
+    ///
+    /// Every match is sampled `samples` times, `interval` apart, and kept if all successive
+    /// samples satisfy the predicate. This finds counters, and timers without knowing their value,
+    /// for instance, by first scanning for a changed value, and then for a monotonically
+    /// increasing one. Values are treated as integers of the given width.
+    ///
+    /// Matches that can not be read, or are not reached before the timeout, are kept. The last
+    /// samples become the stored values.
+    ///
+    /// # Arguments
+    ///
+    /// * `proc` - memory object to read values from
+    /// * `width` - width of the value in bytes, up to 8
+    /// * `samples` - number of samples to take, at least 2
+    /// * `interval` - time between samples
+    /// * `pred` - relation successive samples must have
+    pub fn scan_for_sequence<T: MemoryView + Clone>(
+        &mut self,
+        proc: &mut T,
+        width: usize,
+        samples: usize,
+        interval: Duration,
+        pred: SeqPred,
+    ) -> Result<()> {
+        if !self.scanned {
+            return Err(Error::NotScanned);
+        }
+
+        if width == 0 || width > 8 {
+            return Err(Error::InvalidArgument(format!(
+                "sequence scans support widths of 1 to 8 bytes, not {}",
+                width
+            )));
+        }
+
+        if samples < 2 {
+            return Err(Error::InvalidArgument(format!(
+                "sequence scans need at least 2 samples, not {}",
+                samples
+            )));
+        }
+
+        let little_endian = proc.metadata().little_endian;
+        let deadline = Deadline::new(self.timeout, self.cancel.as_ref());
+        let pb = PBar::new_filter((self.matches.len() * samples) as u64)
+            .report_to(self.progress.as_ref());
+
+        let mut matches = std::mem::take(&mut self.matches);
+        let mut values: Vec<Option<u64>> = vec![];
+
+        for sample in 0..samples {
+            if sample > 0 {
+                std::thread::sleep(interval);
+            }
+
+            let chunks =
+                Self::read_matches(proc, &matches, width, &deadline, |first, chunk, buf| {
+                    let prev = &values;
+                    let mut kept = vec![];
+
+                    for (i, &a) in chunk.iter().enumerate() {
+                        let prev = prev.get(first + i).copied().flatten();
+                        let cur = buf.map(|buf| {
+                            bytes_to_u64(&buf[(i * width)..((i + 1) * width)], little_endian)
+                        });
+
+                        match (prev, cur) {
+                            (Some(prev), Some(cur)) if !pred.matches(prev, cur, width) => {}
+                            (_, None) => kept.push((a, prev)),
+                            (_, cur) => kept.push((a, cur)),
+                        }
+                    }
+
+                    pb.add(chunk.len() as u64);
+
+                    kept
+                });
+
+            let (m, v) = chunks.into_iter().flatten().unzip();
+            matches = m;
+            values = v;
+        }
+
+        pb.finish();
+
+        self.values.clear();
+
+        if self.store_values() && values.iter().all(Option::is_some) {
+            for v in values.into_iter().flatten() {
+                self.values
+                    .extend_from_slice(&u64_to_bytes(v, width, little_endian));
+            }
+        }
+
+        self.matches = matches;
+        self.value_size = width;
+        self.timed_out = deadline.hit();
+
+        deadline.check_gone()
+    }
+
+    /// Keep only the matches whose value is currently zero.
+    ///
+    /// A value is zero when all of its `width` bytes are, thus a 4 byte zero is not confused with
+    /// a single zero byte followed by other data. This is useful for hunting flags and toggles.
+    ///
+    /// # Arguments
+    ///
+    /// * `proc` - memory object to read values from
+    /// * `width` - width of the value in bytes
+    pub fn filter_zero<T: MemoryView + Clone>(&mut self, proc: &mut T, width: usize) -> Result<()> {
+        self.filter_zeroness(proc, width, true)
+    }
+
+    /// Keep only the matches whose value is currently nonzero.
+    ///
+    /// A value is nonzero when any of its `width` bytes is.
+    ///
+    /// # Arguments
+    ///
+    /// * `proc` - memory object to read values from
+    /// * `width` - width of the value in bytes
+    pub fn filter_nonzero<T: MemoryView + Clone>(
+        &mut self,
+        proc: &mut T,
+        width: usize,
+    ) -> Result<()> {
+        self.filter_zeroness(proc, width, false)
+    }
+
+    fn filter_zeroness<T: MemoryView + Clone>(
+        &mut self,
+        proc: &mut T,
+        width: usize,
+        zero: bool,
+    ) -> Result<()> {
+        if !self.scanned {
+            return Err(Error::NotScanned);
+        }
+
+        if width == 0 {
+            return Err(Error::InvalidArgument("value width can not be 0".into()));
+        }
+
+        self.refine(proc, width, |_, cur| cur.iter().all(|&b| b == 0) == zero)
+    }
+
+    /// Change the width of the values of the matches.
+    ///
+    /// Every match is re-read with the new width, and the values are stored as the previous
+    /// values, so that subsequent filters compare against them. Matches are kept, even if they
+    /// turn out to be invalid with the new width.
+    ///
+    /// Returns the number of matches whose new value can not be fully read, or crosses the end of
+    /// the memory region the match was found in, and is thus likely garbage.
+    ///
+    /// # Arguments
+    ///
+    /// * `proc` - memory object to read values from
+    /// * `size` - new width of the values in bytes
+    pub fn reinterpret<T: MemoryView + Clone>(&mut self, proc: &mut T, size: usize) -> usize {
+        const CHUNK_SIZE: usize = 0x100;
+
+        let mem_map = &self.mem_map;
+        let matches = &self.matches;
+
+        // Regions are only known for matches found by the initial scan.
+        let overruns = |a: Address| {
+            let idx = mem_map.partition_point(|&CTup3(base, _, _)| base <= a);
+            match idx.checked_sub(1).map(|i| mem_map[i]) {
+                Some(CTup3(base, len, _)) if a < base + len => a + size > base + len,
+                _ => false,
+            }
+        };
+
+        let pb = PBar::new(matches.len() as u64, false).report_to(self.progress.as_ref());
+
+        let ctx = ThreadLocalCtx::new_locked(move || proc.clone());
+
+        let chunks = matches
+            .par_chunks(CHUNK_SIZE)
+            .map(|chunk| {
+                let mut mem = unsafe { ctx.get() };
+
+                let mut values = vec![0; chunk.len() * size];
+                let mut invalid = 0;
+
+                if size > 0 {
+                    for (&a, buf) in chunk.iter().zip(values.chunks_mut(size)) {
+                        if mem.read_raw_into(a, buf).is_err() || overruns(a) {
+                            invalid += 1;
+                        }
+                    }
+                }
+
+                pb.add(chunk.len() as u64);
+
+                (values, invalid)
+            })
+            .collect::<Vec<_>>();
+
+        pb.finish();
+
+        self.values.clear();
+        self.value_size = size;
+
+        let mut invalid = 0;
+
+        for (values, cnt) in chunks {
+            self.values.extend(values);
+            invalid += cnt;
+        }
+
+        invalid
+    }
+
+    /// Filter matches by their current value.
+    ///
+    /// `size` bytes are read at every match, and passed to `pred` alongside the previously stored
+    /// value. The previous value is empty if none of matching size is stored. Values of the kept
+    /// matches are stored for subsequent filters. Matches not reached before the timeout are kept
+    /// as they are, and so are the ones left when the target turns out to be gone.
+    fn refine<T: MemoryView + Clone>(
+        &mut self,
+        proc: &mut T,
+        size: usize,
+        pred: impl Fn(&[u8], &[u8]) -> bool + Sync,
+    ) -> Result<()> {
+        let old_matches = std::mem::take(&mut self.matches);
+        let old_values = std::mem::take(&mut self.values);
+        let prev_size = if self.has_values_in(&old_matches, &old_values, size) {
+            size
+        } else {
+            0
+        };
+
+        let pb = PBar::new_filter(old_matches.len() as u64).report_to(self.progress.as_ref());
+        let deadline = Deadline::new(self.timeout, self.cancel.as_ref());
+        let store_values = self.store_values();
+
+        let chunks =
+            Self::read_matches(proc, &old_matches, size, &deadline, |first, chunk, buf| {
+                let mut matches = vec![];
+                let mut values = vec![];
+
+                let buf = match buf {
+                    Some(buf) => buf,
+                    None => {
+                        // Previous values of a different size are not kept, which leaves the stored
+                        // values incomplete, and thus unused.
+                        let prev = old_values
+                            .get(first * prev_size..(first + chunk.len()) * prev_size)
+                            .unwrap_or(&[]);
+                        return (chunk.to_vec(), prev.to_vec());
+                    }
+                };
+
+                if size > 0 {
+                    for (i, (&a, cur)) in chunk.iter().zip(buf.chunks(size)).enumerate() {
+                        let idx = first + i;
+                        let prev = old_values
+                            .get(idx * prev_size..(idx + 1) * prev_size)
+                            .unwrap_or(&[]);
+
+                        if pred(prev, cur) {
+                            matches.push(a);
+                            if store_values {
+                                values.extend_from_slice(cur);
+                            }
+                        }
+                    }
+                }
+
+                pb.add_found(matches.len() as u64);
+                pb.add(chunk.len() as u64);
+
+                (matches, values)
+            });
+
+        for (matches, values) in chunks {
+            self.matches.extend(matches);
+            self.values.extend(values);
+        }
+
+        self.value_size = size;
+        self.timed_out = deadline.hit();
+
+        pb.finish();
+
+        deadline.check_gone()
+    }
+
+    /// Sort matches, alongside their values, unless insertion order is to be kept.
+    fn sort_matches(&mut self) {
+        if self.insertion_order {
+            return;
+        }
+
+        let size = self.value_size;
+
+        if size > 0 && self.has_values(size) {
+            let mut pairs = self
+                .matches
+                .iter()
+                .copied()
+                .zip(self.values.chunks(size))
+                .collect::<Vec<_>>();
+
+            pairs.par_sort_unstable_by_key(|&(a, _)| a);
+
+            let values = pairs.iter().flat_map(|&(_, v)| v).copied().collect();
+            self.matches = pairs.into_iter().map(|(a, _)| a).collect();
+            self.values = values;
+        } else {
+            self.matches.par_sort_unstable();
+        }
+    }
+
+    fn has_values(&self, size: usize) -> bool {
+        self.has_values_in(&self.matches, &self.values, size)
+    }
+
+    fn has_values_in(&self, matches: &[Address], values: &[u8], size: usize) -> bool {
+        self.value_size == size && values.len() == matches.len() * size
+    }
+
+    /// Estimate the amount of memory the initial scan would go through.
+    ///
+    /// # Arguments
+    ///
+    /// * `proc` - memory object to estimate the scan of
+    pub fn estimate<T: Process + MemoryView>(&self, proc: &mut T) -> ScanEstimate {
+        let regions = self.initial_regions(proc, self.process_maps_fn());
+        self.estimate_in_regions(regions)
+    }
+
+    /// Estimate the amount of memory the initial scan would go through within the given regions.
+    ///
+    /// This works like `estimate`, but before the initial scan only `regions` are counted in.
+    /// Afterwards, the regions of the initial scan are counted, and `regions` are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `regions` - memory regions the initial scan would go through
+    pub fn estimate_in_regions(&self, regions: Vec<MemoryRange>) -> ScanEstimate {
+        if self.scanned {
+            ScanEstimate::from_ranges(&self.mem_map)
+        } else {
+            ScanEstimate::from_ranges(&self.filter_regions(regions))
+        }
+    }
+
+    /// Get the memory regions to pass to the `_in_regions` scans.
+    ///
+    /// Before the initial scan, these are the regions listed with `maps`, as by `memory_regions`.
+    /// Afterwards, scans only filter existing matches, thus no regions are listed.
+    ///
+    /// # Arguments
+    ///
+    /// * `proc` - memory object to get the regions of
+    /// * `maps` - function to list the memory regions with
+    pub fn initial_regions<T: MemoryView>(
+        &self,
+        proc: &mut T,
+        maps: MapsFn<T>,
+    ) -> Vec<MemoryRange> {
+        if self.scanned {
+            vec![]
+        } else {
+            Self::memory_regions(proc, maps)
+        }
+    }
+
+    /// Get the memory regions mapped in both of two memory objects.
+    ///
+    /// These are the regions `diff_in_regions`, and `scan_equal_between_in_regions` compare.
+    ///
+    /// # Arguments
+    ///
+    /// * `a` - first memory object
+    /// * `maps_a` - function to list the memory regions of `a` with
+    /// * `b` - second memory object
+    /// * `maps_b` - function to list the memory regions of `b` with
+    pub fn common_regions<A: MemoryView, B: MemoryView>(
+        a: &mut A,
+        maps_a: MapsFn<A>,
+        b: &mut B,
+        maps_b: MapsFn<B>,
+    ) -> Vec<MemoryRange> {
+        intersect_regions(
+            Self::memory_regions(a, maps_a),
+            Self::memory_regions(b, maps_b),
+        )
+    }
+
+    /// Get the memory regions the initial scan would go through.
+    ///
+    /// Regions are listed within `default_scan_range`.
+    ///
+    /// # Arguments
+    ///
+    /// * `proc` - memory object to get the regions of
+    /// * `maps` - function to list the memory regions with
+    pub fn memory_regions<T: MemoryView>(proc: &mut T, maps: MapsFn<T>) -> Vec<MemoryRange> {
+        let range = default_scan_range(proc);
+        Self::memory_regions_in_range(proc, maps, range)
+    }
+
+    /// Get the memory regions within an address range the initial scan would go through.
+    ///
+    /// # Arguments
+    ///
+    /// * `proc` - memory object to get the regions of
+    /// * `maps` - function to list the memory regions with
+    /// * `range` - addresses to list the regions within
+    pub fn memory_regions_in_range<T: MemoryView>(
+        proc: &mut T,
+        maps: MapsFn<T>,
+        range: Range<Address>,
+    ) -> Vec<MemoryRange> {
+        maps(proc, mem::mb(16) as _, range.start, range.end)
+    }
+
+    /// Get the memory regions within the image of a module.
+    ///
+    /// The module name is matched case-insensitively, as in module relative addresses. The
+    /// regions are listed even if modules are skipped.
+    ///
+    /// # Arguments
+    ///
+    /// * `proc` - process to get the regions of
+    /// * `module_name` - name of the module
+    pub fn module_regions<T: Process + MemoryView>(
+        proc: &mut T,
+        module_name: &str,
+    ) -> Result<Vec<MemoryRange>> {
+        let module = proc
+            .module_list()?
+            .into_iter()
+            .find(|m| m.name.eq_ignore_ascii_case(module_name))
+            .ok_or_else(|| Error::ModuleNotFound(module_name.to_string()))?;
+
+        Ok(Self::memory_regions_in_range(
+            proc,
+            process_maps,
+            module.base..module.base + module.size,
+        ))
+    }
+
+    /// Check whether the initial scan has been performed.
+    pub fn scanned(&self) -> bool {
+        self.scanned
+    }
+
+    pub fn matches(&self) -> &Vec<Address> {
+        &self.matches
+    }
+
+    /// Check whether an address is among the matches.
+    ///
+    /// Unless insertion order is kept, this is a binary search.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - address to look for
+    pub fn contains(&self, addr: Address) -> bool {
+        self.position(addr).is_some()
+    }
+
+    /// Get the index of a match by its address.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - address to look for
+    pub fn position(&self, addr: Address) -> Option<usize> {
+        if self.insertion_order {
+            self.matches.iter().position(|&m| m == addr)
+        } else {
+            self.matches.binary_search(&addr).ok()
+        }
+    }
+
+    /// Get mutable access to the matches.
+    ///
+    /// Since matches can be arbitrarily changed, this discards the stored previous values. Use
+    /// `add_match` and `remove_match` to keep them. Unless insertion order is kept, the matches
+    /// must be left sorted.
+    pub fn matches_mut(&mut self) -> &mut Vec<Address> {
+        self.values.clear();
+        &mut self.matches
+    }
+
+    /// Get the value a match had during the last scan or filter.
+    ///
+    /// # Arguments
+    ///
+    /// * `idx` - index of the match
+    pub fn previous_value(&self, idx: usize) -> Option<&[u8]> {
+        if self.has_values(self.value_size) {
+            self.values
+                .get(idx * self.value_size..(idx + 1) * self.value_size)
+        } else {
+            None
+        }
+    }
+
+    /// Iterate over the matches, alongside the values they had during the last scan or filter.
+    ///
+    /// Values are `None` if they are not stored, see `set_store_values`.
+    pub fn matches_with_values(&self) -> impl Iterator<Item = (Address, Option<&[u8]>)> + '_ {
+        self.matches
+            .iter()
+            .enumerate()
+            .map(move |(idx, &addr)| (addr, self.previous_value(idx)))
+    }
+
+    /// Manually add a match.
+    ///
+    /// The current value at the address is stored as its previous value. Unless insertion order
+    /// is kept, the match is inserted at its sorted position, and addresses that already are
+    /// matches are not added again.
+    ///
+    /// Returns the index of the match.
+    ///
+    /// # Arguments
+    ///
+    /// * `proc` - memory object to read the value from
+    /// * `addr` - address to add
+    pub fn add_match(&mut self, proc: &mut impl MemoryView, addr: Address) -> usize {
+        let idx = if self.insertion_order {
+            self.matches.len()
+        } else {
+            match self.matches.binary_search(&addr) {
+                Ok(idx) => return idx,
+                Err(idx) => idx,
+            }
+        };
+
+        if self.has_values(self.value_size) {
+            let mut buf = vec![0; self.value_size];
+            proc.read_raw_into(addr, &mut buf).data_part().ok();
+            let off = idx * self.value_size;
+            self.values.splice(off..off, buf);
+        }
+
+        self.matches.insert(idx, addr);
+
+        idx
+    }
+
+    /// Remove a match by its index.
+    ///
+    /// # Arguments
+    ///
+    /// * `idx` - index of the match to remove
+    pub fn remove_match(&mut self, idx: usize) -> Result<Address> {
+        if idx >= self.matches.len() {
+            return Err(Error::InvalidIndex(idx));
+        }
+
+        if self.has_values(self.value_size) {
+            self.values
+                .drain(idx * self.value_size..(idx + 1) * self.value_size);
+        }
+
+        Ok(self.matches.remove(idx))
+    }
+
+    /// Add matches from a file of addresses.
+    ///
+    /// Every line holds an address, either in hex, with or without the `0x` prefix, or relative
+    /// to a module, such as `game.exe+1f00`. Lines may be CSV records, in which case the address is
+    /// taken from the first field. Empty lines, and lines starting with `#` are skipped.
+    ///
+    /// The matches are added as with `add_match`, and are subsequently filtered like scan results.
+    ///
+    /// Returns the number of addresses read.
+    ///
+    /// # Arguments
+    ///
+    /// * `proc` - process to resolve modules of, and to read the values from
+    /// * `path` - path of the file to read
+    pub fn import_matches<T: Process + MemoryView>(
+        &mut self,
+        proc: &mut T,
+        path: impl AsRef<Path>,
+    ) -> Result<usize> {
+        let modules = proc.module_list()?;
+        self.import_matches_with_modules(proc, &modules, path)
+    }
+
+    /// Add matches from a file of addresses.
+    ///
+    /// This works like `import_matches`, but resolves module relative addresses with `modules`.
+    ///
+    /// # Arguments
+    ///
+    /// * `mem` - memory object to read the values from
+    /// * `modules` - modules to resolve module relative addresses with
+    /// * `path` - path of the file to read
+    pub fn import_matches_with_modules(
+        &mut self,
+        mem: &mut impl MemoryView,
+        modules: &[ModuleInfo],
+        path: impl AsRef<Path>,
+    ) -> Result<usize> {
+        let text = std::fs::read_to_string(path)?;
+
+        let addrs = text
+            .lines()
+            .enumerate()
+            .filter_map(|(i, line)| {
+                let field = line.split(',').next().unwrap_or("").trim();
+
+                if field.is_empty() || field.starts_with('#') {
+                    return None;
+                }
+
+                Some(
+                    parse_address(field, modules)
+                        .map_err(|e| Error::InvalidFormat(format!("line {}: {}", i + 1, e))),
+                )
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        for &addr in &addrs {
+            self.add_match(mem, addr);
+        }
+
+        self.scanned = true;
+
+        Ok(addrs.len())
+    }
+
+    /// Save the matches to a file.
+    ///
+    /// Stored values, and the memory regions of the initial scan are saved as well, so that
+    /// loaded matches can be filtered just like fresh ones. Settings are not saved.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - path of the file to write
+    pub fn save_matches(&self, path: impl AsRef<Path>) -> Result<()> {
+        let mut w = Writer::create(path, FILE_MAGIC, FILE_VERSION)?;
+
+        w.u64(self.mem_map.len() as u64)?;
+        for &CTup3(base, size, page_type) in &self.mem_map {
+            w.u64(base.to_umem())?;
+            w.u64(size)?;
+            w.u64(page_type.bits() as u64)?;
+        }
+
+        w.u64(self.matches.len() as u64)?;
+        for &addr in &self.matches {
+            w.u64(addr.to_umem())?;
+        }
+
+        w.u64(self.value_size as u64)?;
+        w.bytes(&self.values)?;
+        w.u64(self.timed_out as u64)?;
+
+        w.finish()
+    }
+
+    /// Load matches previously saved with `save_matches`, replacing the current ones.
+    ///
+    /// The scanner is marked as scanned, thus subsequent scans filter the loaded matches. Files
+    /// that are empty, corrupt, or of another version are rejected with `Error::InvalidFormat`,
+    /// leaving the current matches untouched.
+    ///
+    /// Returns the number of matches loaded.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - path of the file to read
+    pub fn load_matches(&mut self, path: impl AsRef<Path>) -> Result<usize> {
+        let mut r = Reader::open(path, FILE_MAGIC, FILE_VERSION)?;
+
+        let mut mem_map = vec![];
+
+        for _ in 0..r.u64()? {
+            let base = Address::from(r.u64()?);
+            let size = r.u64()?;
+            let page_type = PageType::from_bits_truncate(r.u64()? as u8);
+            mem_map.push(CTup3(base, size, page_type));
+        }
+
+        let mut matches = vec![];
+
+        for _ in 0..r.u64()? {
+            matches.push(Address::from(r.u64()?));
+        }
+
+        let value_size = r.u64()? as usize;
+        let values = r.bytes()?;
+        let timed_out = r.u64()? != 0;
+
+        if !values.is_empty() && Some(values.len()) != matches.len().checked_mul(value_size) {
+            return Err(Error::InvalidFormat(format!(
+                "{} bytes of values do not fit {} matches of {} bytes",
+                values.len(),
+                matches.len(),
+                value_size
+            )));
+        }
+
+        self.reset();
+        self.mem_map = mem_map;
+        self.matches = matches;
+        self.value_size = value_size;
+        self.timed_out = timed_out;
+        self.scanned = true;
+
+        if self.store_values() {
+            self.values = values;
+        }
+
+        Ok(self.matches.len())
+    }
+
+    /// Combine the matches with the ones of another scanner.
+    ///
+    /// This allows to compose scans, for instance, to keep the addresses that matched one value
+    /// in an earlier scan, and another value now. Stored values are kept for the matches that
+    /// have them, unless the scanners store values of different sizes. Merged matches are sorted,
+    /// unless insertion order is kept, in which case matches of `other` are appended.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - scanner to combine the matches with
+    /// * `op` - how to combine the matches
+    pub fn merge(&mut self, other: &ValueScanner, op: MergeOp) {
+        // Without a scan of its own, the union is the other scanner's matches, values included.
+        if !self.scanned && op == MergeOp::Union {
+            self.value_size = other.value_size;
+        }
+
+        let size = self.value_size;
+        let keep_values = self.has_values(size)
+            && (op != MergeOp::Union || (other.value_size == size && other.has_values(size)));
+
+        let mut pairs = self
+            .matches_with_values()
+            .map(|(a, v)| (a, v.unwrap_or(&[]).to_vec()))
+            .collect::<Vec<_>>();
+
+        let theirs = other.matches.iter().copied().collect::<BTreeSet<_>>();
+
+        match op {
+            MergeOp::Union => {
+                let ours = self.matches.iter().copied().collect::<BTreeSet<_>>();
+                pairs.extend(
+                    other
+                        .matches_with_values()
+                        .filter(|(a, _)| !ours.contains(a))
+                        .map(|(a, v)| (a, v.unwrap_or(&[]).to_vec())),
+                );
+            }
+            MergeOp::Intersection => pairs.retain(|(a, _)| theirs.contains(a)),
+            MergeOp::Difference => pairs.retain(|(a, _)| !theirs.contains(a)),
+        }
+
+        self.matches = pairs.iter().map(|&(a, _)| a).collect();
+        self.values = if keep_values {
+            pairs.into_iter().flat_map(|(_, v)| v).collect()
+        } else {
+            vec![]
+        };
+
+        self.scanned |= other.scanned;
+        self.timed_out |= other.timed_out;
+        self.sort_matches();
+    }
+}
+
+/// Wall-clock limit of a single operation.
+///
+/// Every unit of work checks the deadline before it starts, and records whether it was hit. A
+/// cancelled token expires the deadline immediately. So does a read failure indicating that the
+/// target is gone, which is not a timeout, but stops the operation all the same.
+pub(crate) struct Deadline {
+    at: Option<Instant>,
+    cancel: Option<CancelToken>,
+    hit: AtomicBool,
+    gone: AtomicBool,
+}
+
+impl Deadline {
+    /// Start a deadline that expires `timeout` from now, or never, unless cancelled earlier.
+    pub(crate) fn new(timeout: Option<Duration>, cancel: Option<&CancelToken>) -> Self {
+        Self {
+            at: timeout.map(|t| Instant::now() + t),
+            cancel: cancel.cloned(),
+            hit: AtomicBool::new(false),
+            gone: AtomicBool::new(false),
+        }
+    }
+
+    /// Check whether the deadline expired, and if so, remember it.
+    pub(crate) fn expired(&self) -> bool {
+        if self.gone.load(Ordering::Relaxed) {
+            return true;
+        }
+
+        let expired = self.at.is_some_and(|at| Instant::now() >= at)
+            || self.cancel.as_ref().is_some_and(CancelToken::is_cancelled);
+
+        if expired {
+            self.hit.store(true, Ordering::Relaxed);
+        }
+
+        expired
+    }
+
+    /// Check whether any unit of work was skipped due to the deadline.
+    pub(crate) fn hit(&self) -> bool {
+        self.hit.load(Ordering::Relaxed)
+    }
+
+    /// Remember a read error, if it indicates that the target is gone.
+    ///
+    /// Reads of unmapped memory fail routinely, and are not of concern here.
+    pub(crate) fn note_error(&self, e: &memflow::error::Error) {
+        if matches!(e.1, ErrorKind::ProcessNotFound | ErrorKind::TargetNotFound) {
+            self.gone.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Fail with `Error::ProcessGone` if a read indicated that the target is gone.
+    pub(crate) fn check_gone(&self) -> Result<()> {
+        if self.gone.load(Ordering::Relaxed) {
+            Err(Error::ProcessGone)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Keep the regions within the given size limits.
+pub(crate) fn filter_region_sizes(
+    regions: &mut Vec<MemoryRange>,
+    min: Option<umem>,
+    max: Option<umem>,
+) {
+    regions.retain(|&CTup3(_, size, _)| {
+        min.map_or(true, |min| size >= min) && max.map_or(true, |max| size <= max)
+    });
+}
+
+/// Intersect two lists of memory regions.
+///
+/// Page types of the resulting regions are taken from `a`.
+fn intersect_regions(mut a: Vec<MemoryRange>, mut b: Vec<MemoryRange>) -> Vec<MemoryRange> {
+    a.sort_by_key(|&CTup3(addr, _, _)| addr);
+    b.sort_by_key(|&CTup3(addr, _, _)| addr);
+
+    let mut out = vec![];
+    let (mut ia, mut ib) = (0, 0);
+
+    while ia < a.len() && ib < b.len() {
+        let CTup3(base_a, size_a, page_type) = a[ia];
+        let CTup3(base_b, size_b, _) = b[ib];
+        let (end_a, end_b) = (base_a + size_a, base_b + size_b);
+
+        let lower = std::cmp::max(base_a, base_b);
+        let upper = std::cmp::min(end_a, end_b);
+
+        if lower < upper {
+            out.push(CTup3(lower, (upper - lower) as umem, page_type));
+        }
+
+        if end_a < end_b {
+            ia += 1;
+        } else {
+            ib += 1;
+        }
+    }
+
+    out
+}
+
+/// Read memory, retrying failed reads.
+///
+/// Both failed, and partially failed reads are retried. If the retries are exhausted, a partial
+/// read is accepted like it would be without retries. Returns whether the read succeeded.
+///
+/// # Arguments
+///
+/// * `mem` - memory object to read from
+/// * `addr` - address to read at
+/// * `buf` - buffer to read into
+/// * `retries` - how many times to retry the read after the first failure
+pub(crate) fn read_with_retries(
+    mem: &mut impl MemoryView,
+    addr: Address,
+    buf: &mut [u8],
+    retries: u32,
+    deadline: &Deadline,
+) -> bool {
+    let mut ret = mem.read_raw_into(addr, buf);
+
+    for attempt in 1..=retries {
+        if ret.is_ok() {
+            return true;
+        }
+
+        std::thread::sleep(std::time::Duration::from_millis(attempt as u64));
+        ret = mem.read_raw_into(addr, buf);
+    }
+
+    match ret {
+        Ok(()) => true,
+        Err(PartialError::Error(e)) => {
+            deadline.note_error(&e);
+            if retries > 0 {
+                log::warn!("read at {:x} failed after {} retries: {}", addr, retries, e);
+            }
+            false
+        }
+        Err(_) => {
+            if retries > 0 {
+                log::debug!("read at {:x} was partial after {} retries", addr, retries);
+            }
+            true
+        }
+    }
+}
+
+/// Parse a hex address, optionally relative to a module, such as `game.exe+1f00`.
+fn parse_address(input: &str, modules: &[ModuleInfo]) -> std::result::Result<Address, String> {
+    let parse_hex = |hex: &str| {
+        let hex = hex.trim();
+        let digits = hex
+            .strip_prefix("0x")
+            .or_else(|| hex.strip_prefix("0X"))
+            .unwrap_or(hex);
+        umem::from_str_radix(digits, 16).map_err(|_| format!("`{}` is not a hex number", hex))
+    };
+
+    match input.rsplit_once('+') {
+        Some((module, off)) => {
+            let module = module.trim();
+            let base = modules
+                .iter()
+                .find(|m| m.name.eq_ignore_ascii_case(module))
+                .ok_or_else(|| format!("module `{}` not found", module))?
+                .base;
+            Ok(base + parse_hex(off)?)
+        }
+        None => parse_hex(input).map(Address::from),
+    }
+}
+
+/// Get the float matching mode for a tolerance, rejecting negative, and NaN ones.
+fn float_near(epsilon: f64) -> Result<FloatMatch> {
+    if epsilon >= 0.0 {
+        Ok(FloatMatch::Epsilon(epsilon))
+    } else {
+        Err(Error::InvalidArgument(format!(
+            "`{}` is not a valid tolerance",
+            epsilon
+        )))
+    }
+}
+
+/// Get the offset of the first address at, or after `address` that is a multiple of `align`.
+fn first_aligned(address: Address, align: usize) -> usize {
+    let rem = (address.to_umem() % align as umem) as usize;
+    (align - rem) % align
+}
+
+/// Call `f` with the offset of every occurrence of `pattern` in `buf`, overlapping ones included.
+///
+/// Only offsets `start + n * align` are considered. This is the hot loop of full memory scans.
+/// Patterns of common integer widths are compared as integers, and other ones are located by their
+/// first byte with `memchr`, which uses SIMD, before the rest is compared.
+fn find_exact(buf: &[u8], pattern: &[u8], start: usize, align: usize, mut f: impl FnMut(usize)) {
+    macro_rules! find_int {
+        ($ty:ty) => {{
+            const N: usize = std::mem::size_of::<$ty>();
+            let pattern = <$ty>::from_ne_bytes(pattern.try_into().unwrap());
+            if align == 1 {
+                for (o, w) in buf.windows(N).enumerate() {
+                    if <$ty>::from_ne_bytes(w.try_into().unwrap()) == pattern {
+                        f(o);
+                    }
+                }
+            } else {
+                let last = buf.len().saturating_sub(N - 1);
+                for o in (start..last).step_by(align) {
+                    if <$ty>::from_ne_bytes(buf[o..(o + N)].try_into().unwrap()) == pattern {
+                        f(o);
+                    }
+                }
+            }
+        }};
+    }
+
+    let aligned = |o: &usize| *o >= start && (*o - start) % align == 0;
+
+    match pattern.len() {
+        0 => {}
+        1 => memchr::memchr_iter(pattern[0], buf)
+            .filter(aligned)
+            .for_each(f),
+        2 => find_int!(u16),
+        4 => find_int!(u32),
+        8 => find_int!(u64),
+        len => {
+            let last = buf.len().saturating_sub(len - 1);
+            for o in memchr::memchr_iter(pattern[0], &buf[..last]).filter(aligned) {
+                if buf[o..(o + len)] == *pattern {
+                    f(o);
+                }
+            }
+        }
+    }
+}
+
+/// Call `f` with the offset of every occurrence of a pattern with wildcards in `buf`.
+///
+/// Occurrences are located by the first byte of the pattern that is not a wildcard with `memchr`,
+/// and only the remaining bytes that are not wildcards are compared.
+fn find_masked(buf: &[u8], pattern: &[Option<u8>], mut f: impl FnMut(usize)) {
+    let mut fixed = pattern
+        .iter()
+        .enumerate()
+        .filter_map(|(i, b)| Some((i, (*b)?)));
+
+    let (anchor, anchor_byte) = match fixed.next() {
+        Some(first) => first,
+        None => return,
+    };
+
+    let rest = fixed.collect::<Vec<_>>();
+    let last = buf.len().saturating_sub(pattern.len() - 1);
+
+    if last == 0 {
+        return;
+    }
+
+    for o in memchr::memchr_iter(anchor_byte, &buf[anchor..(anchor + last)]) {
+        if rest.iter().all(|&(i, b)| buf[o + i] == b) {
+            f(o);
+        }
+    }
+}
+
+/// Check whether bytes match a pattern with wildcards of the same length.
+fn masked_eq(buf: &[u8], pattern: &[Option<u8>]) -> bool {
+    buf.len() == pattern.len()
+        && buf
+            .iter()
+            .zip(pattern)
+            .all(|(&b, p)| p.map_or(true, |p| p == b))
+}
+
+/// Read an unsigned integer of up to 8 bytes in the given byte order.
+pub(crate) fn bytes_to_u64(buf: &[u8], little_endian: bool) -> u64 {
+    let mut arr = [0; 8];
+
+    if little_endian {
+        arr[..buf.len()].copy_from_slice(buf);
+        u64::from_le_bytes(arr)
+    } else {
+        arr[(8 - buf.len())..].copy_from_slice(buf);
+        u64::from_be_bytes(arr)
+    }
+}
+
+fn bytes_to_u128(buf: &[u8], little_endian: bool) -> u128 {
+    let mut arr = [0; 16];
+
+    if little_endian {
+        arr[..buf.len()].copy_from_slice(buf);
+        u128::from_le_bytes(arr)
+    } else {
+        arr[(16 - buf.len())..].copy_from_slice(buf);
+        u128::from_be_bytes(arr)
+    }
+}
+
+fn u64_to_bytes(v: u64, width: usize, little_endian: bool) -> Vec<u8> {
+    if little_endian {
+        v.to_le_bytes()[..width].to_vec()
+    } else {
+        v.to_be_bytes()[(8 - width)..].to_vec()
+    }
+}
+
+/// Read an integer of up to 16 bytes, returning `None` if it does not fit into an `i128`.
+fn bytes_to_i128(buf: &[u8], little_endian: bool, signed: bool) -> Option<i128> {
+    let msb = if little_endian {
+        buf.last()
+    } else {
+        buf.first()
+    };
+    let negative = signed && msb.is_some_and(|&b| b & 0x80 != 0);
+
+    // Sign extend negative values, by filling the unused bytes with ones.
+    let mut arr = [if negative { 0xff } else { 0 }; 16];
+
+    let v = if little_endian {
+        arr[..buf.len()].copy_from_slice(buf);
+        u128::from_le_bytes(arr)
+    } else {
+        arr[(16 - buf.len())..].copy_from_slice(buf);
+        u128::from_be_bytes(arr)
+    };
+
+    if negative {
+        Some(v as i128)
+    } else {
+        v.try_into().ok()
+    }
+}
+
+fn bytes_to_f64(buf: &[u8], little_endian: bool) -> f64 {
+    let bits = bytes_to_u64(buf, little_endian);
+
+    if buf.len() == 4 {
+        f32::from_bits(bits as u32) as f64
+    } else {
+        f64::from_bits(bits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{alphabet_bytes, DummyView};
+
+    const BASE: u64 = 0x10000;
+
+    #[test]
+    fn scan_for_finds_offsets() {
+        let mut data = vec![0u8; 0x3000];
+        data[0x10..0x14].copy_from_slice(&1234u32.to_le_bytes());
+        data[0xffe..0x1002].copy_from_slice(&1234u32.to_le_bytes());
+        data[0x2ffc..].copy_from_slice(&1234u32.to_le_bytes());
+
+        let mut view = DummyView::new(BASE.into(), data);
+        let mut scanner = ValueScanner::default();
+
+        scanner.scan_for(&mut view, &1234u32.to_le_bytes()).unwrap();
+
+        assert_eq!(
+            scanner.matches(),
+            &[BASE + 0x10, BASE + 0xffe, BASE + 0x2ffc]
+                .iter()
+                .map(|&a| Address::from(a))
+                .collect::<Vec<_>>()
+        );
+
+        view.write_raw(Address::from(BASE + 0xffe), &5678u32.to_le_bytes())
+            .unwrap();
+        scanner.scan_for(&mut view, &1234u32.to_le_bytes()).unwrap();
+
+        assert_eq!(
+            scanner.matches(),
+            &vec![Address::from(BASE + 0x10), Address::from(BASE + 0x2ffc)]
+        );
+    }
+
+    #[test]
+    fn scan_with_rejects_widths_crossing_pages() {
+        let mut view = DummyView::new(BASE.into(), vec![0u8; 0x2000]);
+        let mut scanner = ValueScanner::default();
+
+        for width in [0, 3, 12, 0x2000] {
+            assert!(scanner.scan_with(&mut view, width, |_| true).is_err());
+        }
+
+        scanner.set_store_values(true);
+        assert!(scanner.scan_unknown(&mut view, 6).is_err());
+        scanner.scan_unknown(&mut view, 8).unwrap();
+        assert_eq!(scanner.matches().len(), 0x2000 / 8);
+    }
+
+    #[test]
+    fn scan_for_parses_in_target_byte_order() {
+        let mut data = vec![0u8; 0x1000];
+        data[0x20..0x24].copy_from_slice(&1234u32.to_be_bytes());
+        data[0x40..0x44].copy_from_slice(&1234u32.to_le_bytes());
+
+        let mut view = DummyView::new(BASE.into(), data);
+        view.set_little_endian(false);
+
+        let value = parse_value("1234", "u32", view.metadata().little_endian).unwrap();
+
+        let mut scanner = ValueScanner::default();
+        scanner.scan_for(&mut view, &value).unwrap();
+
+        assert_eq!(scanner.matches(), &vec![Address::from(BASE + 0x20)]);
+    }
+
+    #[test]
+    fn case_insensitive_string_scans() {
+        let mut data = vec![0u8; 0x1000];
+
+        for (off, s) in [
+            (0x10, "Player"),
+            (0x20, "PLAYER"),
+            (0x30, "player"),
+            (0x40, "plaxer"),
+        ] {
+            data[off..(off + s.len())].copy_from_slice(s.as_bytes());
+        }
+
+        for (off, s) in [(0x100, "ÄbC"), (0x120, "äbc"), (0x140, "abc")] {
+            let units = s
+                .encode_utf16()
+                .flat_map(u16::to_le_bytes)
+                .collect::<Vec<_>>();
+            data[off..(off + units.len())].copy_from_slice(&units);
+        }
+
+        let mut view = DummyView::new(BASE.into(), data);
+
+        let mut scanner = ValueScanner::default();
+        scanner.set_case_folding(CaseFolding::Ascii);
+        scanner.scan_for(&mut view, b"player").unwrap();
+
+        assert_eq!(
+            scanner.matches(),
+            &[0x10, 0x20, 0x30]
+                .iter()
+                .map(|&off| Address::from(BASE + off))
+                .collect::<Vec<_>>()
+        );
+
+        let pattern = "äBc"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect::<Vec<_>>();
+
+        let mut scanner = ValueScanner::default();
+        scanner.set_case_folding(CaseFolding::Utf16);
+        scanner.scan_for(&mut view, &pattern).unwrap();
+
+        assert_eq!(
+            scanner.matches(),
+            &vec![Address::from(BASE + 0x100), Address::from(BASE + 0x120)]
+        );
+    }
+
+    #[test]
+    fn odd_length_utf16_folding() {
+        let folding = CaseFolding::Utf16;
+        let pattern = folding.fold(&[b'A', 0, b'B'], true);
+
+        assert_eq!(pattern, vec![b'a', 0, b'B']);
+        assert!(folding.matches(&[b'a', 0, b'B'], &pattern, true));
+        assert!(!folding.matches(&[b'a', 0, b'b'], &pattern, true));
+    }
+
+    #[test]
+    fn find_exact_matches_slow_path() {
+        let buf = alphabet_bytes(0x2545_f491, 0x1000, &[0, 1, 0xff]);
+
+        for len in 1..=9 {
+            for pos in [0, 0x101, 0x7fe, buf.len() - len] {
+                let pattern = &buf[pos..(pos + len)];
+
+                for align in [1, 2, 4, 8] {
+                    for start in 0..align {
+                        let expected = buf
+                            .windows(len)
+                            .enumerate()
+                            .filter(|&(o, w)| {
+                                o >= start && (o - start) % align == 0 && w == pattern
+                            })
+                            .map(|(o, _)| o)
+                            .collect::<Vec<_>>();
+
+                        let mut found = vec![];
+                        find_exact(&buf, pattern, start, align, |o| found.push(o));
+                        assert_eq!(
+                            found, expected,
+                            "len {} align {} start {}",
+                            len, align, start
+                        );
+                    }
+                }
+
+                // A pattern of only wildcards never matches.
+                if len == 1 {
+                    continue;
+                }
+
+                let mut masked = pattern.iter().copied().map(Some).collect::<Vec<_>>();
+                masked[len / 2] = None;
+
+                let expected = buf
+                    .windows(len)
+                    .enumerate()
+                    .filter(|(_, w)| masked_eq(w, &masked))
+                    .map(|(o, _)| o)
+                    .collect::<Vec<_>>();
+
+                let mut found = vec![];
+                find_masked(&buf, &masked, |o| found.push(o));
+                assert_eq!(found, expected, "masked len {}", len);
+            }
+        }
+    }
+
+    /// Compare full scans against the naive `windows` search they replaced.
+    ///
+    /// Run with `cargo test --release -- --ignored --nocapture bench_scan_for`.
+    #[test]
+    #[ignore]
+    fn bench_scan_for_against_naive_search() {
+        let data = alphabet_bytes(0x2545_f491, 64 << 20, &[0, 1, 2, 3, 0xff]);
+        let mut view = DummyView::new(BASE.into(), data.clone());
+
+        for len in [1, 4, 6, 8, 12] {
+            let pattern = &data[0x1234..(0x1234 + len)];
+
+            let start = Instant::now();
+            let naive = data
+                .windows(len)
+                .enumerate()
+                .filter(|(_, w)| w == &pattern)
+                .map(|(o, _)| Address::from(BASE + o as u64))
+                .collect::<Vec<_>>();
+            let naive_time = start.elapsed();
+
+            let start = Instant::now();
+            let mut scanner = ValueScanner::default();
+            scanner.scan_for(&mut view, pattern).unwrap();
+            let scan_time = start.elapsed();
+
+            assert_eq!(scanner.matches(), &naive, "len {}", len);
+            println!(
+                "len {:>2}: {:>8} matches, naive {:?}, scan_for {:?}",
+                len,
+                naive.len(),
+                naive_time,
+                scan_time
+            );
+        }
     }
 }
@@ -0,0 +1,480 @@
+//! Value types that can be scanned for, and written.
+//!
+//! Every type is known by a short name, such as `i32`, or `str`, and converts values between
+//! strings, and their in-memory representation.
+
+use crate::error::{Error, Result};
+use std::convert::TryInto;
+
+pub type PrintFn = fn(&[u8]) -> Option<String>;
+pub type ParseFn = fn(&str) -> Option<Box<[u8]>>;
+
+/// Type of a value in memory.
+///
+/// The fields are the name of the type, its size in bytes, if it is fixed, and the functions to
+/// print a value of the type, and to parse one from a string. Values are in native byte order,
+/// `convert_byte_order` converts them to, and from that of the target.
+pub struct ValueType(
+    pub &'static str,
+    pub Option<usize>,
+    pub PrintFn,
+    pub ParseFn,
+);
+
+impl ValueType {
+    /// Find a type by its name.
+    ///
+    /// # Arguments
+    /// * `typename` - name of the type
+    pub fn find(typename: &str) -> Result<&'static ValueType> {
+        TYPES
+            .iter()
+            .find(|ValueType(name, _, _, _)| *name == typename)
+            .ok_or_else(|| Error::UnknownType(typename.into()))
+    }
+
+    /// Parse a value of this type.
+    ///
+    /// Numbers that do not fit an integer type are reported as out of range, rather than invalid.
+    ///
+    /// # Arguments
+    /// * `value` - string to parse
+    pub fn parse(&self, value: &str) -> Result<Box<[u8]>> {
+        let ValueType(typename, _, _, parse) = self;
+
+        parse(value).ok_or_else(|| {
+            let is_number = value.parse::<i128>().is_ok() || value.parse::<u128>().is_ok();
+
+            match int_range(typename) {
+                Some((min, max)) if is_number => Error::ValueOutOfRange {
+                    typename: (*typename).into(),
+                    value: value.into(),
+                    min,
+                    max,
+                },
+                _ => Error::InvalidValue {
+                    typename: (*typename).into(),
+                    value: value.into(),
+                },
+            }
+        })
+    }
+
+    /// Print a value of this type.
+    ///
+    /// Returns `None` if the buffer does not hold a valid value.
+    ///
+    /// # Arguments
+    /// * `buf` - bytes of the value
+    pub fn print(&self, buf: &[u8]) -> Option<String> {
+        (self.2)(buf)
+    }
+
+    /// Convert a value of this type between native byte order, and the given one.
+    ///
+    /// Numbers are reversed as a whole, and UTF-16 strings per code unit, while strings, and byte
+    /// patterns are left as they are. Converting twice gives back the original bytes.
+    ///
+    /// # Arguments
+    /// * `buf` - bytes of the value
+    /// * `little_endian` - byte order to convert from, or to
+    pub fn convert_byte_order(&self, buf: &mut [u8], little_endian: bool) {
+        if little_endian == cfg!(target_endian = "little") {
+            return;
+        }
+
+        match self.0 {
+            "str" | "bytes" | "v128" | "v256" => {}
+            "str_utf16" => buf.chunks_exact_mut(2).for_each(<[u8]>::reverse),
+            _ => buf.reverse(),
+        }
+    }
+}
+
+/// All supported value types.
+pub const TYPES: &[ValueType] = &[
+    ValueType(
+        "str",
+        None,
+        |buf| Some(String::from_utf8_lossy(buf).to_string()),
+        |value| Some(Box::from(value.as_bytes())),
+    ),
+    ValueType(
+        "str_utf16",
+        None,
+        |buf| {
+            let units = buf
+                .chunks_exact(2)
+                .map(|w| u16::from_ne_bytes([w[0], w[1]]));
+
+            // Unpaired surrogates, and a trailing odd byte are shown as replacement characters,
+            // nulls are escaped, so that they do not disappear from the output.
+            let mut out = String::new();
+
+            for c in char::decode_utf16(units) {
+                match c.unwrap_or(char::REPLACEMENT_CHARACTER) {
+                    '\0' => out.push_str("\\0"),
+                    c => out.push(c),
+                }
+            }
+
+            if buf.len() % 2 != 0 {
+                out.push(char::REPLACEMENT_CHARACTER);
+            }
+
+            Some(out)
+        },
+        |value| {
+            let mut out = vec![];
+            for v in value.encode_utf16() {
+                out.extend(v.to_ne_bytes().iter().copied());
+            }
+            Some(out.into_boxed_slice())
+        },
+    ),
+    ValueType(
+        "bytes",
+        None,
+        |buf| Some(print_hex_bytes(buf)),
+        parse_hex_bytes,
+    ),
+    ValueType(
+        "v256",
+        Some(32),
+        |buf| Some(print_hex_bytes(buf)).filter(|_| buf.len() == 32),
+        |value| parse_hex_bytes(value).filter(|b| b.len() == 32),
+    ),
+    ValueType(
+        "v128",
+        Some(16),
+        |buf| Some(print_hex_bytes(buf)).filter(|_| buf.len() == 16),
+        |value| parse_hex_bytes(value).filter(|b| b.len() == 16),
+    ),
+    ValueType("u256", Some(32), print_u256, parse_u256),
+    ValueType(
+        "i128",
+        Some(16),
+        |buf| Some(format!("{}", i128::from_ne_bytes(buf.try_into().ok()?))),
+        |value| Some(Box::from(value.parse::<i128>().ok()?.to_ne_bytes())),
+    ),
+    ValueType(
+        "i64",
+        Some(8),
+        |buf| Some(format!("{}", i64::from_ne_bytes(buf.try_into().ok()?))),
+        |value| Some(Box::from(value.parse::<i64>().ok()?.to_ne_bytes())),
+    ),
+    ValueType(
+        "i32",
+        Some(4),
+        |buf| Some(format!("{}", i32::from_ne_bytes(buf.try_into().ok()?))),
+        |value| Some(Box::from(value.parse::<i32>().ok()?.to_ne_bytes())),
+    ),
+    ValueType(
+        "i16",
+        Some(2),
+        |buf| Some(format!("{}", i16::from_ne_bytes(buf.try_into().ok()?))),
+        |value| Some(Box::from(value.parse::<i16>().ok()?.to_ne_bytes())),
+    ),
+    ValueType(
+        "i8",
+        Some(1),
+        |buf| Some(format!("{}", i8::from_ne_bytes(buf.try_into().ok()?))),
+        |value| Some(Box::from(value.parse::<i8>().ok()?.to_ne_bytes())),
+    ),
+    ValueType(
+        "u128",
+        Some(16),
+        |buf| Some(format!("{}", u128::from_ne_bytes(buf.try_into().ok()?))),
+        |value| Some(Box::from(value.parse::<u128>().ok()?.to_ne_bytes())),
+    ),
+    ValueType(
+        "u64",
+        Some(8),
+        |buf| Some(format!("{}", u64::from_ne_bytes(buf.try_into().ok()?))),
+        |value| Some(Box::from(value.parse::<u64>().ok()?.to_ne_bytes())),
+    ),
+    ValueType(
+        "u32",
+        Some(4),
+        |buf| Some(format!("{}", u32::from_ne_bytes(buf.try_into().ok()?))),
+        |value| Some(Box::from(value.parse::<u32>().ok()?.to_ne_bytes())),
+    ),
+    ValueType(
+        "u16",
+        Some(2),
+        |buf| Some(format!("{}", u16::from_ne_bytes(buf.try_into().ok()?))),
+        |value| Some(Box::from(value.parse::<u16>().ok()?.to_ne_bytes())),
+    ),
+    ValueType(
+        "u8",
+        Some(1),
+        |buf| Some(format!("{}", u8::from_ne_bytes(buf.try_into().ok()?))),
+        |value| Some(Box::from(value.parse::<u8>().ok()?.to_ne_bytes())),
+    ),
+    ValueType(
+        "x64",
+        Some(8),
+        |buf| {
+            let buf = buf.try_into().ok()?;
+            Some(format!(
+                "{} ({})",
+                u64::from_ne_bytes(buf),
+                i64::from_ne_bytes(buf)
+            ))
+        },
+        |value| parse_any_sign(value, 8),
+    ),
+    ValueType(
+        "x32",
+        Some(4),
+        |buf| {
+            let buf = buf.try_into().ok()?;
+            Some(format!(
+                "{} ({})",
+                u32::from_ne_bytes(buf),
+                i32::from_ne_bytes(buf)
+            ))
+        },
+        |value| parse_any_sign(value, 4),
+    ),
+    ValueType(
+        "x16",
+        Some(2),
+        |buf| {
+            let buf = buf.try_into().ok()?;
+            Some(format!(
+                "{} ({})",
+                u16::from_ne_bytes(buf),
+                i16::from_ne_bytes(buf)
+            ))
+        },
+        |value| parse_any_sign(value, 2),
+    ),
+    ValueType(
+        "x8",
+        Some(1),
+        |buf| {
+            let buf = buf.try_into().ok()?;
+            Some(format!(
+                "{} ({})",
+                u8::from_ne_bytes(buf),
+                i8::from_ne_bytes(buf)
+            ))
+        },
+        |value| parse_any_sign(value, 1),
+    ),
+    ValueType(
+        "f64",
+        Some(8),
+        |buf| Some(format!("{}", f64::from_ne_bytes(buf.try_into().ok()?))),
+        |value| Some(Box::from(value.parse::<f64>().ok()?.to_ne_bytes())),
+    ),
+    ValueType(
+        "f32",
+        Some(4),
+        |buf| Some(format!("{}", f32::from_ne_bytes(buf.try_into().ok()?))),
+        |value| Some(Box::from(value.parse::<f32>().ok()?.to_ne_bytes())),
+    ),
+];
+
+fn print_hex_bytes(buf: &[u8]) -> String {
+    buf.iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn parse_hex_bytes(value: &str) -> Option<Box<[u8]>> {
+    let digits = value
+        .chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<Vec<_>>();
+
+    if digits.is_empty() || digits.len() % 2 != 0 {
+        return None;
+    }
+
+    digits
+        .chunks(2)
+        .map(|c| u8::from_str_radix(&c.iter().collect::<String>(), 16).ok())
+        .collect::<Option<Vec<_>>>()
+        .map(Vec::into_boxed_slice)
+}
+
+/// Print a 256-bit unsigned integer in hex, since there is no native type to format it with.
+fn print_u256(buf: &[u8]) -> Option<String> {
+    let mut be: [u8; 32] = buf.try_into().ok()?;
+
+    if cfg!(target_endian = "little") {
+        be.reverse();
+    }
+
+    let digits = be.iter().map(|b| format!("{:02x}", b)).collect::<String>();
+
+    match digits.trim_start_matches('0') {
+        "" => Some("0x0".into()),
+        digits => Some(format!("0x{}", digits)),
+    }
+}
+
+/// Parse a 256-bit unsigned integer, either decimal, or hex prefixed with `0x`.
+fn parse_u256(value: &str) -> Option<Box<[u8]>> {
+    let mut be = [0u8; 32];
+
+    if let Some(hex) = value.strip_prefix("0x") {
+        if hex.is_empty() || hex.len() > 64 {
+            return None;
+        }
+
+        let hex = format!("{:0>64}", hex);
+
+        for (b, c) in be.iter_mut().zip(hex.as_bytes().chunks(2)) {
+            *b = u8::from_str_radix(std::str::from_utf8(c).ok()?, 16).ok()?;
+        }
+    } else {
+        if value.is_empty() {
+            return None;
+        }
+
+        // Multiply by 10, and add every digit, from the least significant byte upwards.
+        for c in value.chars() {
+            let mut carry = c.to_digit(10)?;
+
+            for b in be.iter_mut().rev() {
+                let v = *b as u32 * 10 + carry;
+                *b = v as u8;
+                carry = v >> 8;
+            }
+
+            if carry != 0 {
+                return None;
+            }
+        }
+    }
+
+    if cfg!(target_endian = "little") {
+        be.reverse();
+    }
+
+    Some(Box::from(be))
+}
+
+/// Print a value of the type with the given name.
+///
+/// Returns `None` if the type is unknown, or the buffer does not hold a valid value.
+///
+/// # Arguments
+/// * `buf` - bytes of the value
+/// * `typename` - name of the type
+/// * `little_endian` - byte order of the value, usually that of the target
+pub fn print_value(buf: &[u8], typename: &str, little_endian: bool) -> Option<String> {
+    let value_type = ValueType::find(typename).ok()?;
+    let mut buf = buf.to_vec();
+    value_type.convert_byte_order(&mut buf, little_endian);
+    value_type.print(&buf)
+}
+
+/// Parse a value of the type with the given name.
+///
+/// # Arguments
+/// * `value` - string to parse
+/// * `typename` - name of the type
+/// * `little_endian` - byte order to produce the value in, usually that of the target
+pub fn parse_value(value: &str, typename: &str, little_endian: bool) -> Result<Box<[u8]>> {
+    let value_type = ValueType::find(typename)?;
+    let mut buf = value_type.parse(value)?;
+    value_type.convert_byte_order(&mut buf, little_endian);
+    Ok(buf)
+}
+
+/// Check whether a type is an integer.
+pub fn is_integer(typename: &str) -> bool {
+    typename.starts_with('i') || typename.starts_with('u') || typename.starts_with('x')
+}
+
+/// Get the width of an integer type in bytes.
+pub fn int_width(typename: &str) -> Option<usize> {
+    int_range(typename)?;
+    Some(typename[1..].parse::<usize>().ok()? / 8)
+}
+
+/// Get the smallest, and largest values of an integer type.
+pub fn int_range(typename: &str) -> Option<(i128, u128)> {
+    let bits = typename.get(1..)?.parse::<u32>().ok()?;
+
+    if !is_integer(typename) || !(8..=128).contains(&bits) {
+        return None;
+    }
+
+    let umax = u128::MAX >> (128 - bits);
+    let imin = i128::MIN >> (128 - bits);
+    let imax = umax >> 1;
+
+    match typename.as_bytes()[0] {
+        b'i' => Some((imin, imax)),
+        b'u' => Some((0, umax)),
+        _ => Some((imin, umax)),
+    }
+}
+
+/// Parse an integer of either sign, that fits into `size` bytes.
+fn parse_any_sign(value: &str, size: usize) -> Option<Box<[u8]>> {
+    let v = value.parse::<i128>().ok()?;
+    let (min, max) = int_range(&format!("x{}", size * 8))?;
+
+    if v < min || (v >= 0 && v as u128 > max) {
+        return None;
+    }
+
+    let bytes = v.to_ne_bytes();
+
+    if cfg!(target_endian = "little") {
+        Some(Box::from(&bytes[..size]))
+    } else {
+        Some(Box::from(&bytes[(bytes.len() - size)..]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_follow_byte_order() {
+        assert_eq!(&*parse_value("258", "i32", false).unwrap(), &[0, 0, 1, 2]);
+        assert_eq!(&*parse_value("258", "i32", true).unwrap(), &[2, 1, 0, 0]);
+        assert_eq!(
+            &*parse_value("ab", "str_utf16", false).unwrap(),
+            &[0, b'a', 0, b'b']
+        );
+        assert_eq!(&*parse_value("ab", "str", false).unwrap(), b"ab");
+
+        assert_eq!(
+            print_value(&[0, 0, 1, 2], "i32", false).as_deref(),
+            Some("258")
+        );
+        assert_eq!(
+            print_value(&1.5f64.to_be_bytes(), "f64", false).as_deref(),
+            Some("1.5")
+        );
+    }
+
+    #[test]
+    fn odd_length_utf16() {
+        assert_eq!(
+            print_value(&[b'A', 0, b'B'], "str_utf16", true).as_deref(),
+            Some("A\u{fffd}")
+        );
+
+        // Surrogate pair, followed by an unpaired high surrogate.
+        let units = [0xd83d, 0xde00, 0xd83d]
+            .iter()
+            .flat_map(|u: &u16| u.to_le_bytes())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            print_value(&units, "str_utf16", true).as_deref(),
+            Some("\u{1f600}\u{fffd}")
+        );
+    }
+}
@@ -0,0 +1,286 @@
+//! Writing typed values to memory.
+//!
+//! Values are parsed with the types of the `value_type` module, and written either once, or
+//! continuously on a background thread, which keeps a value in place while the target keeps
+//! overwriting it.
+
+use crate::error::{Error, Result};
+use crate::task::CancelToken;
+use crate::value_type::{int_width, parse_value, ValueType};
+use memflow::prelude::v1::*;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Value written by `write_pattern`, evaluated on every write.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WritePattern {
+    /// The same value every time.
+    Constant(Box<[u8]>),
+    /// Integer starting at a value, and increased by a step every time. Wraps around at the width
+    /// of the type.
+    IntRamp(i128, i128, usize),
+    /// Floating point number starting at a value, and increased by a step every time.
+    FloatRamp(f64, f64, bool),
+    /// List of values gone through in a loop.
+    Cycle(Vec<Box<[u8]>>),
+}
+
+impl WritePattern {
+    /// Parse a write pattern of the given type.
+    ///
+    /// The input is either a single value, `ramp {start} {step}` for numeric types, or
+    /// `cycle {value} {value}...`.
+    ///
+    /// # Arguments
+    /// * `input` - pattern to parse
+    /// * `typename` - name of the type of the values
+    /// * `little_endian` - byte order to parse values in, usually that of the target
+    pub fn parse(input: &str, typename: &str, little_endian: bool) -> Result<Self> {
+        let mut words = input.split_whitespace();
+
+        match words.next() {
+            Some("ramp") => {
+                let (start, step) = match (words.next(), words.next()) {
+                    (Some(start), Some(step)) => (start, step),
+                    _ => {
+                        return Err(Error::InvalidArgument(
+                            "expected ramp {start} {step}".into(),
+                        ))
+                    }
+                };
+
+                let invalid = |value: &str| Error::InvalidValue {
+                    typename: typename.into(),
+                    value: value.into(),
+                };
+
+                let ValueType(name, _, _, _) = ValueType::find(typename)?;
+
+                if name.starts_with('f') {
+                    Ok(WritePattern::FloatRamp(
+                        start.parse().map_err(|_| invalid(start))?,
+                        step.parse().map_err(|_| invalid(step))?,
+                        *name == "f32",
+                    ))
+                } else if let Some(size) = int_width(name) {
+                    Ok(WritePattern::IntRamp(
+                        start.parse().map_err(|_| invalid(start))?,
+                        step.parse().map_err(|_| invalid(step))?,
+                        size,
+                    ))
+                } else {
+                    Err(Error::InvalidArgument(format!(
+                        "ramps require a numeric type, not `{}`",
+                        typename
+                    )))
+                }
+            }
+            Some("cycle") => {
+                let values = words
+                    .map(|w| parse_value(w, typename, little_endian))
+                    .collect::<Result<Vec<_>>>()?;
+
+                if values.is_empty() {
+                    return Err(Error::InvalidArgument(
+                        "expected cycle {value} {value}...".into(),
+                    ));
+                }
+
+                Ok(WritePattern::Cycle(values))
+            }
+            _ => Ok(WritePattern::Constant(parse_value(
+                input,
+                typename,
+                little_endian,
+            )?)),
+        }
+    }
+
+    /// Get the value to write on the given tick.
+    ///
+    /// Constant, and cycled values are returned as parsed, while ramps are generated in the
+    /// given byte order, which should match the one the pattern was parsed in.
+    ///
+    /// # Arguments
+    /// * `tick` - number of writes done before this one
+    /// * `little_endian` - byte order to generate ramps in
+    pub fn value(&self, tick: usize, little_endian: bool) -> Box<[u8]> {
+        match self {
+            WritePattern::Constant(v) => v.clone(),
+            WritePattern::IntRamp(start, step, size) => {
+                let v = start.wrapping_add(step.wrapping_mul(tick as i128));
+                if little_endian {
+                    Box::from(&v.to_le_bytes()[..*size])
+                } else {
+                    Box::from(&v.to_be_bytes()[(16 - size)..])
+                }
+            }
+            WritePattern::FloatRamp(start, step, is_f32) => {
+                let v = start + step * tick as f64;
+                match (*is_f32, little_endian) {
+                    (true, true) => Box::from((v as f32).to_le_bytes()),
+                    (true, false) => Box::from((v as f32).to_be_bytes()),
+                    (false, true) => Box::from(v.to_le_bytes()),
+                    (false, false) => Box::from(v.to_be_bytes()),
+                }
+            }
+            WritePattern::Cycle(values) => values[tick % values.len()].clone(),
+        }
+    }
+}
+
+/// Parse a value, and write it to an address.
+///
+/// # Arguments
+/// * `mem` - memory to write to
+/// * `addr` - address to write the value to
+/// * `typename` - name of the type of the value
+/// * `value` - value to write
+pub fn write_typed(
+    mem: &mut impl MemoryView,
+    addr: Address,
+    typename: &str,
+    value: &str,
+) -> Result<()> {
+    write_typed_batch(mem, &[addr], typename, value)
+}
+
+/// Parse a value once, and write it to multiple addresses.
+///
+/// # Arguments
+/// * `mem` - memory to write to
+/// * `addrs` - addresses to write the value to
+/// * `typename` - name of the type of the value
+/// * `value` - value to write
+pub fn write_typed_batch(
+    mem: &mut impl MemoryView,
+    addrs: &[Address],
+    typename: &str,
+    value: &str,
+) -> Result<()> {
+    let buf = parse_value(value, typename, mem.metadata().little_endian)?;
+
+    for &addr in addrs {
+        mem.write_raw(addr, &buf).data_part()?;
+    }
+
+    Ok(())
+}
+
+/// Write a pattern to multiple addresses, until cancelled.
+///
+/// The pattern is written at least once, and advances once per interval. This blocks the calling
+/// thread; use `spawn_write_pattern` to write in the background instead.
+///
+/// # Arguments
+/// * `mem` - memory to write to
+/// * `addrs` - addresses to write the pattern to
+/// * `pattern` - values to write
+/// * `interval` - time between writes. Zero writes as often as possible
+/// * `cancel` - token to stop writing with
+pub fn write_pattern(
+    mem: &mut impl MemoryView,
+    addrs: &[Address],
+    pattern: &WritePattern,
+    interval: Duration,
+    cancel: &CancelToken,
+) -> Result<()> {
+    write_pattern_with(mem, addrs, pattern, || {
+        if cancel.is_cancelled() {
+            return true;
+        }
+
+        if !interval.is_zero() {
+            thread::sleep(interval);
+        }
+
+        false
+    })
+}
+
+/// Write a pattern to multiple addresses, until `wait` says to stop.
+///
+/// This works like `write_pattern`, but instead of sleeping for an interval, `wait` is called
+/// between writes. It blocks for as long as the next write should be delayed, and returns whether
+/// to stop writing, for instance, once a line of input is entered.
+///
+/// # Arguments
+/// * `mem` - memory to write to
+/// * `addrs` - addresses to write the pattern to
+/// * `pattern` - values to write
+/// * `wait` - function to wait between writes with, returning true to stop
+pub fn write_pattern_with(
+    mem: &mut impl MemoryView,
+    addrs: &[Address],
+    pattern: &WritePattern,
+    mut wait: impl FnMut() -> bool,
+) -> Result<()> {
+    let little_endian = mem.metadata().little_endian;
+
+    for tick in 0.. {
+        let v = pattern.value(tick, little_endian);
+
+        for &addr in addrs {
+            mem.write_raw(addr, &v).data_part()?;
+        }
+
+        if wait() {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// Write a pattern to multiple addresses on a background thread.
+///
+/// Writing continues until the returned handle is stopped, or a write fails.
+///
+/// # Arguments
+/// * `mem` - memory to write to
+/// * `addrs` - addresses to write the pattern to
+/// * `pattern` - values to write
+/// * `interval` - time between writes. Zero writes as often as possible
+pub fn spawn_write_pattern<T: MemoryView + Send + 'static>(
+    mut mem: T,
+    addrs: Vec<Address>,
+    pattern: WritePattern,
+    interval: Duration,
+) -> WriteHandle<T> {
+    let cancel = CancelToken::default();
+    let token = cancel.clone();
+
+    let handle = thread::spawn(move || {
+        let ret = write_pattern(&mut mem, &addrs, &pattern, interval, &token);
+        (mem, ret)
+    });
+
+    WriteHandle { cancel, handle }
+}
+
+/// Handle to a continuous write running on a background thread.
+///
+/// Returned by `spawn_write_pattern`, which takes ownership of the memory object, and hands it
+/// back once the write is stopped.
+pub struct WriteHandle<T> {
+    cancel: CancelToken,
+    handle: JoinHandle<(T, Result<()>)>,
+}
+
+impl<T> WriteHandle<T> {
+    /// Check whether writing has stopped on its own, which happens if a write fails.
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+
+    /// Stop writing, and wait for the thread to finish.
+    ///
+    /// If the thread panicked, the panic is resumed on the calling thread.
+    pub fn stop(self) -> (T, Result<()>) {
+        self.cancel.cancel();
+        self.handle
+            .join()
+            .unwrap_or_else(|e| std::panic::resume_unwind(e))
+    }
+}